@@ -1,9 +1,12 @@
+use std::hash::{Hash, Hasher};
+
 use arrow2::{
     array::{
-        growable::make_growable, Array, FixedSizeListArray, ListArray, StructArray, UnionArray,
+        growable::make_growable, Array, BinaryArray, BooleanArray, FixedSizeBinaryArray,
+        FixedSizeListArray, ListArray, PrimitiveArray, StructArray, UnionArray, Utf8Array,
     },
     bitmap::Bitmap,
-    datatypes::{DataType, Field, UnionMode},
+    datatypes::{DataType, Field, PhysicalType, PrimitiveType, UnionMode},
     offset::Offsets,
 };
 use itertools::Itertools;
@@ -25,6 +28,13 @@ pub trait ArrayExt: Array {
     ///
     /// Nested types are expanded and cleaned recursively
     fn clean_for_polars(&self) -> Box<dyn Array>;
+
+    /// Hashes the contents of the array, ignoring whatever lies behind null slots.
+    ///
+    /// Arrays that are equal always have the same hash, the converse is obviously not true.
+    ///
+    /// Returns `None` if the array contains a datatype that cannot be hashed (e.g. dictionaries).
+    fn content_hash(&self) -> Option<u64>;
 }
 
 impl ArrayExt for dyn Array {
@@ -220,6 +230,113 @@ impl ArrayExt for dyn Array {
             _ => self.to_boxed(),
         }
     }
+
+    fn content_hash(&self) -> Option<u64> {
+        let mut hasher = ahash::AHasher::default();
+        hash_array(self, &mut hasher)?;
+        Some(hasher.finish())
+    }
+}
+
+fn downcast<T: 'static>(array: &dyn Array) -> &T {
+    array.as_any().downcast_ref::<T>().unwrap()
+}
+
+/// Recursively feeds the contents of `array` to `state`, see [`ArrayExt::content_hash`].
+fn hash_array<H: Hasher>(array: &dyn Array, state: &mut H) -> Option<()> {
+    array.len().hash(state);
+
+    macro_rules! hash_primitive {
+        ($T:ty) => {
+            for value in downcast::<PrimitiveArray<$T>>(array) {
+                value.map(|value| value.to_le_bytes()).hash(state);
+            }
+        };
+    }
+
+    macro_rules! hash_all {
+        ($A:ty) => {
+            for value in downcast::<$A>(array) {
+                value.hash(state);
+            }
+        };
+    }
+
+    match array.data_type().to_physical_type() {
+        PhysicalType::Null => {}
+        PhysicalType::Boolean => hash_all!(BooleanArray),
+        PhysicalType::Primitive(primitive) => match primitive {
+            PrimitiveType::Int8 => hash_primitive!(i8),
+            PrimitiveType::Int16 => hash_primitive!(i16),
+            PrimitiveType::Int32 => hash_primitive!(i32),
+            PrimitiveType::Int64 => hash_primitive!(i64),
+            PrimitiveType::UInt8 => hash_primitive!(u8),
+            PrimitiveType::UInt16 => hash_primitive!(u16),
+            PrimitiveType::UInt32 => hash_primitive!(u32),
+            PrimitiveType::UInt64 => hash_primitive!(u64),
+            PrimitiveType::Float32 => hash_primitive!(f32),
+            PrimitiveType::Float64 => hash_primitive!(f64),
+            _ => return None,
+        },
+        PhysicalType::Utf8 => hash_all!(Utf8Array<i32>),
+        PhysicalType::LargeUtf8 => hash_all!(Utf8Array<i64>),
+        PhysicalType::Binary => hash_all!(BinaryArray<i32>),
+        PhysicalType::LargeBinary => hash_all!(BinaryArray<i64>),
+        PhysicalType::FixedSizeBinary => hash_all!(FixedSizeBinaryArray),
+        PhysicalType::List => {
+            let list = downcast::<ListArray<i32>>(array);
+            for i in 0..list.len() {
+                let is_valid = list.is_valid(i);
+                is_valid.hash(state);
+                if is_valid {
+                    hash_array(&*list.value(i), state)?;
+                }
+            }
+        }
+        PhysicalType::LargeList => {
+            let list = downcast::<ListArray<i64>>(array);
+            for i in 0..list.len() {
+                let is_valid = list.is_valid(i);
+                is_valid.hash(state);
+                if is_valid {
+                    hash_array(&*list.value(i), state)?;
+                }
+            }
+        }
+        PhysicalType::FixedSizeList => {
+            let list = downcast::<FixedSizeListArray>(array);
+            for i in 0..list.len() {
+                let is_valid = list.is_valid(i);
+                is_valid.hash(state);
+                if is_valid {
+                    hash_array(&*list.value(i), state)?;
+                }
+            }
+        }
+        PhysicalType::Struct => {
+            let array = downcast::<StructArray>(array);
+            for i in 0..array.len() {
+                array.is_valid(i).hash(state);
+            }
+            for values in array.values() {
+                hash_array(&**values, state)?;
+            }
+        }
+        PhysicalType::Union => {
+            let array = downcast::<UnionArray>(array);
+            array.types().as_slice().hash(state);
+            array
+                .offsets()
+                .map(|offsets| offsets.as_slice())
+                .hash(state);
+            for values in array.fields() {
+                hash_array(&**values, state)?;
+            }
+        }
+        PhysicalType::Map | PhysicalType::Dictionary(_) => return None,
+    }
+
+    Some(())
 }
 
 #[test]
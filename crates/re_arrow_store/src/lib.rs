@@ -7,8 +7,11 @@
 //!
 //! * See [`DataStore`] for an overview of the core data structures.
 //! * See [`DataStore::latest_at`] and [`DataStore::range`] for the documentation of the public
-//!   read APIs.
+//!   read APIs, and [`DataStore::range_aggregate`] for downsampled range queries.
 //! * See [`DataStore::insert_row`] for the documentation of the public write APIs.
+//! * See [`DataStore::enable_value_index`] for filtering rows based on their values.
+//! * See [`SharedDataStore`] for querying a store while it's being written to from another
+//!   thread.
 //!
 //! ## Feature flags
 #![doc = document_features::document_features!()]
@@ -16,11 +19,18 @@
 
 mod arrow_util;
 mod store;
+mod store_aggregate;
 mod store_format;
 mod store_gc;
 mod store_read;
 mod store_sanity;
+mod store_shared;
+mod store_snapshot;
+mod store_spill;
 mod store_stats;
+mod store_subscriber;
+mod store_subtree;
+mod store_value_index;
 mod store_write;
 
 #[cfg(feature = "polars")]
@@ -36,11 +46,27 @@ pub use self::arrow_util::ArrayExt;
 pub use self::store::{
     DataStore, DataStoreConfig, IndexBucket, IndexRowNr, IndexTable, RowIndex, RowIndexKind,
 };
-pub use self::store_gc::GarbageCollectionTarget;
-pub use self::store_read::{LatestAtQuery, RangeQuery};
+pub use self::store_aggregate::{
+    AggregatedBucket, AggregationError, AggregationResult, RangeAggregationQuery,
+};
+pub use self::store_gc::{GarbageCollectionTarget, RetentionRule};
+pub use self::store_read::{InterpolationSamples, LatestAtQuery, RangeQuery};
+pub use self::store_shared::{DataStoreSnapshot, DataStoreWriteGuard, SharedDataStore};
+pub use self::store_snapshot::{SnapshotError, SnapshotResult};
+pub use self::store_spill::{SpillError, SpillResult};
 pub use self::store_stats::DataStoreStats;
+pub use self::store_subscriber::{
+    StoreEvent, StoreEventKind, StoreSubscriber, StoreSubscriberHandle,
+};
+pub use self::store_subtree::EntityPathPrefix;
+pub use self::store_value_index::{
+    ComponentValue, ValueFilter, ValueIndexError, ValueIndexResult, ValuePredicate,
+};
 pub use self::store_write::{WriteError, WriteResult};
 
+#[cfg(not(target_arch = "wasm32"))]
+pub use self::store_shared::DataStoreIngester;
+
 pub(crate) use self::store::{
     ComponentBucket, ComponentTable, IndexBucketIndices, PersistentComponentTable,
     PersistentIndexTable, SecondaryIndex, TimeIndex,
@@ -11,6 +11,10 @@ use re_log_types::{
     ComponentName, EntityPath, EntityPathHash, MsgId, TimeInt, TimePoint, TimeRange, Timeline,
 };
 
+use crate::store_spill::{SpillTarget, SpilledData};
+use crate::store_value_index::ValueIndex;
+use crate::{StoreSubscriber, StoreSubscriberHandle};
+
 // --- Indices & offsets ---
 
 /// A vector of times. Our primary column, always densely filled.
@@ -52,7 +56,7 @@ pub enum RowIndexKind {
 /// associated with a component name.
 ///
 /// See [`DataStore::latest_at`], [`DataStore::range`] & [`DataStore::get`].
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct RowIndex(pub(crate) NonZeroU64);
 
 impl RowIndex {
@@ -164,6 +168,19 @@ pub struct DataStoreConfig {
 
     /// Should soon-to-be inactive buckets be compacted before being archived?
     pub enable_compaction: bool,
+
+    /// If enabled, incoming cells are hashed and compared to the ones previously inserted for
+    /// the same component: identical payloads are then stored only once, and shared by all the
+    /// rows that reference them.
+    ///
+    /// This is a big win when the same large data (meshes, tensors, annotation contexts...) gets
+    /// logged over and over again, at the cost of hashing every single incoming cell.
+    ///
+    /// Only data in active buckets can be shared: compaction makes a deep copy of the data
+    /// when archiving a bucket.
+    ///
+    /// See [`crate::DataStoreStats::total_deduplicated_size_bytes`].
+    pub enable_deduplication: bool,
 }
 
 impl Default for DataStoreConfig {
@@ -184,6 +201,7 @@ impl DataStoreConfig {
         //
         // This has no noticeable impact on performance.
         enable_compaction: false,
+        enable_deduplication: false,
     };
 }
 
@@ -255,6 +273,31 @@ pub struct DataStore {
 
     /// Monotonically increasing ID for GCs.
     pub(crate) gc_id: u64,
+
+    /// The number of index rows dropped by the garbage collector since the creation of the store.
+    pub(crate) reclaimed_index_rows: u64,
+
+    /// The size of the index data dropped by the garbage collector since the creation of the
+    /// store, in bytes.
+    pub(crate) reclaimed_index_size_bytes: u64,
+
+    /// Everyone that needs to be told about changes to the contents of the store.
+    ///
+    /// See [`Self::register_subscriber`].
+    pub(crate) subscribers: Vec<(StoreSubscriberHandle, Box<dyn StoreSubscriber>)>,
+
+    /// Monotonically increasing ID for subscribers.
+    pub(crate) subscriber_id: u64,
+
+    /// Where to spill archived component buckets, if anywhere.
+    ///
+    /// See [`Self::enable_spilling`].
+    pub(crate) spill_target: Option<SpillTarget>,
+
+    /// The value indices of the components that have one.
+    ///
+    /// See [`Self::enable_value_index`].
+    pub(crate) value_indices: IntMap<ComponentName, ValueIndex>,
 }
 
 impl DataStore {
@@ -272,6 +315,12 @@ impl DataStore {
             insert_id: 0,
             query_id: AtomicU64::new(0),
             gc_id: 0,
+            reclaimed_index_rows: 0,
+            reclaimed_index_size_bytes: 0,
+            subscribers: Vec::new(),
+            subscriber_id: 0,
+            spill_target: None,
+            value_indices: Default::default(),
         }
     }
 
@@ -505,6 +554,12 @@ pub struct IndexBucketIndices {
     // All secondary indices are guaranteed to follow the same sort order and be the same length.
     pub(crate) times: TimeIndex,
 
+    /// The ID of the row that each entry belongs to.
+    ///
+    /// Follows the same sort order as, and is always the same length as, [`Self::times`].
+    /// This is how the garbage collector knows which rows it removes, see [`crate::StoreEvent`].
+    pub(crate) msg_ids: Vec<MsgId>,
+
     /// All secondary indices for this bucket (i.e. everything but time).
     ///
     /// One index per component: new components (and as such, new indices) can be added at any
@@ -520,6 +575,7 @@ impl Default for IndexBucketIndices {
             is_sorted: true,
             time_range: TimeRange::new(i64::MAX.into(), i64::MIN.into()),
             times: Default::default(),
+            msg_ids: Default::default(),
             indices: Default::default(),
         }
     }
@@ -578,6 +634,11 @@ pub struct PersistentComponentTable {
     // TODO(cmc): compact timeless tables once in a while
     pub(crate) chunks: Vec<Box<dyn Array>>,
 
+    /// Maps the content hash of every deduplicated cell to the row that first stored it.
+    ///
+    /// Only maintained if [`DataStoreConfig::enable_deduplication`] is set.
+    pub(crate) dedup_index: IntMap<u64, u64>,
+
     /// The total number of rows present in this bucket, across all chunks.
     pub(crate) total_rows: u64,
 
@@ -586,6 +647,10 @@ pub struct PersistentComponentTable {
     /// Accurately computing the size of arrow arrays is surprisingly costly, which is why we
     /// cache this.
     pub(crate) total_size_bytes: u64,
+
+    /// How many of [`Self::total_size_bytes`] are shared with other rows rather than actually
+    /// allocated, see [`DataStoreConfig::enable_deduplication`].
+    pub(crate) deduplicated_size_bytes: u64,
 }
 
 // --- Components ---
@@ -684,6 +749,12 @@ pub struct ComponentTable {
     /// - pushing to the back for insertions
     /// - binary search for queries
     pub(crate) buckets: VecDeque<ComponentBucket>,
+
+    /// Maps the content hash of every deduplicated cell to the latest row that stored it.
+    ///
+    /// Only maintained if [`DataStoreConfig::enable_deduplication`] is set.
+    /// Entries can outlive the rows they point to, until the next garbage collection.
+    pub(crate) dedup_index: IntMap<u64, u64>,
 }
 
 /// A `ComponentBucket` holds a size-delimited (data size) chunk of a [`ComponentTable`].
@@ -716,6 +787,12 @@ pub struct ComponentBucket {
     /// These are only used for garbage collection.
     pub(crate) time_ranges: HashMap<Timeline, TimeRange>,
 
+    /// The number of rows in this bucket that aren't referenced by any index anymore, as they
+    /// were dropped by the garbage collector.
+    ///
+    /// The bucket itself gets dropped once all of its rows are dead.
+    pub(crate) dead_rows: u64,
+
     /// All the data for this bucket: many rows of a single column.
     ///
     /// Each chunk is a list of arrays of structs, i.e. `ListArray<StructArray>`:
@@ -740,8 +817,15 @@ pub struct ComponentBucket {
     /// As a result, chunks always contain one and only one row's worth of data, at least until
     /// the bucket is archived and compacted.
     /// See also #589.
+    ///
+    /// Empty if the bucket was spilled to disk, see `Self::spilled`.
     pub(crate) chunks: Vec<Box<dyn Array>>,
 
+    /// Where the data of this bucket lives once it's been spilled to disk, if it has.
+    ///
+    /// Only archived buckets ever get spilled, see [`DataStore::enable_spilling`].
+    pub(crate) spilled: Option<SpilledData>,
+
     /// The total number of rows present in this bucket, across all chunks.
     pub(crate) total_rows: u64,
 
@@ -749,5 +833,14 @@ pub struct ComponentBucket {
     ///
     /// Accurately computing the size of arrow arrays is surprisingly costly, which is why we
     /// cache this.
+    ///
+    /// This is the size of the data itself, whether it lives in memory or has been spilled to
+    /// disk.
     pub(crate) total_size_bytes: u64,
+
+    /// How many of [`Self::total_size_bytes`] are shared with other rows rather than actually
+    /// allocated, see [`DataStoreConfig::enable_deduplication`].
+    ///
+    /// Always zero for archived buckets, as compaction copies all the data.
+    pub(crate) deduplicated_size_bytes: u64,
 }
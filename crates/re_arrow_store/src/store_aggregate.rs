@@ -0,0 +1,328 @@
+use std::{collections::hash_map::Entry, sync::atomic::Ordering};
+
+use arrow2::{
+    array::{Array, ListArray, PrimitiveArray},
+    datatypes::DataType,
+};
+use nohash_hasher::IntMap;
+use re_log::trace;
+use re_log_types::{ComponentName, EntityPath, TimeInt, TimeRange, Timeline};
+
+use crate::{ComponentBucket, ComponentTable, DataStore, IndexBucketIndices, RangeQuery, RowIndex};
+
+// --- Queries ---
+
+/// A query over a time range, for a given timeline, whose results get aggregated into buckets of
+/// `resolution` time units.
+///
+/// Buckets are aligned on multiples of the resolution, so that the same data always ends up in
+/// the same buckets no matter the exact bounds of the query (e.g. while panning a plot).
+///
+/// Unlike [`RangeQuery`], this doesn't yield the latest data before the start of the time range,
+/// nor any timeless data.
+#[derive(Clone)]
+pub struct RangeAggregationQuery {
+    pub timeline: Timeline,
+    pub range: TimeRange,
+
+    /// The width of each bucket, in time units of the `timeline`.
+    ///
+    /// Anything smaller than `1` is treated as `1`.
+    pub resolution: TimeInt,
+}
+
+impl std::fmt::Debug for RangeAggregationQuery {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_fmt(format_args!(
+            "<aggregating from {} to {} (all inclusive) on {:?}, {} per bucket>",
+            self.timeline.typ().format(self.range.min),
+            self.timeline.typ().format(self.range.max),
+            self.timeline.name(),
+            self.resolution.as_i64(),
+        ))
+    }
+}
+
+impl RangeAggregationQuery {
+    pub const fn new(timeline: Timeline, range: TimeRange, resolution: TimeInt) -> Self {
+        Self {
+            timeline,
+            range,
+            resolution,
+        }
+    }
+
+    /// Aggregates the results of an existing [`RangeQuery`].
+    pub fn from_range(query: &RangeQuery, resolution: TimeInt) -> Self {
+        Self::new(query.timeline, query.range, resolution)
+    }
+}
+
+// --- Results ---
+
+#[derive(thiserror::Error, Debug)]
+pub enum AggregationError {
+    #[error(
+        "Only components made of primitive values can be aggregated, {component} is {datatype:?}"
+    )]
+    NotPrimitive {
+        component: ComponentName,
+        datatype: DataType,
+    },
+}
+
+pub type AggregationResult<T> = ::std::result::Result<T, AggregationError>;
+
+/// The aggregated values of a component within a single time bucket.
+///
+/// All instances of all rows within the bucket are taken into account. Nulls and NaNs are
+/// ignored.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AggregatedBucket {
+    /// The time range covered by this bucket (all inclusive).
+    pub time_range: TimeRange,
+
+    /// The number of values aggregated into this bucket.
+    pub count: u64,
+
+    pub min: f64,
+    pub max: f64,
+    pub sum: f64,
+}
+
+impl AggregatedBucket {
+    fn new(time_range: TimeRange) -> Self {
+        Self {
+            time_range,
+            count: 0,
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+            sum: 0.0,
+        }
+    }
+
+    #[inline]
+    fn add(&mut self, value: f64) {
+        if value.is_nan() {
+            return;
+        }
+        self.count += 1;
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+        self.sum += value;
+    }
+
+    /// The average of all values in this bucket.
+    #[inline]
+    pub fn mean(&self) -> f64 {
+        self.sum / self.count as f64
+    }
+}
+
+// --- Data store ---
+
+impl DataStore {
+    /// Aggregates the values of a `component` for a given `ent_path` over a time range, into
+    /// buckets of `query.resolution` time units.
+    ///
+    /// This is meant for plotting large series of primitive components (e.g. scalars): the
+    /// values are read straight from the underlying arrow arrays, with no deserialization nor
+    /// joins involved.
+    ///
+    /// Only buckets that contain at least one value are returned, in ascending time order.
+    ///
+    /// Fails if the data for `component` isn't made of primitive values, see
+    /// [`AggregationError::NotPrimitive`].
+    pub fn range_aggregate(
+        &self,
+        query: &RangeAggregationQuery,
+        ent_path: &EntityPath,
+        component: ComponentName,
+    ) -> AggregationResult<Vec<AggregatedBucket>> {
+        crate::profile_function!();
+
+        // TODO(cmc): kind & query_id need to somehow propagate through the span system.
+        self.query_id.fetch_add(1, Ordering::Relaxed);
+
+        trace!(
+            kind = "range_aggregate",
+            id = self.query_id.load(Ordering::Relaxed),
+            query = ?query,
+            entity = %ent_path,
+            %component,
+            "query started..."
+        );
+
+        let Some(index) = self.indices.get(&(query.timeline, ent_path.hash())) else {
+            return Ok(Vec::new());
+        };
+        let Some(table) = self.components.get(&component) else {
+            return Ok(Vec::new());
+        };
+
+        let resolution = query.resolution.as_i64().max(1);
+
+        let mut buckets: Vec<AggregatedBucket> = Vec::new();
+        let mut values = ComponentValues::new(table);
+
+        // We need to find the _indexing time_ that corresponds to this time range's minimum bound!
+        let (time_range_min, _) = index.find_bucket(query.range.min);
+        for (_, index_bucket) in index.range_buckets(time_range_min..=query.range.max) {
+            index_bucket.sort_indices_if_needed();
+
+            let IndexBucketIndices { times, indices, .. } = &*index_bucket.indices.read();
+            let Some(row_indices) = indices.get(&component) else { continue; };
+
+            // Index buckets are sorted by time, so the rows within the time range are a single
+            // slice of each index.
+            let start = times.partition_point(|t| *t < query.range.min.as_i64());
+            let end = times.partition_point(|t| *t <= query.range.max.as_i64());
+
+            for (&time, row_idx) in times[start..end].iter().zip(&row_indices[start..end]) {
+                let Some(row_idx) = row_idx else { continue; };
+                let Some(row_values) = values.row(*row_idx)? else { continue; };
+
+                let bucket_start = time.div_euclid(resolution).saturating_mul(resolution);
+                let bucket = match buckets.last_mut() {
+                    Some(bucket) if bucket.time_range.min.as_i64() == bucket_start => bucket,
+                    _ => {
+                        let bucket_end = bucket_start.saturating_add(resolution - 1);
+                        buckets.push(AggregatedBucket::new(TimeRange::new(
+                            bucket_start.into(),
+                            bucket_end.into(),
+                        )));
+                        buckets.last_mut().unwrap()
+                    }
+                };
+
+                for &value in row_values {
+                    bucket.add(value);
+                }
+            }
+        }
+
+        // Buckets that only ever saw nulls and NaNs.
+        buckets.retain(|bucket| bucket.count > 0);
+
+        trace!(
+            kind = "range_aggregate",
+            id = self.query_id.load(Ordering::Relaxed),
+            query = ?query,
+            entity = %ent_path,
+            %component,
+            nb_buckets = buckets.len(),
+            "query done"
+        );
+
+        Ok(buckets)
+    }
+}
+
+/// The data of a component table, converted to `f64`s one component bucket at a time.
+///
+/// Each component bucket is downcast and converted only once, no matter how many of its rows
+/// end up being aggregated.
+struct ComponentValues<'a> {
+    table: &'a ComponentTable,
+
+    /// Keyed by the row offset of their component bucket.
+    buckets: IntMap<u64, BucketValues>,
+}
+
+/// The values of all the rows of a single component bucket.
+struct BucketValues {
+    /// Where the values of each row start in `values`, followed by where the last one ends.
+    row_starts: Vec<usize>,
+
+    /// Nulls are NaNs, which get ignored when aggregating.
+    values: Vec<f64>,
+}
+
+impl<'a> ComponentValues<'a> {
+    fn new(table: &'a ComponentTable) -> Self {
+        Self {
+            table,
+            buckets: Default::default(),
+        }
+    }
+
+    /// The values of a single row, or `None` if it doesn't exist (anymore).
+    fn row(&mut self, row_idx: RowIndex) -> AggregationResult<Option<&[f64]>> {
+        let row_idx = row_idx.as_u64();
+
+        // See `ComponentTable::get`.
+        let bucket_nr = self
+            .table
+            .buckets
+            .partition_point(|bucket| row_idx >= bucket.row_offset);
+        let Some(bucket) = bucket_nr
+            .checked_sub(1)
+            .and_then(|bucket_nr| self.table.buckets.get(bucket_nr)) else {
+            return Ok(None);
+        };
+
+        let values = match self.buckets.entry(bucket.row_offset) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(BucketValues::new(self.table, bucket)?),
+        };
+
+        let row = (row_idx - bucket.row_offset) as usize;
+        Ok(values
+            .row_starts
+            .get(row..row + 2)
+            .map(|range| &values.values[range[0]..range[1]]))
+    }
+}
+
+impl BucketValues {
+    fn new(table: &ComponentTable, bucket: &ComponentBucket) -> AggregationResult<Self> {
+        crate::profile_function!();
+
+        let mut row_starts = vec![0];
+        let mut values = Vec::new();
+
+        // Archived buckets hold all their rows in a single chunk, the active one has a chunk
+        // per row.
+        for chunk in bucket.data() {
+            let list = chunk.as_any().downcast_ref::<ListArray<i32>>().unwrap();
+            let chunk_values =
+                to_f64s(list.values().as_ref()).ok_or_else(|| AggregationError::NotPrimitive {
+                    component: table.name,
+                    datatype: list.values().data_type().clone(),
+                })?;
+
+            for row in 0..list.len() {
+                let (start, end) = list.offsets().start_end(row);
+                values.extend_from_slice(&chunk_values[start..end]);
+                row_starts.push(values.len());
+            }
+        }
+
+        Ok(Self { row_starts, values })
+    }
+}
+
+/// Converts every value of `array` to a `f64`, nulls included as NaNs.
+///
+/// Returns `None` if `array` isn't made of primitive values.
+#[allow(clippy::unnecessary_cast)] // f64 as f64
+fn to_f64s(array: &dyn Array) -> Option<Vec<f64>> {
+    macro_rules! try_primitive {
+        ($($ty:ty),*) => {
+            $(
+                if let Some(array) = array.as_any().downcast_ref::<PrimitiveArray<$ty>>() {
+                    return Some(
+                        array
+                            .iter()
+                            .map(|value| value.map_or(f64::NAN, |value| *value as f64))
+                            .collect(),
+                    );
+                }
+            )*
+        };
+    }
+
+    try_primitive!(f64, f32, i64, i32, i16, i8, u64, u32, u16, u8);
+
+    None
+}
@@ -1,4 +1,5 @@
 use arrow2::array::UInt64Array;
+use itertools::Itertools;
 use re_format::{arrow, format_bytes, format_number};
 
 use crate::{
@@ -40,6 +41,12 @@ impl std::fmt::Display for DataStore {
             insert_id: _,
             query_id: _,
             gc_id: _,
+            reclaimed_index_rows: _,
+            reclaimed_index_size_bytes: _,
+            subscribers: _,
+            subscriber_id: _,
+            spill_target: _,
+            value_indices,
         } = self;
 
         f.write_str("DataStore {\n")?;
@@ -49,6 +56,13 @@ impl std::fmt::Display for DataStore {
             format!("cluster_key: {cluster_key:?}\n"),
         ))?;
         f.write_str(&indent::indent_all_by(4, format!("config: {config:?}\n")))?;
+        f.write_str(&indent::indent_all_by(
+            4,
+            format!(
+                "value indices: {:?}\n",
+                value_indices.keys().sorted().collect::<Vec<_>>()
+            ),
+        ))?;
 
         {
             f.write_str(&indent::indent_all_by(
@@ -273,8 +287,10 @@ impl std::fmt::Display for PersistentComponentTable {
             name,
             datatype,
             chunks,
+            dedup_index: _,
             total_rows,
             total_size_bytes,
+            deduplicated_size_bytes,
         } = self;
 
         f.write_fmt(format_args!("name: {name}\n"))?;
@@ -286,8 +302,9 @@ impl std::fmt::Display for PersistentComponentTable {
         }
 
         f.write_fmt(format_args!(
-            "size: {} across {} total rows\n",
+            "size: {} ({} deduplicated) across {} total rows\n",
             format_bytes(*total_size_bytes as _),
+            format_bytes(*deduplicated_size_bytes as _),
             format_number(*total_rows as _),
         ))?;
 
@@ -313,6 +330,7 @@ impl std::fmt::Display for ComponentTable {
             name,
             datatype,
             buckets,
+            dedup_index: _,
         } = self;
 
         f.write_fmt(format_args!("name: {name}\n"))?;
@@ -324,9 +342,10 @@ impl std::fmt::Display for ComponentTable {
         }
 
         f.write_fmt(format_args!(
-            "size: {} buckets for a total of {} across {} total rows\n",
+            "size: {} buckets for a total of {} ({} deduplicated) across {} total rows\n",
             self.buckets.len(),
             format_bytes(self.total_size_bytes() as _),
+            format_bytes(self.deduplicated_size_bytes() as _),
             format_number(self.total_rows() as _),
         ))?;
         f.write_str("buckets: [\n")?;
@@ -357,16 +376,17 @@ impl std::fmt::Display for ComponentBucket {
             // - all buckets that follow are lazily instantiated when data get inserted
             //
             // TODO(#439): is that still true with deletion?
-            // TODO(#589): support for non-unit-length chunks
             self.row_offset
                 + self
-                    .chunks
-                    .len()
+                    .total_rows()
                     .checked_sub(1)
-                    .expect("buckets are never empty") as u64,
+                    .expect("buckets are never empty"),
         ))?;
 
         f.write_fmt(format_args!("archived: {}\n", self.archived))?;
+        if let Some(spilled) = &self.spilled {
+            f.write_fmt(format_args!("spilled to: {}\n", spilled.path().display()))?;
+        }
         f.write_str("time ranges:\n")?;
         for (timeline, time_range) in &self.time_ranges {
             f.write_fmt(format_args!(
@@ -377,7 +397,8 @@ impl std::fmt::Display for ComponentBucket {
 
         let data = {
             use arrow2::compute::concatenate::concatenate;
-            let chunks = self.chunks.iter().map(|chunk| &**chunk).collect::<Vec<_>>();
+            let chunks = self.data();
+            let chunks = chunks.iter().map(|chunk| &**chunk).collect::<Vec<_>>();
             concatenate(&chunks).unwrap()
         };
 
@@ -1,20 +1,37 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 
 use arrow2::array::{Array, ListArray};
 
+use nohash_hasher::{IntMap, IntSet};
 use re_log::trace;
-use re_log_types::{ComponentName, TimeRange, Timeline};
+use re_log_types::{
+    ComponentName, EntityPath, EntityPathHash, MsgId, TimeInt, TimeRange, Timeline,
+};
 
-use crate::{ComponentBucket, DataStore};
+use crate::{
+    ComponentBucket, ComponentTable, DataStore, IndexBucketIndices, IndexTable, RowIndex,
+    RowIndexKind, StoreEvent, StoreEventKind,
+};
 
 // ---
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
 pub enum GarbageCollectionTarget {
     /// Try to drop _at least_ the given percentage.
     ///
     /// The percentage must be a float in the range [0.0 : 1.0].
     DropAtLeastPercentage(f64),
+
+    /// Drop all data that was logged strictly before the given time on the primary timeline.
+    ///
+    /// Data that wasn't logged on the primary timeline at all is left untouched.
+    DropEverythingBefore(TimeInt),
+
+    /// Only keep the most recent data of each entity on the primary timeline, as specified by
+    /// the given [`RetentionRule`]s.
+    ///
+    /// Entities that aren't covered by any rule are left untouched.
+    KeepLatest(Vec<RetentionRule>),
 }
 
 impl std::fmt::Display for GarbageCollectionTarget {
@@ -24,8 +41,71 @@ impl std::fmt::Display for GarbageCollectionTarget {
                 "DropAtLeast({}%)",
                 re_format::format_f64(*p * 100.0)
             )),
+            GarbageCollectionTarget::DropEverythingBefore(time) => {
+                f.write_fmt(format_args!("DropEverythingBefore({})", time.as_i64()))
+            }
+            GarbageCollectionTarget::KeepLatest(rules) => {
+                f.write_str("KeepLatest(")?;
+                for (i, rule) in rules.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    f.write_fmt(format_args!("{rule}"))?;
+                }
+                f.write_str(")")
+            }
+        }
+    }
+}
+
+/// How much data to keep for an entity and all of its descendants.
+///
+/// See [`GarbageCollectionTarget::KeepLatest`].
+///
+/// When several rules cover the same entity, the most specific one wins, e.g. given rules for
+/// both `camera` and `camera/left`, the latter applies to `camera/left/image`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetentionRule {
+    /// The rule covers this entity and all of its descendants, i.e. `camera` covers `camera/**`.
+    pub entity_path: EntityPath,
+
+    /// How much data to keep, counting back from the most recent data of each entity, in the
+    /// units of the primary timeline (i.e. nanoseconds for temporal timelines).
+    pub keep: TimeInt,
+}
+
+impl RetentionRule {
+    /// E.g. `RetentionRule::new("camera", Duration::from_secs(10.0))` keeps the last 10 seconds
+    /// of `camera/**`.
+    pub fn new(entity_path: impl Into<EntityPath>, keep: impl Into<TimeInt>) -> Self {
+        Self {
+            entity_path: entity_path.into(),
+            keep: keep.into(),
         }
     }
+
+    /// Does this rule cover the given entity?
+    pub fn covers(&self, ent_path: &EntityPath) -> bool {
+        ent_path == &self.entity_path || ent_path.is_descendant_of(&self.entity_path)
+    }
+
+    /// Finds the most specific rule that covers the given entity, if any.
+    pub fn find<'a>(rules: &'a [Self], ent_path: &EntityPath) -> Option<&'a Self> {
+        rules
+            .iter()
+            .filter(|rule| rule.covers(ent_path))
+            .max_by_key(|rule| rule.entity_path.len())
+    }
+}
+
+impl std::fmt::Display for RetentionRule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_fmt(format_args!(
+            "{}/**: {}",
+            self.entity_path,
+            self.keep.as_i64()
+        ))
+    }
 }
 
 impl DataStore {
@@ -33,13 +113,33 @@ impl DataStore {
     /// `primary_component` and `primary_timeline`.
     /// Returns all the raw data that was removed from the store for the given `primary_component`.
     ///
-    /// This only affects component tables, indices are left as-is, effectively behaving as
-    /// tombstones.
+    /// ## [`GarbageCollectionTarget::DropAtLeastPercentage`]
     ///
     /// The garbage collection is based on _insertion order_, which makes it both very efficient
     /// and very simple from an implementation standpoint.
     /// The tradeoff is that the given `primary_timeline` is expected to roughly follow insertion
     /// order, otherwise the behaviour is essentially undefined.
+    ///
+    /// Component buckets are dropped first, and the indices are cleaned up afterwards: index rows
+    /// that only point to collected component rows are dropped, along with the index buckets
+    /// they leave empty. References to collected rows in the remaining index rows are nulled
+    /// out.
+    ///
+    /// ## [`GarbageCollectionTarget::DropEverythingBefore`] & [`GarbageCollectionTarget::KeepLatest`]
+    ///
+    /// These are based on the actual times of the data on the `primary_timeline`, regardless of
+    /// insertion order.
+    /// Rows are removed from the index tables of all timelines, and component buckets are
+    /// dropped once none of their rows are referenced anymore.
+    ///
+    /// Since component tables are shared across all entities, memory only gets reclaimed once
+    /// _all_ the rows of a bucket have been dropped: see
+    /// [`crate::DataStoreConfig::component_bucket_size_bytes`].
+    ///
+    /// ## Subscribers
+    ///
+    /// Every registered [`crate::StoreSubscriber`] is sent a [`StoreEventKind::Deletion`] event
+    /// for each row that was affected, listing the components of that row that were removed.
     pub fn gc(
         &mut self,
         target: GarbageCollectionTarget,
@@ -52,9 +152,13 @@ impl DataStore {
 
         let initial_nb_rows = self.total_temporal_component_rows();
         let initial_size_bytes = self.total_temporal_component_size_bytes() as f64;
+        let initial_index_nb_rows = self.total_temporal_index_rows();
+        let initial_index_size_bytes = self.total_temporal_index_size_bytes();
 
-        let res = match target {
+        let mut dropped_rows = DroppedRows::default();
+        let res = match &target {
             GarbageCollectionTarget::DropAtLeastPercentage(p) => {
+                let p = *p;
                 assert!((0.0..=1.0).contains(&p));
 
                 let drop_at_least_size_bytes = initial_size_bytes * p;
@@ -73,19 +177,71 @@ impl DataStore {
                     "starting GC"
                 );
 
-                self.gc_drop_at_least_size_bytes(
+                let dropped = self.gc_drop_at_least_size_bytes(
                     primary_timeline,
                     primary_component,
                     drop_at_least_size_bytes,
+                );
+                self.gc_drop_collected_index_rows(&mut dropped_rows);
+                dropped
+            }
+            GarbageCollectionTarget::DropEverythingBefore(time) => {
+                re_log::debug!(
+                    kind = "gc",
+                    id = self.gc_id,
+                    %target,
+                    timeline = %primary_timeline.name(),
+                    %primary_component,
+                    initial_nb_rows = re_format::format_large_number(initial_nb_rows as _),
+                    initial_size_bytes = re_format::format_bytes(initial_size_bytes),
+                    "starting GC"
+                );
+
+                let time = *time;
+                self.gc_drop_older_than(
+                    primary_timeline,
+                    primary_component,
+                    &mut dropped_rows,
+                    |_| Some(time),
+                )
+            }
+            GarbageCollectionTarget::KeepLatest(rules) => {
+                re_log::debug!(
+                    kind = "gc",
+                    id = self.gc_id,
+                    %target,
+                    timeline = %primary_timeline.name(),
+                    %primary_component,
+                    initial_nb_rows = re_format::format_large_number(initial_nb_rows as _),
+                    initial_size_bytes = re_format::format_bytes(initial_size_bytes),
+                    "starting GC"
+                );
+
+                self.gc_drop_older_than(
+                    primary_timeline,
+                    primary_component,
+                    &mut dropped_rows,
+                    |table| {
+                        let rule = RetentionRule::find(rules, &table.ent_path)?;
+                        table.latest_time().map(|latest| latest - rule.keep)
+                    },
                 )
             }
         };
 
+        self.prune_value_indices(&dropped_rows);
+
         #[cfg(debug_assertions)]
         self.sanity_check().unwrap();
 
         let new_nb_rows = self.total_temporal_component_rows();
         let new_size_bytes = self.total_temporal_component_size_bytes() as f64;
+        let new_index_nb_rows = self.total_temporal_index_rows();
+        let new_index_size_bytes = self.total_temporal_index_size_bytes();
+
+        self.reclaimed_index_rows += initial_index_nb_rows.saturating_sub(new_index_nb_rows);
+        self.reclaimed_index_size_bytes +=
+            initial_index_size_bytes.saturating_sub(new_index_size_bytes);
 
         re_log::debug!(
             kind = "gc",
@@ -97,12 +253,54 @@ impl DataStore {
             initial_size_bytes = re_format::format_bytes(initial_size_bytes),
             new_nb_rows = re_format::format_large_number(new_nb_rows as _),
             new_size_bytes = re_format::format_bytes(new_size_bytes),
+            initial_index_nb_rows = re_format::format_large_number(initial_index_nb_rows as _),
+            new_index_nb_rows = re_format::format_large_number(new_index_nb_rows as _),
             "GC done"
         );
 
+        if self.has_subscribers() {
+            let events = dropped_rows
+                .msg_ids
+                .into_iter()
+                .map(|(msg_id, (entity_path, components))| StoreEvent {
+                    kind: StoreEventKind::Deletion,
+                    msg_id,
+                    entity_path,
+                    timepoint: self.messages.get(&msg_id).cloned().unwrap_or_default(),
+                    components: components.into_iter().collect(),
+                })
+                .collect::<Vec<_>>();
+            self.notify_subscribers(&events);
+        }
+
         res
     }
 
+    /// Removes all the rows that are gone or not referenced by any index anymore from the value
+    /// indices, see [`DataStore::enable_value_index`].
+    fn prune_value_indices(&mut self, dropped_rows: &DroppedRows) {
+        crate::profile_function!();
+
+        let Self {
+            components,
+            value_indices,
+            ..
+        } = self;
+
+        for (component, index) in value_indices {
+            let table = components.get(component);
+            let dropped = dropped_rows.rows.get(component);
+            index.retain(|row_idx| match row_idx.kind() {
+                RowIndexKind::Timeless => true,
+                RowIndexKind::Temporal => {
+                    let row_idx = row_idx.as_u64();
+                    table.map_or(false, |table| table.contains(row_idx))
+                        && !dropped.map_or(false, |rows| rows.contains(&row_idx))
+                }
+            });
+        }
+    }
+
     fn gc_drop_at_least_size_bytes(
         &mut self,
         primary_timeline: Timeline,
@@ -179,7 +377,7 @@ impl DataStore {
 
             // We don't collect indices: they behave as tombstones.
 
-            dropped.extend(primary_bucket.chunks.into_iter().map(|chunk| {
+            dropped.extend(primary_bucket.data().into_iter().map(|chunk| {
                 chunk
                     .as_any()
                     .downcast_ref::<ListArray<i32>>()
@@ -189,8 +387,443 @@ impl DataStore {
             }));
         }
 
+        for table in self.components.values_mut() {
+            table.prune_dedup_index();
+        }
+
+        dropped
+    }
+
+    /// Drops all rows that are older than the cutoff time returned by `cutoff` for each index
+    /// table on the `primary_timeline`, along with the same rows on all other timelines.
+    ///
+    /// Returns the data of the `primary_component` for all dropped rows.
+    fn gc_drop_older_than(
+        &mut self,
+        primary_timeline: Timeline,
+        primary_component: ComponentName,
+        dropped_rows: &mut DroppedRows,
+        mut cutoff: impl FnMut(&IndexTable) -> Option<TimeInt>,
+    ) -> Vec<Box<dyn Array>> {
+        let mut dropped_entities = IntSet::<EntityPathHash>::default();
+
+        // Drop the expired rows on the primary timeline first...
+        for ((timeline, ent_path_hash), table) in &mut self.indices {
+            if *timeline != primary_timeline {
+                continue;
+            }
+            let Some(cutoff) = cutoff(table) else { continue; };
+
+            let nb_rows = table.drop_rows(dropped_rows, |_, time, _| time < cutoff.as_i64());
+            if nb_rows > 0 {
+                trace!(
+                    kind = "gc",
+                    id = self.gc_id,
+                    timeline = %primary_timeline.name(),
+                    entity = %table.ent_path,
+                    cutoff = primary_timeline.typ().format(cutoff),
+                    nb_rows,
+                    "dropped index rows"
+                );
+                dropped_entities.insert(*ent_path_hash);
+            }
+        }
+
+        // ...then the very same rows on every other timeline, which we can recognize by the
+        // component rows they point to.
+        let primary_dropped = dropped_rows.rows.clone();
+        for ((timeline, ent_path_hash), table) in &mut self.indices {
+            if *timeline == primary_timeline || !dropped_entities.contains(ent_path_hash) {
+                continue;
+            }
+
+            table.drop_rows(dropped_rows, |_, _, row| {
+                DroppedRows::contains_any(&primary_dropped, row)
+            });
+        }
+
+        let dropped = dropped_rows
+            .rows
+            .get(&primary_component)
+            .and_then(|row_indices| {
+                let table = self.components.get(&primary_component)?;
+                Some(
+                    row_indices
+                        .iter()
+                        .filter_map(|row_idx| {
+                            table.get(RowIndex::from_u63(RowIndexKind::Temporal, *row_idx))
+                        })
+                        .collect::<Vec<_>>(),
+                )
+            })
+            .unwrap_or_default();
+
+        // Finally, reclaim the component buckets that nobody references anymore.
+        for (component, row_indices) in &dropped_rows.rows {
+            if let Some(table) = self.components.get_mut(component) {
+                table.drop_rows(self.gc_id, row_indices);
+            }
+        }
+
         dropped
     }
+
+    /// Cleans up the indices after component buckets have been dropped.
+    ///
+    /// Index rows that only point to collected component rows, or whose cluster key was
+    /// collected, are dropped altogether.
+    /// In all other rows, references to collected component rows are nulled out.
+    fn gc_drop_collected_index_rows(&mut self, dropped_rows: &mut DroppedRows) {
+        crate::profile_function!();
+
+        let Self {
+            cluster_key,
+            indices,
+            components,
+            gc_id,
+            ..
+        } = self;
+
+        let is_collected = |component: &ComponentName, row_idx: &RowIndex| {
+            *component != DataStore::insert_id_key()
+                && row_idx.kind() == RowIndexKind::Temporal
+                && components
+                    .get(component)
+                    .map_or(true, |table| !table.contains(row_idx.as_u64()))
+        };
+
+        for table in indices.values_mut() {
+            let nb_rows = table.drop_collected_rows(*cluster_key, dropped_rows, is_collected);
+            if nb_rows > 0 {
+                trace!(
+                    kind = "gc",
+                    id = *gc_id,
+                    timeline = %table.timeline.name(),
+                    entity = %table.ent_path,
+                    nb_rows,
+                    "dropped collected index rows"
+                );
+            }
+        }
+
+        // Dropping a row whose cluster key was collected might leave other component rows
+        // unreferenced.
+        for (component, row_indices) in &dropped_rows.rows {
+            if let Some(table) = components.get_mut(component) {
+                table.drop_rows(*gc_id, row_indices);
+            }
+        }
+    }
+}
+
+/// The component rows that were dropped from the indices during a garbage collection.
+///
+/// Temporal row indices are unique to a single row of data, even when that row is indexed on
+/// several timelines.
+#[derive(Default)]
+struct DroppedRows {
+    rows: IntMap<ComponentName, IntSet<u64>>,
+
+    /// The rows of data that were affected, and which of their components were removed.
+    ///
+    /// Used to notify subscribers, see [`DataStore::gc`].
+    msg_ids: BTreeMap<MsgId, (EntityPath, BTreeSet<ComponentName>)>,
+}
+
+impl DroppedRows {
+    fn insert(&mut self, component: ComponentName, row_idx: RowIndex) {
+        // Insert IDs aren't actual row indices, and timeless rows (e.g. auto-generated cluster
+        // keys) are shared and never collected.
+        if component == DataStore::insert_id_key() || row_idx.kind() != RowIndexKind::Temporal {
+            return;
+        }
+        self.rows
+            .entry(component)
+            .or_default()
+            .insert(row_idx.as_u64());
+    }
+
+    fn insert_msg_id(&mut self, msg_id: MsgId, ent_path: &EntityPath, component: ComponentName) {
+        if component == DataStore::insert_id_key() {
+            return;
+        }
+        self.msg_ids
+            .entry(msg_id)
+            .or_insert_with(|| (ent_path.clone(), BTreeSet::new()))
+            .1
+            .insert(component);
+    }
+
+    fn contains_any(
+        rows: &IntMap<ComponentName, IntSet<u64>>,
+        row: &IntMap<ComponentName, RowIndex>,
+    ) -> bool {
+        row.iter().any(|(component, row_idx)| {
+            *component != DataStore::insert_id_key()
+                && row_idx.kind() == RowIndexKind::Temporal
+                && rows
+                    .get(component)
+                    .map_or(false, |rows| rows.contains(&row_idx.as_u64()))
+        })
+    }
+}
+
+impl IndexTable {
+    /// The most recent time of any row in this table.
+    fn latest_time(&self) -> Option<TimeInt> {
+        self.buckets
+            .values()
+            .filter(|bucket| bucket.total_rows() > 0)
+            .map(|bucket| bucket.indices.read().time_range.max)
+            .max()
+    }
+
+    /// Drops all rows for which `should_drop` returns `true`, given their row number within
+    /// their bucket, their time and their component row indices, and keeps track of the
+    /// component rows they pointed to.
+    ///
+    /// Buckets left empty are removed, see [`Self::drop_empty_buckets`].
+    ///
+    /// Returns the number of dropped rows.
+    fn drop_rows(
+        &mut self,
+        dropped_rows: &mut DroppedRows,
+        mut should_drop: impl FnMut(usize, i64, &IntMap<ComponentName, RowIndex>) -> bool,
+    ) -> u64 {
+        let mut nb_rows = 0;
+        for bucket in self.buckets.values_mut() {
+            nb_rows +=
+                bucket
+                    .indices
+                    .get_mut()
+                    .drop_rows(&self.ent_path, dropped_rows, &mut should_drop);
+        }
+
+        if nb_rows > 0 {
+            self.drop_empty_buckets();
+        }
+
+        nb_rows
+    }
+
+    /// Drops the rows that would be left pointing to nothing but their cluster key, or that lost
+    /// their cluster key, once collected component rows are removed; then nulls out the
+    /// references to collected component rows in all remaining rows.
+    ///
+    /// Returns the number of dropped rows.
+    fn drop_collected_rows(
+        &mut self,
+        cluster_key: ComponentName,
+        dropped_rows: &mut DroppedRows,
+        is_collected: impl Fn(&ComponentName, &RowIndex) -> bool,
+    ) -> u64 {
+        let mut nb_rows = 0;
+        for bucket in self.buckets.values_mut() {
+            let indices = bucket.indices.get_mut();
+
+            nb_rows += indices.drop_rows(&self.ent_path, dropped_rows, |_, _, row| {
+                if !row
+                    .iter()
+                    .any(|(component, row_idx)| is_collected(component, row_idx))
+                {
+                    return false;
+                }
+
+                let lost_cluster_key = row
+                    .get(&cluster_key)
+                    .map_or(true, |row_idx| is_collected(&cluster_key, row_idx));
+                let nothing_left = row.iter().all(|(component, row_idx)| {
+                    *component == cluster_key
+                        || *component == DataStore::insert_id_key()
+                        || is_collected(component, row_idx)
+                });
+
+                lost_cluster_key || nothing_left
+            });
+
+            indices.null_out(&self.ent_path, dropped_rows, &is_collected);
+        }
+
+        if nb_rows > 0 {
+            self.drop_empty_buckets();
+        }
+
+        nb_rows
+    }
+
+    /// Removes all empty buckets, although a table always keeps at least one bucket that covers
+    /// `[-∞;+∞]`.
+    fn drop_empty_buckets(&mut self) {
+        let min_time = TimeInt::from(i64::MIN);
+        self.buckets
+            .retain(|time, bucket| *time == min_time || bucket.total_rows() > 0);
+
+        // The first bucket must always start at -∞, see `IndexTable::buckets`: if it is empty,
+        // the next one takes over its range.
+        let first_is_empty = self
+            .buckets
+            .get(&min_time)
+            .map_or(true, |bucket| bucket.total_rows() == 0);
+        if first_is_empty && self.buckets.len() > 1 {
+            self.buckets.remove(&min_time);
+            if let Some((_, bucket)) = self.buckets.pop_first() {
+                self.buckets.insert(min_time, bucket);
+            }
+        }
+    }
+}
+
+impl IndexBucketIndices {
+    /// See [`IndexTable::drop_rows`].
+    fn drop_rows(
+        &mut self,
+        ent_path: &EntityPath,
+        dropped_rows: &mut DroppedRows,
+        mut should_drop: impl FnMut(usize, i64, &IntMap<ComponentName, RowIndex>) -> bool,
+    ) -> u64 {
+        let Self {
+            is_sorted: _,
+            time_range,
+            times,
+            msg_ids,
+            indices,
+        } = self;
+
+        let mut row = IntMap::default();
+        let keep = times
+            .iter()
+            .enumerate()
+            .map(|(i, time)| {
+                row.clear();
+                row.extend(
+                    indices
+                        .iter()
+                        .filter_map(|(component, index)| Some((*component, index[i]?))),
+                );
+
+                let is_dropped = should_drop(i, *time, &row);
+                if is_dropped {
+                    for (component, row_idx) in &row {
+                        dropped_rows.insert(*component, *row_idx);
+                        dropped_rows.insert_msg_id(msg_ids[i], ent_path, *component);
+                    }
+                }
+                !is_dropped
+            })
+            .collect::<Vec<_>>();
+
+        let nb_rows = keep.iter().filter(|keep| !**keep).count() as u64;
+        if nb_rows == 0 {
+            return 0;
+        }
+
+        // Dropping rows preserves their relative order, so sortedness doesn't change.
+        fn retain<T>(values: &mut Vec<T>, keep: &[bool]) {
+            let mut keep = keep.iter();
+            values.retain(|_| *keep.next().unwrap());
+            values.shrink_to_fit();
+        }
+        retain(times, &keep);
+        retain(msg_ids, &keep);
+        for index in indices.values_mut() {
+            retain(index, &keep);
+        }
+
+        *time_range = times.iter().fold(
+            TimeRange::new(i64::MAX.into(), i64::MIN.into()),
+            |range, &time| TimeRange::new(range.min.min(time.into()), range.max.max(time.into())),
+        );
+
+        nb_rows
+    }
+
+    /// Replaces all references to collected component rows with nulls.
+    fn null_out(
+        &mut self,
+        ent_path: &EntityPath,
+        dropped_rows: &mut DroppedRows,
+        is_collected: impl Fn(&ComponentName, &RowIndex) -> bool,
+    ) {
+        for (component, index) in &mut self.indices {
+            for (row_idx, msg_id) in index.iter_mut().zip(&self.msg_ids) {
+                if row_idx.map_or(false, |row_idx| is_collected(component, &row_idx)) {
+                    *row_idx = None;
+                    dropped_rows.insert_msg_id(*msg_id, ent_path, *component);
+                }
+            }
+        }
+    }
+}
+
+impl ComponentTable {
+    /// Returns the position of the bucket that holds the given row, if it hasn't been dropped.
+    pub(crate) fn find_bucket_nr(&self, row_idx: u64) -> Option<usize> {
+        let bucket_nr = self
+            .buckets
+            .partition_point(|bucket| row_idx >= bucket.row_offset)
+            .checked_sub(1)?;
+        let bucket = &self.buckets[bucket_nr];
+        (row_idx < bucket.row_offset + bucket.total_rows).then_some(bucket_nr)
+    }
+
+    /// Is the given row still around, i.e. hasn't it been garbage collected yet?
+    fn contains(&self, row_idx: u64) -> bool {
+        self.find_bucket_nr(row_idx).is_some()
+    }
+
+    /// Marks the given rows as dropped, and drops all archived buckets that don't hold a single
+    /// live row anymore.
+    ///
+    /// The active bucket is never dropped.
+    fn drop_rows(&mut self, gc_id: u64, row_indices: &IntSet<u64>) {
+        for &row_idx in row_indices {
+            // The bucket might have already been dropped by a previous GC.
+            if let Some(bucket_nr) = self.find_bucket_nr(row_idx) {
+                self.buckets[bucket_nr].dead_rows += 1;
+            }
+        }
+
+        let nb_buckets = self.buckets.len();
+        let mut bucket_nr = 0;
+        self.buckets.retain(|bucket| {
+            bucket_nr += 1;
+            let is_active = bucket_nr == nb_buckets;
+            let is_dropped = !is_active && bucket.is_dead();
+            if is_dropped {
+                trace!(
+                    kind = "gc",
+                    id = gc_id,
+                    component = %bucket.name,
+                    reclaimed = re_format::format_bytes(bucket.total_size_bytes() as f64),
+                    "dropped dead component bucket"
+                );
+            }
+            !is_dropped
+        });
+
+        self.prune_dedup_index();
+    }
+
+    /// Forgets about the content hashes of all the rows that have been dropped.
+    fn prune_dedup_index(&mut self) {
+        if self.dedup_index.is_empty() {
+            return;
+        }
+
+        let mut dedup_index = std::mem::take(&mut self.dedup_index);
+        dedup_index.retain(|_, row_idx| self.contains(*row_idx));
+        self.dedup_index = dedup_index;
+    }
+}
+
+impl ComponentBucket {
+    /// Are all the rows in this bucket dead, i.e. not referenced by any index anymore?
+    fn is_dead(&self) -> bool {
+        // The very first bucket of every table starts with a fake row that is never referenced.
+        // TODO(#1619): the whole fake row thing needs to go
+        let fake_rows = u64::from(self.row_offset == 0);
+        self.dead_rows + fake_rows >= self.total_rows
+    }
 }
 
 impl ComponentBucket {
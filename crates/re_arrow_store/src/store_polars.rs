@@ -212,6 +212,7 @@ impl IndexBucket {
             is_sorted: _,
             time_range: _,
             times: _,
+            msg_ids: _,
             indices,
         } = &*self.indices.read();
 
@@ -9,7 +9,7 @@ use re_log_types::{ComponentName, EntityPath, MsgId, TimeInt, TimePoint, TimeRan
 use crate::{
     ComponentBucket, ComponentTable, DataStore, IndexBucket, IndexBucketIndices, IndexRowNr,
     IndexTable, PersistentComponentTable, PersistentIndexTable, RowIndex, RowIndexKind,
-    SecondaryIndex,
+    SecondaryIndex, ValueFilter,
 };
 
 // --- Queries ---
@@ -49,12 +49,17 @@ impl LatestAtQuery {
 pub struct RangeQuery {
     pub timeline: Timeline,
     pub range: TimeRange,
+
+    /// Only yield the rows whose values match this filter, if any.
+    ///
+    /// See [`DataStore::enable_value_index`].
+    pub filter: Option<ValueFilter>,
 }
 
 impl std::fmt::Debug for RangeQuery {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.write_fmt(format_args!(
-            "<ranging from {} to {} (all inclusive) on {:?} ({} timeless)>",
+            "<ranging from {} to {} (all inclusive) on {:?} ({} timeless)",
             self.timeline.typ().format(self.range.min),
             self.timeline.typ().format(self.range.max),
             self.timeline.name(),
@@ -63,16 +68,52 @@ impl std::fmt::Debug for RangeQuery {
             } else {
                 "excluding"
             }
-        ))
+        ))?;
+        if let Some(filter) = &self.filter {
+            f.write_fmt(format_args!(" where {filter}"))?;
+        }
+        f.write_str(">")
     }
 }
 
 impl RangeQuery {
     pub const fn new(timeline: Timeline, range: TimeRange) -> Self {
-        Self { timeline, range }
+        Self {
+            timeline,
+            range,
+            filter: None,
+        }
+    }
+
+    /// Only yield the rows whose values match `filter`.
+    ///
+    /// The filtered component must be one of the queried components, and have a value index
+    /// (see [`DataStore::enable_value_index`]): otherwise, nothing matches.
+    #[inline]
+    pub fn with_filter(mut self, filter: ValueFilter) -> Self {
+        self.filter = Some(filter);
+        self
     }
 }
 
+/// The samples that surround the time of a [`LatestAtQuery`], so that they can be
+/// interpolated.
+///
+/// See [`DataStore::latest_at_and_next`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InterpolationSamples<const N: usize> {
+    /// The time of the latest sample at or before the queried time, or `None` if that sample is
+    /// timeless.
+    pub before_time: Option<TimeInt>,
+
+    /// The row indices of the latest sample at or before the queried time, exactly as returned
+    /// by [`DataStore::latest_at`].
+    pub before: [Option<RowIndex>; N],
+
+    /// The time and row indices of the earliest sample strictly after the queried time, if any.
+    pub after: Option<(TimeInt, [Option<RowIndex>; N])>,
+}
+
 // --- Data store ---
 
 impl DataStore {
@@ -286,6 +327,89 @@ impl DataStore {
         None
     }
 
+    /// Same as [`Self::latest_at`], but additionally looks for the earliest sample of the
+    /// `primary` component that comes strictly after the queried time, and returns the times of
+    /// both samples.
+    ///
+    /// This is all that's needed to interpolate the value of a component at any point in time,
+    /// rather than stepping from one logged value to the next.
+    ///
+    /// Returns `None` iff no row index could be found for the `primary` component at or before
+    /// the queried time: there is nothing to interpolate from.
+    pub fn latest_at_and_next<const N: usize>(
+        &self,
+        query: &LatestAtQuery,
+        ent_path: &EntityPath,
+        primary: ComponentName,
+        components: &[ComponentName; N],
+    ) -> Option<InterpolationSamples<N>> {
+        crate::profile_function!();
+
+        let before = self.latest_at(query, ent_path, primary, components)?;
+
+        let index = self.indices.get(&(query.timeline, ent_path.hash()));
+
+        // Timeless samples don't have a time, and cannot be interpolated from.
+        let before_time = index.and_then(|index| index.latest_time_at(query.at, primary));
+
+        let after = index.and_then(|index| {
+            let after_time = index.earliest_time_after(query.at, primary)?;
+            let row_indices = index.latest_at(after_time, primary, components)?;
+            Some((after_time, row_indices))
+        });
+
+        trace!(
+            kind = "latest_at_and_next",
+            query = ?query,
+            entity = %ent_path,
+            %primary,
+            ?components,
+            ?before_time,
+            ?after,
+            "row indices fetched"
+        );
+
+        Some(InterpolationSamples {
+            before_time,
+            before,
+            after,
+        })
+    }
+
+    /// Returns the range of query times over which a latest-at query for `components` returns
+    /// the same results as `query`, given the current contents of the store.
+    ///
+    /// That is: from the latest row at or before the queried time that holds data for any of
+    /// `components`, up to (but excluding) the earliest such row that comes strictly after it.
+    /// The range is unbounded on either side if there is no such row.
+    ///
+    /// Timeless data applies to all query times alike, and is therefore not taken into account.
+    pub fn latest_at_validity(
+        &self,
+        query: &LatestAtQuery,
+        ent_path: &EntityPath,
+        components: &[ComponentName],
+    ) -> TimeRange {
+        crate::profile_function!();
+
+        let Some(index) = self.indices.get(&(query.timeline, ent_path.hash())) else {
+            return TimeRange::new(TimeInt::MIN, TimeInt::MAX);
+        };
+
+        let min = components
+            .iter()
+            .filter_map(|component| index.latest_time_at(query.at, *component))
+            .max()
+            .unwrap_or(TimeInt::MIN);
+        let max = components
+            .iter()
+            .filter_map(|component| index.earliest_time_after(query.at, *component))
+            .min()
+            .map_or(TimeInt::MAX, |next| next - TimeInt::from(1));
+
+        TimeRange::new(min, max)
+    }
+
     /// Iterates the datastore in order to return the internal row indices of the the specified
     /// `components`, as seen from the point of view of the so-called `primary` component, for the
     /// given time range.
@@ -315,6 +439,12 @@ impl DataStore {
     ///
     /// When yielding timeless entries, the associated time will be `None`.
     ///
+    /// # Filtering
+    ///
+    /// If the query specifies a [`ValueFilter`], only the rows whose values match it are
+    /// yielded. This relies on the value index of the filtered component, the actual data is
+    /// never looked at.
+    ///
     /// ## Example
     ///
     /// The following example demonstrate how to range over the row indices of a given
@@ -408,6 +538,21 @@ impl DataStore {
             "query started..."
         );
 
+        // `None` if there's no filter at all, `Some(None)` if nothing can match.
+        let filter = query.filter.as_ref().map(|filter| {
+            let rows = components
+                .iter()
+                .position(|component| *component == filter.component)
+                .and_then(|pos| Some((pos, self.filter_rows(filter)?)));
+            if rows.is_none() {
+                re_log::warn_once!(
+                    "Cannot filter on {}: it must be both queried and value-indexed",
+                    filter.component
+                );
+            }
+            rows
+        });
+
         let temporal = self
             .indices
             .get(&(query.timeline, ent_path_hash))
@@ -416,7 +561,7 @@ impl DataStore {
             .flatten()
             .map(|(time, idx_row_nr, row_indices)| (Some(time), idx_row_nr, row_indices));
 
-        if query.range.min == TimeInt::MIN {
+        let results = if query.range.min == TimeInt::MIN {
             let timeless = self
                 .timeless_indices
                 .get(&ent_path_hash)
@@ -430,7 +575,15 @@ impl DataStore {
             itertools::Either::Left(timeless.chain(temporal))
         } else {
             itertools::Either::Right(temporal)
-        }
+        };
+
+        results.filter(move |(_, _, row_indices)| match &filter {
+            None => true,
+            Some(None) => false,
+            Some(Some((pos, rows))) => {
+                row_indices[*pos].map_or(false, |row_idx| rows.contains(&row_idx))
+            }
+        })
     }
 
     /// Retrieves the data associated with a list of `components` at the specified `indices`.
@@ -726,6 +879,50 @@ impl IndexTable {
             })
     }
 
+    /// Returns the time of the latest row at or before `time` that holds data for `primary`.
+    pub fn latest_time_at(&self, time: TimeInt, primary: ComponentName) -> Option<TimeInt> {
+        crate::profile_function!();
+
+        if !self.all_components.contains(&primary) {
+            return None;
+        }
+
+        self.range_buckets_rev(..=time).find_map(|(_, bucket)| {
+            bucket.sort_indices_if_needed();
+
+            let IndexBucketIndices { times, indices, .. } = &*bucket.indices.read();
+            let primary_idx = indices.get(&primary)?;
+
+            let end = times.partition_point(|t| *t <= time.as_i64());
+            (0..end)
+                .rev()
+                .find(|&i| primary_idx[i].is_some())
+                .map(|i| times[i].into())
+        })
+    }
+
+    /// Returns the time of the earliest row strictly after `time` that holds data for `primary`.
+    pub fn earliest_time_after(&self, time: TimeInt, primary: ComponentName) -> Option<TimeInt> {
+        crate::profile_function!();
+
+        if !self.all_components.contains(&primary) {
+            return None;
+        }
+
+        let (time_indexing, _) = self.find_bucket(time);
+        self.range_buckets(time_indexing..).find_map(|(_, bucket)| {
+            bucket.sort_indices_if_needed();
+
+            let IndexBucketIndices { times, indices, .. } = &*bucket.indices.read();
+            let primary_idx = indices.get(&primary)?;
+
+            let start = times.partition_point(|t| *t <= time.as_i64());
+            (start..times.len())
+                .find(|&i| primary_idx[i].is_some())
+                .map(|i| times[i].into())
+        })
+    }
+
     /// Returns the index bucket whose time range covers the given `time`.
     ///
     /// In addition to returning a reference to the `IndexBucket` itself, this also returns its
@@ -857,6 +1054,7 @@ impl IndexBucket {
             is_sorted,
             time_range: _,
             times,
+            msg_ids: _,
             indices,
         } = &*self.indices.read();
         debug_assert!(is_sorted);
@@ -960,6 +1158,7 @@ impl IndexBucket {
             is_sorted,
             time_range: bucket_time_range,
             times,
+            msg_ids: _,
             indices,
         } = &*self.indices.read();
         debug_assert!(is_sorted);
@@ -1067,6 +1266,7 @@ impl IndexBucketIndices {
             is_sorted,
             time_range: _,
             times,
+            msg_ids,
             indices,
         } = self;
 
@@ -1098,6 +1298,14 @@ impl IndexBucketIndices {
             }
         }
 
+        // shuffle row IDs back into a sorted state
+        {
+            let source = msg_ids.clone();
+            for (from, to) in swaps.iter().copied() {
+                msg_ids[to] = source[from];
+            }
+        }
+
         fn reshuffle_index(index: &mut SecondaryIndex, swaps: &[(usize, usize)]) {
             // shuffle data
             {
@@ -1192,7 +1400,11 @@ impl ComponentBucket {
     pub fn get(&self, row_idx: RowIndex) -> Option<Box<dyn Array>> {
         let row_idx = row_idx.as_u64() - self.row_offset;
         // This has to be safe to unwrap, otherwise it would never have made it past insertion.
-        if self.archived {
+        if let Some(spilled) = &self.spilled {
+            let data = spilled.data()?;
+            let list = data.as_any().downcast_ref::<ListArray<i32>>().unwrap();
+            (row_idx < list.len() as u64).then(|| list.value(row_idx as _))
+        } else if self.archived {
             debug_assert_eq!(self.chunks.len(), 1);
             let list = self.chunks[0]
                 .as_any()
@@ -1211,8 +1423,13 @@ impl ComponentBucket {
     }
 
     /// Returns a shallow clone of all the chunks in this bucket.
+    ///
+    /// If the bucket was spilled to disk, this maps it back into memory first.
     #[allow(dead_code)]
     pub fn data(&self) -> Vec<Box<dyn Array>> {
+        if let Some(spilled) = &self.spilled {
+            return spilled.data().into_iter().collect();
+        }
         self.chunks.clone() // shallow
     }
 
@@ -216,12 +216,18 @@ impl IndexBucket {
             is_sorted: _,
             time_range: _,
             times,
+            msg_ids,
             indices,
         } = &*self.indices.read();
 
         // All indices should contain the exact same number of rows as the time index.
         {
             let primary_len = times.len();
+            ensure!(
+                primary_len == msg_ids.len(),
+                "found rogue row ID index: expected {primary_len} rows, got {} instead",
+                msg_ids.len(),
+            );
             for (comp, index) in indices {
                 let secondary_len = index.len();
                 ensure!(
@@ -322,6 +328,30 @@ impl ComponentBucket {
             }
         }
 
+        // Spilled buckets are archived, and don't keep any data in memory
+        if self.spilled.is_some() {
+            ensure!(self.archived, "only archived buckets can be spilled");
+            ensure!(
+                self.chunks.is_empty(),
+                "spilled buckets shouldn't keep any chunks in memory, got {}",
+                self.chunks.len(),
+            );
+        }
+
+        // Deduplicated data is a subset of the data, and compaction undoes deduplication
+        {
+            ensure!(
+                self.deduplicated_size_bytes <= self.total_size_bytes,
+                "deduplicated more data than there is: {} > {}",
+                self.deduplicated_size_bytes,
+                self.total_size_bytes,
+            );
+            ensure!(
+                !self.archived || self.deduplicated_size_bytes == 0,
+                "archived buckets are compacted and cannot share any data",
+            );
+        }
+
         Ok(())
     }
 }
@@ -0,0 +1,303 @@
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc,
+};
+
+use parking_lot::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+use crate::DataStore;
+
+// --- Shared store ---
+
+/// A [`DataStore`] that can be shared across threads: written to by one thread (see
+/// [`SharedDataStore::spawn_ingester`]) while being queried by any number of others.
+///
+/// Cloning a `SharedDataStore` is cheap: all clones refer to the same underlying store.
+///
+/// ## Visibility
+///
+/// Writes are applied in batches, one epoch at a time: a reader holding a
+/// [`DataStoreSnapshot`] is guaranteed to see the store exactly as it was at the end of a given
+/// epoch, never in the middle of one.
+/// The store keeps on ingesting as soon as the snapshot is released, which makes it possible
+/// for e.g. the UI thread to query a consistent view of the data for the duration of a frame,
+/// without ever having to do the ingestion itself.
+#[derive(Clone)]
+pub struct SharedDataStore {
+    inner: Arc<SharedDataStoreInner>,
+}
+
+struct SharedDataStoreInner {
+    store: RwLock<DataStore>,
+
+    /// Bumped every time a batch of writes has been applied to `store`.
+    epoch: AtomicU64,
+}
+
+static_assertions::assert_impl_all!(SharedDataStore: Send, Sync);
+
+impl SharedDataStore {
+    pub fn new(store: DataStore) -> Self {
+        Self {
+            inner: Arc::new(SharedDataStoreInner {
+                store: RwLock::new(store),
+                epoch: AtomicU64::new(0),
+            }),
+        }
+    }
+
+    /// The current epoch of the store, i.e. the number of batches of writes that have been
+    /// applied to it so far.
+    ///
+    /// Cheap to compute: this is a good way of checking whether anything changed since the last
+    /// time the store was queried, without having to lock it.
+    #[inline]
+    pub fn epoch(&self) -> u64 {
+        self.inner.epoch.load(Ordering::Acquire)
+    }
+
+    /// Returns a consistent, read-only view of the store.
+    ///
+    /// Writes are blocked for as long as the snapshot is alive: keep it short-lived (e.g. one
+    /// frame's worth of queries).
+    ///
+    /// It is fine to take several snapshots at once on the same thread (e.g. in nested function
+    /// calls), even while a writer is waiting: they won't deadlock.
+    pub fn read(&self) -> DataStoreSnapshot<'_> {
+        crate::profile_function!();
+        // A regular `read` would wait behind any pending writer, which deadlocks if this thread
+        // is already holding another snapshot.
+        let store = self.inner.store.read_recursive();
+        // Writers bump the epoch while still holding the lock, so this cannot be torn.
+        let epoch = self.epoch();
+        DataStoreSnapshot { store, epoch }
+    }
+
+    /// Locks the store for writing, waiting for all current readers to be done first.
+    ///
+    /// Everything that's done with the returned guard is considered part of a single epoch,
+    /// which ends when the guard is dropped.
+    pub fn write(&self) -> DataStoreWriteGuard<'_> {
+        crate::profile_function!();
+        DataStoreWriteGuard {
+            store: self.inner.store.write(),
+            epoch: &self.inner.epoch,
+        }
+    }
+}
+
+// --- Snapshots ---
+
+/// A consistent, read-only view of a [`SharedDataStore`] at a given epoch.
+///
+/// See [`SharedDataStore::read`].
+pub struct DataStoreSnapshot<'a> {
+    store: RwLockReadGuard<'a, DataStore>,
+    epoch: u64,
+}
+
+impl<'a> DataStoreSnapshot<'a> {
+    /// The epoch this snapshot was taken at.
+    #[inline]
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+}
+
+impl<'a> std::ops::Deref for DataStoreSnapshot<'a> {
+    type Target = DataStore;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.store
+    }
+}
+
+/// Exclusive access to a [`SharedDataStore`], see [`SharedDataStore::write`].
+///
+/// Dropping the guard ends the current epoch.
+pub struct DataStoreWriteGuard<'a> {
+    store: RwLockWriteGuard<'a, DataStore>,
+    epoch: &'a AtomicU64,
+}
+
+impl<'a> std::ops::Deref for DataStoreWriteGuard<'a> {
+    type Target = DataStore;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.store
+    }
+}
+
+impl<'a> std::ops::DerefMut for DataStoreWriteGuard<'a> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.store
+    }
+}
+
+impl<'a> Drop for DataStoreWriteGuard<'a> {
+    fn drop(&mut self) {
+        // Still holding the lock at this point: readers can never observe a new epoch alongside
+        // stale data.
+        self.epoch.fetch_add(1, Ordering::AcqRel);
+    }
+}
+
+// --- Ingestion ---
+
+#[cfg(not(target_arch = "wasm32"))]
+pub use self::ingester::DataStoreIngester;
+
+#[cfg(not(target_arch = "wasm32"))]
+mod ingester {
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc, Arc,
+    };
+
+    use re_log_types::DataRow;
+
+    use super::SharedDataStore;
+
+    enum Command {
+        Insert(DataRow),
+        Flush(mpsc::SyncSender<()>),
+    }
+
+    /// Inserts rows into a [`SharedDataStore`] from a dedicated background thread.
+    ///
+    /// Rows are inserted in the order they're sent in, in batches of everything that's pending
+    /// (up to [`DataStoreIngester::MAX_ROWS_PER_EPOCH`] rows): each batch makes up one epoch, see
+    /// [`SharedDataStore`].
+    ///
+    /// Dropping the ingester inserts all pending rows, then stops the background thread.
+    ///
+    /// See [`SharedDataStore::spawn_ingester`].
+    pub struct DataStoreIngester {
+        tx: Option<mpsc::Sender<Command>>,
+        thread: Option<std::thread::JoinHandle<()>>,
+
+        /// Number of rows sent but not inserted yet.
+        num_pending_rows: Arc<AtomicUsize>,
+    }
+
+    impl SharedDataStore {
+        /// Spawns a background thread that inserts all the rows sent to the returned
+        /// [`DataStoreIngester`] into this store.
+        ///
+        /// Rows that fail to be inserted are logged and dropped.
+        pub fn spawn_ingester(&self) -> std::io::Result<DataStoreIngester> {
+            let (tx, rx) = mpsc::channel();
+            let num_pending_rows = Arc::new(AtomicUsize::new(0));
+
+            let store = self.clone();
+            let thread = std::thread::Builder::new()
+                .name("data_store_ingester".to_owned())
+                .spawn({
+                    let num_pending_rows = num_pending_rows.clone();
+                    move || ingest(&store, &rx, &num_pending_rows)
+                })?;
+
+            Ok(DataStoreIngester {
+                tx: Some(tx),
+                thread: Some(thread),
+                num_pending_rows,
+            })
+        }
+    }
+
+    impl DataStoreIngester {
+        /// The maximum number of rows inserted under a single write lock.
+        ///
+        /// Readers have to wait for the current batch to be done before they can take a
+        /// snapshot: this bounds how long that can take, however much data is pending.
+        pub const MAX_ROWS_PER_EPOCH: usize = 1024;
+
+        /// Queues `row` for insertion.
+        pub fn insert_row(&self, row: DataRow) {
+            self.num_pending_rows.fetch_add(1, Ordering::Relaxed);
+            self.send(Command::Insert(row));
+        }
+
+        /// Are there rows that have been queued but aren't visible to readers yet?
+        ///
+        /// Useful to know whether to keep polling the store for new data.
+        pub fn has_pending_rows(&self) -> bool {
+            self.num_pending_rows.load(Ordering::Acquire) > 0
+        }
+
+        /// Blocks until all the rows queued so far have been inserted and are visible to
+        /// readers.
+        pub fn flush(&self) {
+            let (tx, rx) = mpsc::sync_channel(1);
+            self.send(Command::Flush(tx));
+            // The ingester thread is gone if it panicked, in which case there's nothing to
+            // wait for anymore.
+            rx.recv().ok();
+        }
+
+        fn send(&self, cmd: Command) {
+            if let Some(tx) = &self.tx {
+                if tx.send(cmd).is_err() {
+                    re_log::warn_once!("The data store ingester thread is gone, dropping data");
+                }
+            }
+        }
+    }
+
+    impl Drop for DataStoreIngester {
+        fn drop(&mut self) {
+            // Closing the channel is what tells the ingester thread to stop.
+            self.tx = None;
+            if let Some(thread) = self.thread.take() {
+                thread.join().ok();
+            }
+        }
+    }
+
+    fn ingest(
+        store: &SharedDataStore,
+        rx: &mpsc::Receiver<Command>,
+        num_pending_rows: &AtomicUsize,
+    ) {
+        // Block until there's something to do, then grab everything else that's pending on the
+        // way, so that readers get to see as few epochs as possible.
+        while let Ok(cmd) = rx.recv() {
+            let mut cmds = std::iter::once(cmd).chain(rx.try_iter()).peekable();
+
+            // …but never hold the write lock for too long in one go, so that readers still get
+            // a chance to see the data as it comes in.
+            while cmds.peek().is_some() {
+                let mut rows = Vec::new();
+                let mut flushes = Vec::new();
+                for cmd in cmds.by_ref() {
+                    match cmd {
+                        Command::Insert(row) => rows.push(row),
+                        Command::Flush(ack) => flushes.push(ack),
+                    }
+                    if rows.len() >= DataStoreIngester::MAX_ROWS_PER_EPOCH {
+                        break;
+                    }
+                }
+
+                if !rows.is_empty() {
+                    crate::profile_scope!("ingest_batch");
+                    let mut store = store.write();
+                    for row in &rows {
+                        if let Err(err) = store.insert_row(row) {
+                            re_log::error!("Failed to insert incoming row: {err}");
+                        }
+                    }
+                }
+                // The epoch is over by now: the rows are visible.
+                num_pending_rows.fetch_sub(rows.len(), Ordering::Release);
+
+                for ack in flushes {
+                    ack.send(()).ok();
+                }
+            }
+        }
+    }
+}
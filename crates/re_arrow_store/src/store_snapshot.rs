@@ -0,0 +1,832 @@
+//! Saving a [`DataStore`] to disk as-is, and loading it back without having to go through
+//! the whole ingestion process again.
+//!
+//! A snapshot is a directory of Arrow IPC files:
+//! - `messages.arrow`: one row per (message, timeline) pair, see `DataStore::messages`.
+//!   Its schema metadata also holds the version of the format and store-wide settings.
+//! - `indices.arrow` & `timeless_indices.arrow`: one row per (index row, component) pair.
+//! - `component_buckets.arrow`: one row per (component bucket, timeline) pair, holding the
+//!   metadata of all temporal component buckets.
+//! - `components/*.arrow` & `timeless_components/*.arrow`: one file per component table, whose
+//!   one and only column is named after the component.
+//!   Temporal tables are written as one record batch per bucket.
+//!
+//! Derived state (caches, statistics, subscribers, spilling…) is not part of the snapshot.
+
+use std::collections::HashMap;
+use std::num::NonZeroU64;
+use std::path::{Path, PathBuf};
+
+use arrow2::{
+    array::{Array, BooleanArray, ListArray, PrimitiveArray, UInt64Array, UInt8Array, Utf8Array},
+    chunk::Chunk,
+    compute::concatenate::concatenate,
+    datatypes::{DataType, Field, Metadata, Schema},
+};
+use re_log_types::{
+    external::arrow2_convert::deserialize::arrow_array_deserialize_iterator, ComponentName,
+    DataCell, EntityPath, MsgId, TimeRange, TimeType, Timeline,
+};
+
+use crate::{
+    ComponentBucket, ComponentTable, DataStore, DataStoreConfig, IndexBucket, IndexTable,
+    PersistentComponentTable, PersistentIndexTable, RowIndex,
+};
+
+// --- Errors ---
+
+#[derive(thiserror::Error, Debug)]
+pub enum SnapshotError {
+    #[error("Failed to access the snapshot directory")]
+    Io(#[from] std::io::Error),
+
+    #[error("Failed to (de)serialize snapshot data")]
+    Arrow(#[from] arrow2::error::Error),
+
+    #[error("Unsupported snapshot version {0:?}")]
+    UnsupportedVersion(String),
+
+    #[error("Malformed snapshot: {0}")]
+    Malformed(String),
+
+    #[error("Failed to read back the data of {0}, see the logs for details")]
+    UnreadableData(ComponentName),
+}
+
+pub type SnapshotResult<T> = ::std::result::Result<T, SnapshotError>;
+
+fn malformed(reason: impl Into<String>) -> SnapshotError {
+    SnapshotError::Malformed(reason.into())
+}
+
+// --- Layout ---
+
+/// Bumped whenever the layout of snapshots changes in a non-backwards compatible way.
+const SNAPSHOT_VERSION: &str = "1";
+
+const FILE_MESSAGES: &str = "messages.arrow";
+const FILE_INDICES: &str = "indices.arrow";
+const FILE_TIMELESS_INDICES: &str = "timeless_indices.arrow";
+const FILE_COMPONENT_BUCKETS: &str = "component_buckets.arrow";
+const DIR_COMPONENTS: &str = "components";
+const DIR_TIMELESS_COMPONENTS: &str = "timeless_components";
+
+const METADATA_VERSION: &str = "rerun.snapshot_version";
+const METADATA_CLUSTER_KEY: &str = "rerun.cluster_key";
+const METADATA_INSERT_ID: &str = "rerun.insert_id";
+
+const COL_ENTITY_PATH: &str = "entity_path";
+const COL_TIMELINE: &str = "timeline";
+const COL_TIMELINE_TYPE: &str = "timeline_type";
+const COL_BUCKET: &str = "bucket";
+const COL_ROW: &str = "row";
+const COL_TIME: &str = "time";
+const COL_MSG_ID: &str = "msg_id";
+const COL_COMPONENT: &str = "component";
+const COL_ROW_INDEX: &str = "row_index";
+const COL_ROW_OFFSET: &str = "row_offset";
+const COL_ARCHIVED: &str = "archived";
+const COL_DEAD_ROWS: &str = "dead_rows";
+const COL_TIME_MIN: &str = "time_min";
+const COL_TIME_MAX: &str = "time_max";
+
+// --- Data store ---
+
+impl DataStore {
+    /// Saves the complete contents of the store to `directory`, as a set of Arrow IPC files.
+    ///
+    /// The snapshot can then be loaded back with [`Self::load_snapshot`], which is much faster
+    /// than re-inserting all the original data, as none of the indices need to be rebuilt.
+    ///
+    /// `directory` is created if it doesn't exist yet; any snapshot already in there is
+    /// overwritten.
+    pub fn save_snapshot(&self, directory: impl AsRef<Path>) -> SnapshotResult<()> {
+        crate::profile_function!();
+
+        let directory = directory.as_ref();
+        std::fs::create_dir_all(directory)?;
+
+        // Snapshots are always sorted, so that there's nothing left to do when loading them.
+        for index in self.indices.values() {
+            index.sort_indices_if_needed();
+        }
+
+        self.save_messages(&directory.join(FILE_MESSAGES))?;
+        self.save_indices(&directory.join(FILE_INDICES))?;
+        self.save_timeless_indices(&directory.join(FILE_TIMELESS_INDICES))?;
+        self.save_component_buckets(&directory.join(FILE_COMPONENT_BUCKETS))?;
+
+        let components_dir = clear_dir(directory.join(DIR_COMPONENTS))?;
+        for (i, table) in self.components.values().enumerate() {
+            // Buckets are concatenated one at a time, as they get written: they might have been
+            // spilled to disk.
+            let chunks = table.buckets.iter().map(|bucket| -> SnapshotResult<_> {
+                Ok(Chunk::new(vec![concat(table.name, &bucket.data())?]))
+            });
+            write_file(
+                &components_dir.join(format!("{i}.arrow")),
+                component_schema(table.name, &table.datatype),
+                chunks,
+            )?;
+        }
+
+        let components_dir = clear_dir(directory.join(DIR_TIMELESS_COMPONENTS))?;
+        for (i, table) in self.timeless_components.values().enumerate() {
+            write_file(
+                &components_dir.join(format!("{i}.arrow")),
+                component_schema(table.name, &table.datatype),
+                [Ok(Chunk::new(vec![concat(table.name, &table.chunks)?]))],
+            )?;
+        }
+
+        re_log::debug!(directory = %directory.display(), "saved snapshot");
+
+        Ok(())
+    }
+
+    /// Loads a store that was previously saved with [`Self::save_snapshot`].
+    ///
+    /// `config` doesn't have to match the configuration of the store that was saved: it only
+    /// applies to the data that gets inserted from now on.
+    pub fn load_snapshot(
+        directory: impl AsRef<Path>,
+        config: DataStoreConfig,
+    ) -> SnapshotResult<Self> {
+        crate::profile_function!();
+
+        let directory = directory.as_ref();
+
+        let (schema, chunks) = read_file(&directory.join(FILE_MESSAGES))?;
+        let metadata = |key: &str| {
+            schema
+                .metadata
+                .get(key)
+                .ok_or_else(|| malformed(format!("missing {key:?} metadata")))
+        };
+
+        let version = metadata(METADATA_VERSION)?;
+        if version.as_str() != SNAPSHOT_VERSION {
+            return Err(SnapshotError::UnsupportedVersion(version.clone()));
+        }
+
+        let cluster_key = ComponentName::from(metadata(METADATA_CLUSTER_KEY)?.as_str());
+        let mut store = Self::new(cluster_key, config);
+        store.insert_id = metadata(METADATA_INSERT_ID)?
+            .parse()
+            .map_err(|_err| malformed("bad insert id"))?;
+
+        store.load_messages(&schema, &chunks)?;
+        store.load_indices(&directory.join(FILE_INDICES))?;
+        store.load_timeless_indices(&directory.join(FILE_TIMELESS_INDICES))?;
+        store.load_components(
+            &directory.join(FILE_COMPONENT_BUCKETS),
+            &directory.join(DIR_COMPONENTS),
+        )?;
+        store.load_timeless_components(&directory.join(DIR_TIMELESS_COMPONENTS))?;
+
+        re_log::debug!(directory = %directory.display(), "loaded snapshot");
+
+        Ok(store)
+    }
+
+    // --- Messages ---
+
+    fn save_messages(&self, path: &Path) -> SnapshotResult<()> {
+        let mut msg_ids = Vec::new();
+        let mut timelines = TimelineColumns::default();
+        for (msg_id, timepoint) in &self.messages {
+            if timepoint.is_timeless() {
+                msg_ids.push(*msg_id);
+                timelines.push(None);
+            }
+            for (timeline, time) in timepoint.iter() {
+                msg_ids.push(*msg_id);
+                timelines.push(Some((*timeline, time.as_i64())));
+            }
+        }
+
+        let mut columns = vec![(COL_MSG_ID, msg_id_array(&msg_ids))];
+        columns.extend(timelines.into_columns(COL_TIME));
+
+        let mut metadata = Metadata::new();
+        metadata.insert(METADATA_VERSION.into(), SNAPSHOT_VERSION.into());
+        metadata.insert(METADATA_CLUSTER_KEY.into(), self.cluster_key.to_string());
+        metadata.insert(METADATA_INSERT_ID.into(), self.insert_id.to_string());
+
+        write_table(path, columns, metadata)
+    }
+
+    fn load_messages(
+        &mut self,
+        schema: &Schema,
+        chunks: &[Chunk<Box<dyn Array>>],
+    ) -> SnapshotResult<()> {
+        for chunk in chunks {
+            let table = Table { schema, chunk };
+            let msg_ids = table.msg_ids(COL_MSG_ID)?;
+            let timelines = table.timelines(COL_TIME)?;
+
+            for (msg_id, timeline) in msg_ids.into_iter().zip(timelines) {
+                let timepoint = self.messages.entry(msg_id).or_default();
+                if let Some((timeline, time)) = timeline {
+                    timepoint.insert(timeline, time.into());
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    // --- Indices ---
+
+    fn save_indices(&self, path: &Path) -> SnapshotResult<()> {
+        let mut ent_paths = Vec::new();
+        let mut timelines = TimelineColumns::default();
+        let mut buckets = Vec::new();
+        let mut rows = Vec::new();
+        let mut msg_ids = Vec::new();
+        let mut components = Vec::new();
+        let mut row_indices = Vec::new();
+
+        for table in self.indices.values() {
+            let ent_path = table.ent_path.to_string();
+            for (bucket_time, bucket) in &table.buckets {
+                let guard = bucket.indices.read();
+                let mut bucket_components = guard.indices.keys().collect::<Vec<_>>();
+                bucket_components.sort();
+
+                for (row, (time, msg_id)) in guard.times.iter().zip(&guard.msg_ids).enumerate() {
+                    for component in &bucket_components {
+                        ent_paths.push(ent_path.clone());
+                        timelines.push(Some((table.timeline, *time)));
+                        buckets.push(bucket_time.as_i64());
+                        rows.push(row as u64);
+                        msg_ids.push(*msg_id);
+                        components.push(component.as_str());
+                        row_indices.push(guard.indices[*component][row].map(|idx| idx.0.get()));
+                    }
+                }
+            }
+        }
+
+        let mut columns = vec![(
+            COL_ENTITY_PATH,
+            Utf8Array::<i32>::from_slice(ent_paths).boxed(),
+        )];
+        columns.extend(timelines.into_columns(COL_TIME));
+        columns.extend([
+            (COL_BUCKET, PrimitiveArray::<i64>::from_vec(buckets).boxed()),
+            (COL_ROW, UInt64Array::from_vec(rows).boxed()),
+            (COL_MSG_ID, msg_id_array(&msg_ids)),
+            (
+                COL_COMPONENT,
+                Utf8Array::<i32>::from_slice(components).boxed(),
+            ),
+            (COL_ROW_INDEX, UInt64Array::from(row_indices).boxed()),
+        ]);
+
+        write_table(path, columns, Metadata::new())
+    }
+
+    fn load_indices(&mut self, path: &Path) -> SnapshotResult<()> {
+        let (schema, chunks) = read_file(path)?;
+        for chunk in &chunks {
+            let table = Table {
+                schema: &schema,
+                chunk,
+            };
+            let ent_paths = table.column::<Utf8Array<i32>>(COL_ENTITY_PATH)?;
+            let timelines = table.timelines(COL_TIME)?;
+            let buckets = table.column::<PrimitiveArray<i64>>(COL_BUCKET)?;
+            let rows = table.column::<UInt64Array>(COL_ROW)?;
+            let msg_ids = table.msg_ids(COL_MSG_ID)?;
+            let components = table.column::<Utf8Array<i32>>(COL_COMPONENT)?;
+            let row_indices = table.column::<UInt64Array>(COL_ROW_INDEX)?;
+
+            for (i, (timeline, msg_id)) in timelines.into_iter().zip(msg_ids).enumerate() {
+                let (timeline, time) = timeline.ok_or_else(|| malformed("timeless index row"))?;
+                let ent_path = EntityPath::from(ent_paths.value(i));
+                let component = ComponentName::from(components.value(i));
+
+                let cluster_key = self.cluster_key;
+                let index = self
+                    .indices
+                    .entry((timeline, ent_path.hash()))
+                    .or_insert_with(|| IndexTable::new(cluster_key, timeline, ent_path));
+                index.all_components.insert(component);
+
+                let bucket = index
+                    .buckets
+                    .entry(buckets.value(i).into())
+                    .or_insert_with(|| IndexBucket::new(cluster_key, timeline));
+                let indices = bucket.indices.get_mut();
+
+                let row = rows.value(i) as usize;
+                match row.cmp(&indices.times.len()) {
+                    std::cmp::Ordering::Less => {}
+                    std::cmp::Ordering::Equal => {
+                        indices.times.push(time);
+                        indices.msg_ids.push(msg_id);
+                    }
+                    std::cmp::Ordering::Greater => {
+                        return Err(malformed("index rows out of order"));
+                    }
+                }
+
+                let secondary = indices.indices.entry(component).or_default();
+                if secondary.len() <= row {
+                    secondary.resize(row + 1, None);
+                }
+                secondary[row] = row_index(get(row_indices, i));
+            }
+        }
+
+        for index in self.indices.values_mut() {
+            for bucket in index.buckets.values_mut() {
+                let indices = bucket.indices.get_mut();
+                for secondary in indices.indices.values_mut() {
+                    secondary.resize(indices.times.len(), None);
+                }
+                indices.is_sorted = indices.times.windows(2).all(|t| t[0] <= t[1]);
+                indices.time_range = indices.times.iter().fold(
+                    TimeRange::new(i64::MAX.into(), i64::MIN.into()),
+                    |range, &time| {
+                        TimeRange::new(range.min.min(time.into()), range.max.max(time.into()))
+                    },
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    fn save_timeless_indices(&self, path: &Path) -> SnapshotResult<()> {
+        let mut ent_paths = Vec::new();
+        let mut rows = Vec::new();
+        let mut components = Vec::new();
+        let mut row_indices = Vec::new();
+
+        for table in self.timeless_indices.values() {
+            let ent_path = table.ent_path.to_string();
+            let mut table_components = table.indices.keys().collect::<Vec<_>>();
+            table_components.sort();
+
+            for row in 0..table.num_rows as usize {
+                for component in &table_components {
+                    ent_paths.push(ent_path.clone());
+                    rows.push(row as u64);
+                    components.push(component.as_str());
+                    row_indices.push(table.indices[*component][row].map(|idx| idx.0.get()));
+                }
+            }
+        }
+
+        write_table(
+            path,
+            vec![
+                (
+                    COL_ENTITY_PATH,
+                    Utf8Array::<i32>::from_slice(ent_paths).boxed(),
+                ),
+                (COL_ROW, UInt64Array::from_vec(rows).boxed()),
+                (
+                    COL_COMPONENT,
+                    Utf8Array::<i32>::from_slice(components).boxed(),
+                ),
+                (COL_ROW_INDEX, UInt64Array::from(row_indices).boxed()),
+            ],
+            Metadata::new(),
+        )
+    }
+
+    fn load_timeless_indices(&mut self, path: &Path) -> SnapshotResult<()> {
+        let (schema, chunks) = read_file(path)?;
+        for chunk in &chunks {
+            let table = Table {
+                schema: &schema,
+                chunk,
+            };
+            let ent_paths = table.column::<Utf8Array<i32>>(COL_ENTITY_PATH)?;
+            let rows = table.column::<UInt64Array>(COL_ROW)?;
+            let components = table.column::<Utf8Array<i32>>(COL_COMPONENT)?;
+            let row_indices = table.column::<UInt64Array>(COL_ROW_INDEX)?;
+
+            for i in 0..chunk.len() {
+                let ent_path = EntityPath::from(ent_paths.value(i));
+                let component = ComponentName::from(components.value(i));
+
+                let cluster_key = self.cluster_key;
+                let index = self
+                    .timeless_indices
+                    .entry(ent_path.hash())
+                    .or_insert_with(|| PersistentIndexTable::new(cluster_key, ent_path));
+                index.all_components.insert(component);
+
+                let row = rows.value(i) as usize;
+                match row.cmp(&(index.num_rows as usize)) {
+                    std::cmp::Ordering::Less => {}
+                    std::cmp::Ordering::Equal => index.num_rows += 1,
+                    std::cmp::Ordering::Greater => {
+                        return Err(malformed("timeless index rows out of order"));
+                    }
+                }
+
+                let secondary = index.indices.entry(component).or_default();
+                if secondary.len() <= row {
+                    secondary.resize(row + 1, None);
+                }
+                secondary[row] = row_index(get(row_indices, i));
+            }
+        }
+
+        for index in self.timeless_indices.values_mut() {
+            for secondary in index.indices.values_mut() {
+                secondary.resize(index.num_rows as usize, None);
+            }
+        }
+
+        Ok(())
+    }
+
+    // --- Components ---
+
+    fn save_component_buckets(&self, path: &Path) -> SnapshotResult<()> {
+        let mut components = Vec::new();
+        let mut bucket_nrs = Vec::new();
+        let mut row_offsets = Vec::new();
+        let mut archived = Vec::new();
+        let mut dead_rows = Vec::new();
+        let mut timelines = TimelineColumns::default();
+        let mut time_maxs = Vec::new();
+
+        for table in self.components.values() {
+            for (bucket_nr, bucket) in table.buckets.iter().enumerate() {
+                let mut push = |time_range: Option<(&Timeline, &TimeRange)>| {
+                    components.push(table.name.as_str());
+                    bucket_nrs.push(bucket_nr as u64);
+                    row_offsets.push(bucket.row_offset);
+                    archived.push(bucket.archived);
+                    dead_rows.push(bucket.dead_rows);
+                    timelines
+                        .push(time_range.map(|(timeline, range)| (*timeline, range.min.as_i64())));
+                    time_maxs.push(time_range.map(|(_, range)| range.max.as_i64()));
+                };
+
+                if bucket.time_ranges.is_empty() {
+                    push(None);
+                }
+                for time_range in &bucket.time_ranges {
+                    push(Some(time_range));
+                }
+            }
+        }
+
+        let mut columns = vec![
+            (
+                COL_COMPONENT,
+                Utf8Array::<i32>::from_slice(components).boxed(),
+            ),
+            (COL_BUCKET, UInt64Array::from_vec(bucket_nrs).boxed()),
+            (COL_ROW_OFFSET, UInt64Array::from_vec(row_offsets).boxed()),
+            (COL_ARCHIVED, BooleanArray::from_slice(archived).boxed()),
+            (COL_DEAD_ROWS, UInt64Array::from_vec(dead_rows).boxed()),
+        ];
+        columns.extend(timelines.into_columns(COL_TIME_MIN));
+        columns.push((COL_TIME_MAX, PrimitiveArray::<i64>::from(time_maxs).boxed()));
+
+        write_table(path, columns, Metadata::new())
+    }
+
+    fn load_components(&mut self, buckets_path: &Path, directory: &Path) -> SnapshotResult<()> {
+        // The metadata of every bucket, indexed by component and bucket number.
+        let mut buckets: HashMap<(ComponentName, u64), ComponentBucket> = HashMap::default();
+
+        let (schema, chunks) = read_file(buckets_path)?;
+        for chunk in &chunks {
+            let table = Table {
+                schema: &schema,
+                chunk,
+            };
+            let components = table.column::<Utf8Array<i32>>(COL_COMPONENT)?;
+            let bucket_nrs = table.column::<UInt64Array>(COL_BUCKET)?;
+            let row_offsets = table.column::<UInt64Array>(COL_ROW_OFFSET)?;
+            let archived = table.column::<BooleanArray>(COL_ARCHIVED)?;
+            let dead_rows = table.column::<UInt64Array>(COL_DEAD_ROWS)?;
+            let timelines = table.timelines(COL_TIME_MIN)?;
+            let time_maxs = table.column::<PrimitiveArray<i64>>(COL_TIME_MAX)?;
+
+            for (i, timeline) in timelines.into_iter().enumerate() {
+                let name = ComponentName::from(components.value(i));
+                let bucket = buckets
+                    .entry((name, bucket_nrs.value(i)))
+                    .or_insert_with(|| ComponentBucket {
+                        name,
+                        row_offset: row_offsets.value(i),
+                        archived: archived.value(i),
+                        time_ranges: Default::default(),
+                        dead_rows: dead_rows.value(i),
+                        chunks: Vec::new(),
+                        spilled: None,
+                        total_rows: 0,
+                        total_size_bytes: 0,
+                        deduplicated_size_bytes: 0,
+                    });
+                if let (Some((timeline, min)), Some(max)) = (timeline, get(time_maxs, i)) {
+                    bucket
+                        .time_ranges
+                        .insert(timeline, TimeRange::new(min.into(), max.into()));
+                }
+            }
+        }
+
+        for (name, datatype, chunks) in read_component_files(directory)? {
+            let mut table = ComponentTable {
+                name,
+                datatype,
+                buckets: Default::default(),
+                dedup_index: Default::default(),
+            };
+
+            for (bucket_nr, data) in chunks.into_iter().enumerate() {
+                let mut bucket = buckets
+                    .remove(&(name, bucket_nr as u64))
+                    .ok_or_else(|| malformed(format!("missing metadata for bucket of {name}")))?;
+
+                bucket.total_rows = data.len() as u64;
+                // Archived buckets are compacted, everything else is made of unit-length chunks.
+                bucket.chunks = if bucket.archived {
+                    vec![data]
+                } else {
+                    unit_chunks(name, &*data)?
+                };
+                bucket.total_size_bytes = bucket
+                    .chunks
+                    .iter()
+                    .map(|chunk| arrow2::compute::aggregate::estimated_bytes_size(&**chunk) as u64)
+                    .sum();
+
+                table.buckets.push_back(bucket);
+            }
+
+            self.components.insert(name, table);
+        }
+
+        if let Some((name, _)) = buckets.keys().next() {
+            return Err(malformed(format!("missing data for bucket of {name}")));
+        }
+
+        Ok(())
+    }
+
+    fn load_timeless_components(&mut self, directory: &Path) -> SnapshotResult<()> {
+        for (name, datatype, chunks) in read_component_files(directory)? {
+            let data = chunks
+                .into_iter()
+                .next()
+                .ok_or_else(|| malformed(format!("missing data for timeless {name}")))?;
+
+            let chunks = unit_chunks(name, &*data)?;
+            let total_size_bytes = chunks
+                .iter()
+                .map(|chunk| arrow2::compute::aggregate::estimated_bytes_size(&**chunk) as u64)
+                .sum();
+
+            self.timeless_components.insert(
+                name,
+                PersistentComponentTable {
+                    name,
+                    datatype,
+                    total_rows: chunks.len() as u64,
+                    total_size_bytes,
+                    deduplicated_size_bytes: 0,
+                    chunks,
+                    dedup_index: Default::default(),
+                },
+            );
+        }
+
+        Ok(())
+    }
+}
+
+// --- Helpers ---
+
+fn get<T: arrow2::types::NativeType>(array: &PrimitiveArray<T>, i: usize) -> Option<T> {
+    array.is_valid(i).then(|| array.value(i))
+}
+
+fn row_index(raw: Option<u64>) -> Option<RowIndex> {
+    raw.and_then(NonZeroU64::new).map(RowIndex)
+}
+
+fn msg_id_array(msg_ids: &[MsgId]) -> Box<dyn Array> {
+    DataCell::from_native(msg_ids).as_arrow()
+}
+
+/// Concatenates the chunks of a component table or bucket into a single `ListArray`.
+///
+/// Fails if there are no chunks at all, e.g. because a spilled bucket couldn't be read back.
+fn concat(name: ComponentName, chunks: &[Box<dyn Array>]) -> SnapshotResult<Box<dyn Array>> {
+    if chunks.is_empty() {
+        return Err(SnapshotError::UnreadableData(name));
+    }
+    let chunks = chunks.iter().map(|chunk| &**chunk).collect::<Vec<_>>();
+    Ok(concatenate(&chunks)?)
+}
+
+/// The opposite of [`concat`]: splits a `ListArray` into unit-length chunks, as if its rows had
+/// been inserted one by one.
+fn unit_chunks(name: ComponentName, data: &dyn Array) -> SnapshotResult<Vec<Box<dyn Array>>> {
+    let list = data
+        .as_any()
+        .downcast_ref::<ListArray<i32>>()
+        .ok_or_else(|| malformed(format!("{name} is not a list array")))?;
+
+    Ok((0..list.len())
+        .map(|i| DataCell::from_arrow(name, list.value(i)).as_arrow_monolist())
+        .collect())
+}
+
+fn component_schema(name: ComponentName, datatype: &DataType) -> Schema {
+    Schema::from(vec![Field::new(
+        name.as_str(),
+        ListArray::<i32>::default_datatype(datatype.clone()),
+        false,
+    )])
+}
+
+/// Removes and re-creates `directory`, so that no stale files are left behind.
+fn clear_dir(directory: PathBuf) -> SnapshotResult<PathBuf> {
+    if directory.exists() {
+        std::fs::remove_dir_all(&directory)?;
+    }
+    std::fs::create_dir_all(&directory)?;
+    Ok(directory)
+}
+
+/// A component table read back from a snapshot: (name, datatype, chunks).
+type ComponentFile = (ComponentName, DataType, Vec<Box<dyn Array>>);
+
+/// Reads back all the component tables in `directory`.
+fn read_component_files(directory: &Path) -> SnapshotResult<Vec<ComponentFile>> {
+    let mut tables = Vec::new();
+    for entry in std::fs::read_dir(directory)? {
+        let (schema, chunks) = read_file(&entry?.path())?;
+        let field = schema
+            .fields
+            .first()
+            .ok_or_else(|| malformed("component table without any column"))?;
+
+        let name = ComponentName::from(field.name.as_str());
+        if !matches!(field.data_type.to_logical_type(), DataType::List(_)) {
+            return Err(malformed(format!("{name} is not a list array")));
+        }
+        let datatype = ListArray::<i32>::get_child_type(&field.data_type).clone();
+        let chunks = chunks
+            .into_iter()
+            .map(|chunk| chunk.into_arrays().swap_remove(0))
+            .collect();
+
+        tables.push((name, datatype, chunks));
+    }
+    Ok(tables)
+}
+
+fn time_type_to_u8(typ: TimeType) -> u8 {
+    match typ {
+        TimeType::Time => 0,
+        TimeType::Sequence => 1,
+    }
+}
+
+fn time_type_from_u8(typ: u8) -> SnapshotResult<TimeType> {
+    match typ {
+        0 => Ok(TimeType::Time),
+        1 => Ok(TimeType::Sequence),
+        _ => Err(malformed(format!("unknown time type {typ}"))),
+    }
+}
+
+/// Builds the (nullable) timeline, timeline type and time columns of a table.
+#[derive(Default)]
+struct TimelineColumns {
+    names: Vec<Option<String>>,
+    types: Vec<Option<u8>>,
+    times: Vec<Option<i64>>,
+}
+
+impl TimelineColumns {
+    fn push(&mut self, time: Option<(Timeline, i64)>) {
+        self.names
+            .push(time.map(|(timeline, _)| timeline.name().to_string()));
+        self.types
+            .push(time.map(|(timeline, _)| time_type_to_u8(timeline.typ())));
+        self.times.push(time.map(|(_, time)| time));
+    }
+
+    fn into_columns(self, time_column: &'static str) -> [(&'static str, Box<dyn Array>); 3] {
+        [
+            (
+                COL_TIMELINE,
+                Utf8Array::<i32>::from_iter(self.names).boxed(),
+            ),
+            (COL_TIMELINE_TYPE, UInt8Array::from(self.types).boxed()),
+            (time_column, PrimitiveArray::<i64>::from(self.times).boxed()),
+        ]
+    }
+}
+
+/// Gives access to the columns of a chunk read back from disk, by name.
+struct Table<'a> {
+    schema: &'a Schema,
+    chunk: &'a Chunk<Box<dyn Array>>,
+}
+
+impl<'a> Table<'a> {
+    fn column<T: Array + 'static>(&self, name: &str) -> SnapshotResult<&'a T> {
+        self.schema
+            .fields
+            .iter()
+            .position(|field| field.name == name)
+            .and_then(|i| self.chunk.arrays()[i].as_any().downcast_ref::<T>())
+            .ok_or_else(|| malformed(format!("missing or bad {name:?} column")))
+    }
+
+    fn msg_ids(&self, name: &str) -> SnapshotResult<Vec<MsgId>> {
+        let column = self
+            .schema
+            .fields
+            .iter()
+            .position(|field| field.name == name)
+            .map(|i| &*self.chunk.arrays()[i])
+            .ok_or_else(|| malformed(format!("missing {name:?} column")))?;
+        Ok(arrow_array_deserialize_iterator::<MsgId>(column)?.collect())
+    }
+
+    /// Reads back the columns written by [`TimelineColumns`].
+    fn timelines(&self, time_column: &str) -> SnapshotResult<Vec<Option<(Timeline, i64)>>> {
+        let names = self.column::<Utf8Array<i32>>(COL_TIMELINE)?;
+        let types = self.column::<UInt8Array>(COL_TIMELINE_TYPE)?;
+        let times = self.column::<PrimitiveArray<i64>>(time_column)?;
+
+        names
+            .iter()
+            .zip(types.iter())
+            .zip(times.iter())
+            .map(|((name, typ), time)| match (name, typ, time) {
+                (Some(name), Some(typ), Some(time)) => {
+                    Ok(Some((Timeline::new(name, time_type_from_u8(*typ)?), *time)))
+                }
+                _ => Ok(None),
+            })
+            .collect()
+    }
+}
+
+/// Writes a single-chunk table.
+fn write_table(
+    path: &Path,
+    columns: Vec<(&'static str, Box<dyn Array>)>,
+    metadata: Metadata,
+) -> SnapshotResult<()> {
+    let schema = Schema::from(
+        columns
+            .iter()
+            .map(|(name, array)| Field::new(*name, array.data_type().clone(), true))
+            .collect::<Vec<_>>(),
+    )
+    .with_metadata(metadata);
+    let arrays = columns.into_iter().map(|(_, array)| array).collect();
+
+    write_file(path, schema, [Ok(Chunk::new(arrays))])
+}
+
+fn write_file(
+    path: &Path,
+    schema: Schema,
+    chunks: impl IntoIterator<Item = SnapshotResult<Chunk<Box<dyn Array>>>>,
+) -> SnapshotResult<()> {
+    use arrow2::io::ipc::write::{FileWriter, WriteOptions};
+
+    let file = std::io::BufWriter::new(std::fs::File::create(path)?);
+    let mut writer = FileWriter::try_new(file, schema, None, WriteOptions { compression: None })?;
+    for chunk in chunks {
+        writer.write(&chunk?, None)?;
+    }
+    writer.finish()?;
+
+    Ok(())
+}
+
+type ArrowChunk = Chunk<Box<dyn Array>>;
+
+fn read_file(path: &Path) -> SnapshotResult<(Schema, Vec<ArrowChunk>)> {
+    use arrow2::io::ipc::read::{read_file_metadata, FileReader};
+
+    let mut file = std::io::BufReader::new(std::fs::File::open(path)?);
+    let metadata = read_file_metadata(&mut file)?;
+    let schema = metadata.schema.clone();
+    let chunks = FileReader::new(file, metadata, None, None).collect::<Result<Vec<_>, _>>()?;
+
+    Ok((schema, chunks))
+}
@@ -0,0 +1,319 @@
+use std::{
+    collections::VecDeque,
+    path::{Path, PathBuf},
+    sync::{Arc, Weak},
+};
+
+use arrow2::{
+    array::Array,
+    chunk::Chunk,
+    datatypes::{Field, Schema},
+};
+use parking_lot::{Mutex, RwLock};
+
+use crate::{ComponentBucket, DataStore};
+
+// --- Errors ---
+
+#[derive(thiserror::Error, Debug)]
+pub enum SpillError {
+    #[error("Failed to access the spill directory")]
+    Io(#[from] std::io::Error),
+
+    #[error("Failed to (de)serialize spilled data")]
+    Arrow(#[from] arrow2::error::Error),
+
+    #[error("Spilling to disk is not supported on this platform")]
+    Unsupported,
+}
+
+pub type SpillResult<T> = ::std::result::Result<T, SpillError>;
+
+// --- Data store ---
+
+impl DataStore {
+    /// Enables spilling of archived component buckets to `directory`.
+    ///
+    /// From now on, whenever a component bucket gets archived (i.e. it is full and a new one takes
+    /// its place), its data is written to `directory` as an Arrow IPC file and dropped from
+    /// memory. The file then gets memory-mapped back on demand, whenever a query needs it.
+    /// At most [`Self::MAX_LOADED_SPILL_FILES`] spill files are kept loaded at any given time:
+    /// past that, the ones that were loaded the longest ago get unloaded.
+    ///
+    /// `directory` is created if it doesn't exist yet, and must be dedicated to this store: spill
+    /// files are named after the component and row offset of their bucket only.
+    /// Spill files are deleted once their bucket gets garbage collected, or the store dropped.
+    ///
+    /// Buckets that were archived before spilling was enabled stay in memory.
+    pub fn enable_spilling(&mut self, directory: impl Into<PathBuf>) -> SpillResult<()> {
+        if cfg!(target_arch = "wasm32") {
+            return Err(SpillError::Unsupported);
+        }
+
+        let directory = directory.into();
+        std::fs::create_dir_all(&directory)?;
+
+        re_log::debug!(directory = %directory.display(), "spilling of component buckets enabled");
+        self.spill_target = Some(SpillTarget {
+            directory,
+            loaded: Default::default(),
+        });
+
+        Ok(())
+    }
+
+    /// How many spill files are kept loaded in memory at most, see [`Self::enable_spilling`].
+    pub const MAX_LOADED_SPILL_FILES: usize = 32;
+
+    /// The directory archived component buckets get spilled to, if any.
+    ///
+    /// See [`Self::enable_spilling`].
+    pub fn spill_directory(&self) -> Option<&Path> {
+        self.spill_target
+            .as_ref()
+            .map(|target| target.directory.as_path())
+    }
+}
+
+/// Where the archived component buckets of a [`DataStore`] get spilled to.
+pub(crate) struct SpillTarget {
+    pub(crate) directory: PathBuf,
+
+    /// The spilled data that is currently loaded in memory, in loading order.
+    ///
+    /// Shared by all the spilled buckets of the store, see [`DataStore::MAX_LOADED_SPILL_FILES`].
+    loaded: Arc<Mutex<VecDeque<Weak<LoadedData>>>>,
+}
+
+/// `None` whenever the spilled data isn't loaded in memory.
+type LoadedData = RwLock<Option<Box<dyn Array>>>;
+
+// --- Component buckets ---
+
+impl ComponentBucket {
+    /// Writes the data of this archived bucket to the spill directory, and drops it from memory.
+    ///
+    /// On failure, the bucket is left untouched.
+    pub(crate) fn spill(&mut self, target: &SpillTarget) -> SpillResult<()> {
+        crate::profile_function!();
+
+        debug_assert!(self.archived, "only archived buckets can be spilled");
+        debug_assert_eq!(self.chunks.len(), 1, "archived buckets are compacted");
+
+        let Some(data) = self.chunks.first() else { return Ok(()) };
+
+        // Component names are namespaced paths (e.g. `rerun.point3d`): keep them readable, but
+        // file-system friendly.
+        // Distinct names can end up sanitized the same way (`a.b` vs. `a_b`), hence the hash.
+        let name = self
+            .name
+            .as_str()
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect::<String>();
+        let path = target.directory.join(format!(
+            "{name}-{:016x}-{}.arrow",
+            self.name.hash(),
+            self.row_offset
+        ));
+
+        let mut spilled = SpilledData::write(path, &**data)?;
+        spilled.loaded_spills = target.loaded.clone();
+        self.spilled = Some(spilled);
+        self.chunks.clear();
+
+        Ok(())
+    }
+}
+
+// --- Spilled data ---
+
+/// The data of a [`ComponentBucket`] that was spilled to disk.
+///
+/// The data gets loaded back whenever it is accessed, until it gets unloaded again to make room
+/// for other spill files (see [`DataStore::MAX_LOADED_SPILL_FILES`]).
+/// The file is deleted once the bucket is dropped.
+pub(crate) struct SpilledData {
+    path: PathBuf,
+
+    /// `None` whenever the data isn't loaded.
+    loaded: Arc<LoadedData>,
+
+    /// All the spilled data of the store that is currently loaded, see [`SpillTarget::loaded`].
+    loaded_spills: Arc<Mutex<VecDeque<Weak<LoadedData>>>>,
+}
+
+impl std::fmt::Debug for SpilledData {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SpilledData")
+            .field("path", &self.path)
+            .field("loaded", &self.loaded.read().is_some())
+            .finish()
+    }
+}
+
+impl SpilledData {
+    /// The name of the one and only column of a spill file.
+    const COLUMN_NAME: &'static str = "data";
+
+    /// Writes `data` to `path`, as an Arrow IPC file with a single column.
+    fn write(path: PathBuf, data: &dyn Array) -> SpillResult<Self> {
+        crate::profile_function!();
+
+        use arrow2::io::ipc::write::{FileWriter, WriteOptions};
+
+        let schema = Schema::from(vec![Field::new(
+            Self::COLUMN_NAME,
+            data.data_type().clone(),
+            false,
+        )]);
+
+        let file = std::io::BufWriter::new(std::fs::File::create(&path)?);
+        let mut writer =
+            FileWriter::try_new(file, schema, None, WriteOptions { compression: None })?;
+        writer.write(&Chunk::new(vec![data.to_boxed()]), None)?;
+        writer.finish()?;
+
+        Ok(Self {
+            path,
+            loaded: Default::default(),
+            loaded_spills: Default::default(),
+        })
+    }
+
+    /// The path of the spill file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns a shallow clone of the spilled data, loading it first if needed.
+    ///
+    /// Returns `None` (and logs an error) if the spill file cannot be read back.
+    pub fn data(&self) -> Option<Box<dyn Array>> {
+        if let Some(data) = self.loaded.read().as_ref() {
+            return Some(data.clone()); // shallow
+        }
+
+        let data = {
+            let mut loaded = self.loaded.write();
+            // Somebody else might have beaten us to it while we were waiting for the lock.
+            if let Some(data) = loaded.as_ref() {
+                return Some(data.clone()); // shallow
+            }
+
+            let data = match load(&self.path) {
+                Ok(data) => data,
+                Err(err) => {
+                    re_log::error!(path = %self.path.display(), %err, "failed to load spilled data");
+                    return None;
+                }
+            };
+            *loaded = Some(data.clone()); // shallow
+            data
+        };
+
+        // Must not be holding our own lock by now: unloading takes other spills' locks, and they
+        // might be doing the very same thing concurrently.
+        self.unload_oldest();
+
+        Some(data)
+    }
+
+    /// Registers this spill as loaded, and unloads the ones that were loaded the longest ago if
+    /// that makes for too many.
+    fn unload_oldest(&self) {
+        let evicted = {
+            let mut loaded_spills = self.loaded_spills.lock();
+            loaded_spills.retain(|loaded| loaded.strong_count() > 0);
+            loaded_spills.push_back(Arc::downgrade(&self.loaded));
+
+            let nb_evicted = loaded_spills
+                .len()
+                .saturating_sub(DataStore::MAX_LOADED_SPILL_FILES);
+            loaded_spills.drain(..nb_evicted).collect::<Vec<_>>()
+        };
+
+        for loaded in evicted.iter().filter_map(Weak::upgrade) {
+            *loaded.write() = None;
+        }
+    }
+}
+
+impl Drop for SpilledData {
+    fn drop(&mut self) {
+        if let Err(err) = std::fs::remove_file(&self.path) {
+            re_log::warn!(path = %self.path.display(), %err, "failed to remove spill file");
+        }
+    }
+}
+
+/// Loads the data of a spill file back in memory.
+///
+/// The file is memory-mapped whenever arrow2 supports it for the datatype at hand, and read the
+/// regular way otherwise.
+#[cfg(not(target_arch = "wasm32"))]
+fn load(path: &Path) -> SpillResult<Box<dyn Array>> {
+    crate::profile_function!();
+
+    let file = std::fs::File::open(path)?;
+    // SAFETY: spill files are private to their store: nobody else is supposed to be modifying
+    // them while they are mapped.
+    let mmap = Arc::new(unsafe { memmap2::Mmap::map(&file)? });
+
+    let metadata = arrow2::io::ipc::read::read_file_metadata(&mut std::io::Cursor::new(&mmap[..]))?;
+
+    let chunk = if metadata
+        .schema
+        .fields
+        .iter()
+        .all(|field| can_mmap(field.data_type()))
+    {
+        // SAFETY: spill files are only ever written by `SpilledData::write`, using the very same
+        // version of arrow2, so their contents are known to be valid.
+        let dictionaries =
+            unsafe { arrow2::mmap::mmap_dictionaries_unchecked(&metadata, mmap.clone()) }?;
+        // SAFETY: same as above.
+        unsafe { arrow2::mmap::mmap_unchecked(&metadata, &dictionaries, mmap, 0) }?
+    } else {
+        let mut reader = arrow2::io::ipc::read::FileReader::new(
+            std::io::Cursor::new(&mmap[..]),
+            metadata,
+            None,
+            None,
+        );
+        reader.next().transpose()?.ok_or_else(|| {
+            SpillError::Arrow(arrow2::error::Error::OutOfSpec(
+                "spill file without any chunk".to_owned(),
+            ))
+        })?
+    };
+
+    chunk.into_arrays().into_iter().next().ok_or_else(|| {
+        SpillError::Arrow(arrow2::error::Error::OutOfSpec(
+            "spill file without any column".to_owned(),
+        ))
+    })
+}
+
+/// Whether arrow2 knows how to memory-map arrays of the given datatype.
+///
+/// It doesn't for unions (which is what e.g. `Transform` and `TensorData` are made of) nor maps.
+#[cfg(not(target_arch = "wasm32"))]
+fn can_mmap(datatype: &arrow2::datatypes::DataType) -> bool {
+    use arrow2::datatypes::DataType;
+
+    match datatype.to_logical_type() {
+        DataType::Union(..) | DataType::Map(..) => false,
+        DataType::List(field) | DataType::LargeList(field) | DataType::FixedSizeList(field, _) => {
+            can_mmap(field.data_type())
+        }
+        DataType::Struct(fields) => fields.iter().all(|field| can_mmap(field.data_type())),
+        DataType::Dictionary(_, values, _) => can_mmap(values),
+        _ => true,
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn load(_path: &Path) -> SpillResult<Box<dyn Array>> {
+    Err(SpillError::Unsupported)
+}
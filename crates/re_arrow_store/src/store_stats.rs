@@ -25,6 +25,16 @@ pub struct DataStoreStats {
     pub total_component_rows: u64,
     pub total_component_size_bytes: u64,
 
+    /// How many of [`Self::total_component_size_bytes`] are shared between identical cells
+    /// rather than actually allocated, i.e. the memory saved by deduplication.
+    ///
+    /// See [`DataStoreConfig::enable_deduplication`].
+    pub total_deduplicated_size_bytes: u64,
+
+    /// Index rows dropped by the garbage collector since the creation of the store.
+    pub total_reclaimed_index_rows: u64,
+    pub total_reclaimed_index_size_bytes: u64,
+
     pub config: DataStoreConfig,
 }
 
@@ -51,6 +61,8 @@ impl DataStoreStats {
         let total_component_size_bytes =
             total_timeless_component_size_bytes + total_temporal_component_size_bytes;
 
+        let total_deduplicated_size_bytes = store.total_deduplicated_size_bytes();
+
         Self {
             total_timeless_index_rows,
             total_timeless_index_size_bytes,
@@ -69,6 +81,11 @@ impl DataStoreStats {
             total_component_rows,
             total_component_size_bytes,
 
+            total_deduplicated_size_bytes,
+
+            total_reclaimed_index_rows: store.reclaimed_index_rows,
+            total_reclaimed_index_size_bytes: store.reclaimed_index_size_bytes,
+
             config: store.config.clone(),
         }
     }
@@ -171,6 +188,25 @@ impl DataStore {
             .map(|table| table.total_buckets())
             .sum()
     }
+
+    /// Returns the size of the component data that is shared between identical cells rather
+    /// than actually allocated, across both timeless and temporal tables, in bytes.
+    ///
+    /// See [`DataStoreConfig::enable_deduplication`].
+    pub fn total_deduplicated_size_bytes(&self) -> u64 {
+        crate::profile_function!();
+        let timeless = self
+            .timeless_components
+            .values()
+            .map(|table| table.deduplicated_size_bytes())
+            .sum::<u64>();
+        let temporal = self
+            .components
+            .values()
+            .map(|table| table.deduplicated_size_bytes())
+            .sum::<u64>();
+        timeless + temporal
+    }
 }
 
 // --- Persistent Indices ---
@@ -229,10 +265,12 @@ impl IndexBucket {
             is_sorted: _,
             time_range: _,
             times,
+            msg_ids,
             indices,
         } = &*self.indices.read();
 
         std::mem::size_of_val(times.as_slice()) as u64
+            + std::mem::size_of_val(msg_ids.as_slice()) as u64
             + indices
                 .values()
                 .map(|index| std::mem::size_of_val(index.as_slice()) as u64)
@@ -252,6 +290,11 @@ impl PersistentComponentTable {
     pub fn total_size_bytes(&self) -> u64 {
         self.total_size_bytes
     }
+
+    /// Returns how many of [`Self::total_size_bytes`] are shared between identical cells.
+    pub fn deduplicated_size_bytes(&self) -> u64 {
+        self.deduplicated_size_bytes
+    }
 }
 
 // --- Components ---
@@ -276,6 +319,14 @@ impl ComponentTable {
     pub fn total_buckets(&self) -> u64 {
         self.buckets.len() as _
     }
+
+    /// Returns how many of [`Self::total_size_bytes`] are shared between identical cells.
+    pub fn deduplicated_size_bytes(&self) -> u64 {
+        self.buckets
+            .iter()
+            .map(|bucket| bucket.deduplicated_size_bytes)
+            .sum()
+    }
 }
 
 impl ComponentBucket {
@@ -0,0 +1,135 @@
+use re_log_types::{ComponentName, EntityPath, MsgId, TimePoint};
+
+use crate::DataStore;
+
+// ---
+
+/// What happened to a row of data, see [`StoreEvent`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StoreEventKind {
+    /// The row was inserted, via [`DataStore::insert_row`] or [`DataStore::insert_table`].
+    Addition,
+
+    /// The row was removed by the garbage collector, see [`DataStore::gc`].
+    Deletion,
+}
+
+/// A single row of data was added to, or removed from, a [`DataStore`].
+///
+/// See [`StoreSubscriber`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StoreEvent {
+    pub kind: StoreEventKind,
+
+    /// The ID of the row.
+    pub msg_id: MsgId,
+
+    pub entity_path: EntityPath,
+
+    /// The timepoint of the row; empty for timeless data.
+    pub timepoint: TimePoint,
+
+    /// The components of the row, cluster key included.
+    pub components: Vec<ComponentName>,
+}
+
+/// Gets notified of every change to the contents of a [`DataStore`].
+///
+/// This makes it possible to maintain derived data (secondary indices, statistics, caches…)
+/// outside of the store, without having to poll it.
+///
+/// See [`DataStore::register_subscriber`].
+pub trait StoreSubscriber: std::any::Any + Send + Sync {
+    /// Called with every batch of changes, right after they were applied to the store.
+    ///
+    /// A single insertion yields a single event, whereas a single garbage collection yields one
+    /// event for every row it removed.
+    fn on_events(&mut self, events: &[StoreEvent]);
+
+    /// Needed for [`DataStore::with_subscriber`].
+    fn as_any(&self) -> &dyn std::any::Any;
+
+    /// Needed for [`DataStore::with_subscriber_mut`].
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any;
+}
+
+/// Identifies a [`StoreSubscriber`] registered on a [`DataStore`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct StoreSubscriberHandle(u64);
+
+impl DataStore {
+    /// Registers a [`StoreSubscriber`], which will be notified of all the changes made to the
+    /// store from now on.
+    ///
+    /// Subscribers are not told about the data that was already in the store when they were
+    /// registered.
+    pub fn register_subscriber(
+        &mut self,
+        subscriber: Box<dyn StoreSubscriber>,
+    ) -> StoreSubscriberHandle {
+        self.subscriber_id += 1;
+        let handle = StoreSubscriberHandle(self.subscriber_id);
+        self.subscribers.push((handle, subscriber));
+        handle
+    }
+
+    /// Unregisters a [`StoreSubscriber`], handing it back to the caller.
+    ///
+    /// Returns `None` if there is no subscriber registered with that handle.
+    pub fn unregister_subscriber(
+        &mut self,
+        handle: StoreSubscriberHandle,
+    ) -> Option<Box<dyn StoreSubscriber>> {
+        let pos = self.subscribers.iter().position(|(h, _)| *h == handle)?;
+        Some(self.subscribers.remove(pos).1)
+    }
+
+    /// Gives access to a registered subscriber, e.g. to read the data it derived.
+    ///
+    /// Returns `None` if there is no subscriber of type `T` registered with that handle.
+    pub fn with_subscriber<T: StoreSubscriber, R>(
+        &self,
+        handle: StoreSubscriberHandle,
+        f: impl FnOnce(&T) -> R,
+    ) -> Option<R> {
+        self.subscribers
+            .iter()
+            .find(|(h, _)| *h == handle)
+            .and_then(|(_, subscriber)| subscriber.as_any().downcast_ref::<T>())
+            .map(f)
+    }
+
+    /// Gives mutable access to a registered subscriber.
+    ///
+    /// Returns `None` if there is no subscriber of type `T` registered with that handle.
+    pub fn with_subscriber_mut<T: StoreSubscriber, R>(
+        &mut self,
+        handle: StoreSubscriberHandle,
+        f: impl FnOnce(&mut T) -> R,
+    ) -> Option<R> {
+        self.subscribers
+            .iter_mut()
+            .find(|(h, _)| *h == handle)
+            .and_then(|(_, subscriber)| subscriber.as_any_mut().downcast_mut::<T>())
+            .map(f)
+    }
+
+    /// Are there any subscribers to notify at all?
+    ///
+    /// Building events isn't free: check this first.
+    pub(crate) fn has_subscribers(&self) -> bool {
+        !self.subscribers.is_empty()
+    }
+
+    pub(crate) fn notify_subscribers(&mut self, events: &[StoreEvent]) {
+        if events.is_empty() {
+            return;
+        }
+
+        crate::profile_function!();
+
+        for (_, subscriber) in &mut self.subscribers {
+            subscriber.on_events(events);
+        }
+    }
+}
@@ -0,0 +1,166 @@
+use re_log_types::{ComponentName, EntityPath, TimeInt, Timeline};
+
+use crate::{DataStore, IndexRowNr, LatestAtQuery, RangeQuery, RowIndex};
+
+// --- Queries ---
+
+/// Selects an entity along with all of its descendants, i.e. a whole subtree of the entity
+/// hierarchy.
+///
+/// Can be parsed from either a plain entity path prefix (`foo/bar`) or its glob form
+/// (`foo/bar/**`), both of which match `foo/bar` itself as well as everything below it.
+/// `**` on its own matches all entities.
+///
+/// See [`DataStore::latest_at_subtree`] & [`DataStore::range_subtree`].
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct EntityPathPrefix {
+    root: EntityPath,
+}
+
+impl EntityPathPrefix {
+    /// Matches `root` and all of its descendants.
+    #[inline]
+    pub fn new(root: EntityPath) -> Self {
+        Self { root }
+    }
+
+    /// Parses either `foo/bar` or `foo/bar/**`.
+    ///
+    /// Panics if the path itself is malformed, just like `EntityPath::from`.
+    pub fn parse(path: &str) -> Self {
+        let path = path.trim();
+        let path = path
+            .strip_suffix("/**")
+            .or_else(|| path.strip_suffix("**"))
+            .unwrap_or(path);
+
+        if path.is_empty() {
+            Self::new(EntityPath::root())
+        } else {
+            Self::new(EntityPath::from(path))
+        }
+    }
+
+    /// The topmost entity of the subtree.
+    #[inline]
+    pub fn root(&self) -> &EntityPath {
+        &self.root
+    }
+
+    /// Is `ent_path` part of this subtree?
+    #[inline]
+    pub fn matches(&self, ent_path: &EntityPath) -> bool {
+        ent_path == &self.root || ent_path.is_descendant_of(&self.root)
+    }
+}
+
+impl From<EntityPath> for EntityPathPrefix {
+    #[inline]
+    fn from(root: EntityPath) -> Self {
+        Self::new(root)
+    }
+}
+
+impl From<&str> for EntityPathPrefix {
+    #[inline]
+    fn from(path: &str) -> Self {
+        Self::parse(path)
+    }
+}
+
+impl std::fmt::Display for EntityPathPrefix {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.root.is_root() {
+            f.write_str("**")
+        } else {
+            f.write_fmt(format_args!("{}/**", self.root))
+        }
+    }
+}
+
+// --- Data store ---
+
+impl DataStore {
+    /// Same as [`Self::latest_at`], but for all the entities within the `prefix` subtree at once.
+    ///
+    /// Returns the row indices of every entity for which the `primary` component could be found,
+    /// along with the path of said entity, in entity path order.
+    pub fn latest_at_subtree<const N: usize>(
+        &self,
+        query: &LatestAtQuery,
+        prefix: &EntityPathPrefix,
+        primary: ComponentName,
+        components: &[ComponentName; N],
+    ) -> Vec<(EntityPath, [Option<RowIndex>; N])> {
+        crate::profile_function!();
+
+        self.entities_in_subtree(query.timeline, prefix)
+            .into_iter()
+            .filter_map(|ent_path| {
+                let row_indices = self.latest_at(query, &ent_path, primary, components)?;
+                Some((ent_path, row_indices))
+            })
+            .collect()
+    }
+
+    /// Same as [`Self::range`], but for all the entities within the `prefix` subtree at once.
+    ///
+    /// Results are yielded one entity at a time, in entity path order: the results of each
+    /// entity come in the same order as they would with [`Self::range`], along with the path of
+    /// said entity.
+    pub fn range_subtree<'a, const N: usize>(
+        &'a self,
+        query: &RangeQuery,
+        prefix: &EntityPathPrefix,
+        components: [ComponentName; N],
+    ) -> impl Iterator<
+        Item = (
+            EntityPath,
+            Option<TimeInt>,
+            IndexRowNr,
+            [Option<RowIndex>; N],
+        ),
+    > + 'a {
+        // Beware! This merely measures the time it takes to gather all the necessary metadata
+        // for building the returned iterator.
+        crate::profile_function!();
+
+        let query = query.clone();
+        self.entities_in_subtree(query.timeline, prefix)
+            .into_iter()
+            .flat_map(move |ent_path| {
+                self.range(&query, &ent_path, components).map(
+                    move |(time, idx_row_nr, row_indices)| {
+                        (ent_path.clone(), time, idx_row_nr, row_indices)
+                    },
+                )
+            })
+    }
+
+    /// All the entities within the `prefix` subtree that have any data at all on `timeline`,
+    /// timeless data included, in entity path order.
+    pub fn entities_in_subtree(
+        &self,
+        timeline: Timeline,
+        prefix: &EntityPathPrefix,
+    ) -> Vec<EntityPath> {
+        crate::profile_function!();
+
+        let temporal = self
+            .indices
+            .iter()
+            .filter(|((index_timeline, _), _)| *index_timeline == timeline)
+            .map(|(_, index)| &index.ent_path);
+        let timeless = self.timeless_indices.values().map(|index| &index.ent_path);
+
+        let mut ent_paths = temporal
+            .chain(timeless)
+            .filter(|ent_path| prefix.matches(ent_path))
+            .cloned()
+            .collect::<Vec<_>>();
+        ent_paths.sort();
+        ent_paths.dedup();
+
+        ent_paths
+    }
+}
@@ -0,0 +1,542 @@
+use std::{
+    collections::{BTreeMap, HashSet},
+    ops::Bound,
+};
+
+use arrow2::{
+    array::{Array, PrimitiveArray, Utf8Array},
+    datatypes::DataType,
+};
+use re_log_types::ComponentName;
+
+use crate::{DataStore, RowIndex, RowIndexKind};
+
+// --- Errors ---
+
+#[derive(thiserror::Error, Debug)]
+pub enum ValueIndexError {
+    #[error(
+        "Cannot index the values of {component}: only integers, floats and strings are \
+            supported, got {datatype:?}"
+    )]
+    UnsupportedDatatype {
+        component: ComponentName,
+        datatype: DataType,
+    },
+}
+
+pub type ValueIndexResult<T> = ::std::result::Result<T, ValueIndexError>;
+
+// --- Filters ---
+
+/// A single component value to filter on, see [`ValueFilter`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum ComponentValue {
+    Int(i64),
+    UInt(u64),
+    Float(f64),
+    String(String),
+}
+
+macro_rules! impl_from_for_component_value {
+    ($variant:ident($inner:ty): $($ty:ty),+) => {
+        $(
+            impl From<$ty> for ComponentValue {
+                #[inline]
+                fn from(v: $ty) -> Self {
+                    Self::$variant(v as $inner)
+                }
+            }
+        )+
+    };
+}
+
+impl_from_for_component_value!(Int(i64): i8, i16, i32, i64);
+impl_from_for_component_value!(UInt(u64): u8, u16, u32, u64);
+impl_from_for_component_value!(Float(f64): f32, f64);
+
+impl From<&str> for ComponentValue {
+    #[inline]
+    fn from(v: &str) -> Self {
+        Self::String(v.to_owned())
+    }
+}
+
+impl From<String> for ComponentValue {
+    #[inline]
+    fn from(v: String) -> Self {
+        Self::String(v)
+    }
+}
+
+/// What the values of a [`ValueFilter`]'s component must look like for a row to match.
+///
+/// A row matches as soon as any one of its instances does.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ValuePredicate {
+    /// Equal to the given value.
+    Eq(ComponentValue),
+
+    /// Within the given bounds.
+    Range(Bound<ComponentValue>, Bound<ComponentValue>),
+
+    /// A string that contains the given substring.
+    Contains(String),
+
+    /// Equal to any one of the given values.
+    AnyOf(Vec<ComponentValue>),
+}
+
+/// Only keep the rows whose values for `component` satisfy `predicate`.
+///
+/// `component` must have a value index, see [`DataStore::enable_value_index`].
+///
+/// See [`crate::RangeQuery::with_filter`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct ValueFilter {
+    pub component: ComponentName,
+    pub predicate: ValuePredicate,
+}
+
+impl ValueFilter {
+    #[inline]
+    pub fn new(component: ComponentName, predicate: ValuePredicate) -> Self {
+        Self {
+            component,
+            predicate,
+        }
+    }
+
+    /// `component == value`
+    #[inline]
+    pub fn eq(component: ComponentName, value: impl Into<ComponentValue>) -> Self {
+        Self::new(component, ValuePredicate::Eq(value.into()))
+    }
+
+    /// `component > value`
+    #[inline]
+    pub fn gt(component: ComponentName, value: impl Into<ComponentValue>) -> Self {
+        let predicate = ValuePredicate::Range(Bound::Excluded(value.into()), Bound::Unbounded);
+        Self::new(component, predicate)
+    }
+
+    /// `component >= value`
+    #[inline]
+    pub fn ge(component: ComponentName, value: impl Into<ComponentValue>) -> Self {
+        let predicate = ValuePredicate::Range(Bound::Included(value.into()), Bound::Unbounded);
+        Self::new(component, predicate)
+    }
+
+    /// `component < value`
+    #[inline]
+    pub fn lt(component: ComponentName, value: impl Into<ComponentValue>) -> Self {
+        let predicate = ValuePredicate::Range(Bound::Unbounded, Bound::Excluded(value.into()));
+        Self::new(component, predicate)
+    }
+
+    /// `component <= value`
+    #[inline]
+    pub fn le(component: ComponentName, value: impl Into<ComponentValue>) -> Self {
+        let predicate = ValuePredicate::Range(Bound::Unbounded, Bound::Included(value.into()));
+        Self::new(component, predicate)
+    }
+
+    /// `component` contains the substring `pattern`.
+    #[inline]
+    pub fn contains(component: ComponentName, pattern: impl Into<String>) -> Self {
+        Self::new(component, ValuePredicate::Contains(pattern.into()))
+    }
+
+    /// `component` is equal to any one of `values`.
+    #[inline]
+    pub fn any_of<V: Into<ComponentValue>>(
+        component: ComponentName,
+        values: impl IntoIterator<Item = V>,
+    ) -> Self {
+        let values = values.into_iter().map(Into::into).collect();
+        Self::new(component, ValuePredicate::AnyOf(values))
+    }
+}
+
+impl std::fmt::Display for ValueFilter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let component = self.component;
+        match &self.predicate {
+            ValuePredicate::Eq(value) => f.write_fmt(format_args!("{component} == {value:?}")),
+            ValuePredicate::Range(min, max) => {
+                f.write_fmt(format_args!("{component} in ({min:?}, {max:?})"))
+            }
+            ValuePredicate::Contains(pattern) => {
+                f.write_fmt(format_args!("{component} contains {pattern:?}"))
+            }
+            ValuePredicate::AnyOf(values) => {
+                f.write_fmt(format_args!("{component} any of {values:?}"))
+            }
+        }
+    }
+}
+
+// --- Data store ---
+
+impl DataStore {
+    /// Starts maintaining an index of all the values of `component`, so that rows can be
+    /// filtered on these values without having to look at their data, see [`ValueFilter`].
+    ///
+    /// Only components made of plain integers, floats or strings can be indexed, e.g. class IDs,
+    /// labels or scalars.
+    /// Indexing the cluster key makes it cheap to look up the rows that hold a given instance,
+    /// auto-generated cluster keys included.
+    ///
+    /// The index is built from all the data currently in the store, and then kept up to date
+    /// with every insertion and garbage collection.
+    /// Does nothing if `component` is already indexed.
+    pub fn enable_value_index(&mut self, component: ComponentName) -> ValueIndexResult<()> {
+        crate::profile_function!();
+
+        if self.value_indices.contains_key(&component) {
+            return Ok(());
+        }
+
+        let datatype = self
+            .components
+            .get(&component)
+            .map(|table| &table.datatype)
+            .or_else(|| {
+                self.timeless_components
+                    .get(&component)
+                    .map(|table| &table.datatype)
+            });
+        if let Some(datatype) = datatype {
+            if ValueKind::from_datatype(datatype).is_none() {
+                return Err(ValueIndexError::UnsupportedDatatype {
+                    component,
+                    datatype: datatype.clone(),
+                });
+            }
+        }
+
+        let mut index = ValueIndex::default();
+
+        // Row #0 is always a fake, empty row.
+        // TODO(#1619): the whole fake row thing needs to go
+
+        if let Some(table) = self.timeless_components.get(&component) {
+            for row_nr in 1..table.chunks.len() as u64 {
+                let row_idx = RowIndex::from_u63(RowIndexKind::Timeless, row_nr);
+                index.insert(component, row_idx, &*table.get(row_idx));
+            }
+        }
+
+        if let Some(table) = self.components.get(&component) {
+            for bucket in &table.buckets {
+                let row_nrs = bucket.row_offset.max(1)..bucket.row_offset + bucket.total_rows;
+                for row_nr in row_nrs {
+                    let row_idx = RowIndex::from_u63(RowIndexKind::Temporal, row_nr);
+                    if let Some(data) = bucket.get(row_idx) {
+                        index.insert(component, row_idx, &*data);
+                    }
+                }
+            }
+        }
+
+        self.value_indices.insert(component, index);
+
+        Ok(())
+    }
+
+    /// Stops maintaining the value index of `component`, see [`Self::enable_value_index`].
+    pub fn disable_value_index(&mut self, component: ComponentName) {
+        self.value_indices.remove(&component);
+    }
+
+    /// Does `component` have a value index? See [`Self::enable_value_index`].
+    #[inline]
+    pub fn has_value_index(&self, component: ComponentName) -> bool {
+        self.value_indices.contains_key(&component)
+    }
+
+    /// Returns the rows of `filter.component` whose values match `filter`.
+    ///
+    /// Returns `None` if there is no value index for that component.
+    pub(crate) fn filter_rows(&self, filter: &ValueFilter) -> Option<HashSet<RowIndex>> {
+        crate::profile_function!();
+        let index = self.value_indices.get(&filter.component)?;
+        Some(index.matching_rows(&filter.predicate))
+    }
+}
+
+// --- Value index ---
+
+/// Maps every value of a component to the rows that hold it.
+///
+/// See [`DataStore::enable_value_index`].
+#[derive(Debug, Default)]
+pub(crate) struct ValueIndex {
+    /// `None` until the first non-empty row gets indexed.
+    kind: Option<ValueKind>,
+
+    rows: BTreeMap<ValueKey, HashSet<RowIndex>>,
+}
+
+impl ValueIndex {
+    /// Indexes all the values in `data` as belonging to `row_idx`.
+    pub(crate) fn insert(&mut self, component: ComponentName, row_idx: RowIndex, data: &dyn Array) {
+        let Some(kind) = ValueKind::from_datatype(data.data_type()) else {
+            re_log::warn_once!(
+                "Cannot index the values of {component}: unsupported datatype {:?}",
+                data.data_type()
+            );
+            return;
+        };
+        self.kind = Some(kind);
+
+        for key in ValueKey::from_array(kind, data) {
+            self.rows.entry(key).or_default().insert(row_idx);
+        }
+    }
+
+    /// Forgets about every row for which `is_alive` returns false.
+    pub(crate) fn retain(&mut self, mut is_alive: impl FnMut(RowIndex) -> bool) {
+        self.rows.retain(|_, rows| {
+            rows.retain(|row_idx| is_alive(*row_idx));
+            !rows.is_empty()
+        });
+    }
+
+    fn matching_rows(&self, predicate: &ValuePredicate) -> HashSet<RowIndex> {
+        // Nothing was ever indexed, so nothing can match.
+        let Some(kind) = self.kind else { return Default::default() };
+
+        let keys: Box<dyn Iterator<Item = (&ValueKey, &HashSet<RowIndex>)> + '_> = match predicate {
+            ValuePredicate::Eq(value) => {
+                let (min, max) = (
+                    ValueKey::lower_bound(kind, &Bound::Included(value.clone())),
+                    ValueKey::upper_bound(kind, &Bound::Included(value.clone())),
+                );
+                range(&self.rows, min, max)
+            }
+            ValuePredicate::Range(min, max) => range(
+                &self.rows,
+                ValueKey::lower_bound(kind, min),
+                ValueKey::upper_bound(kind, max),
+            ),
+            ValuePredicate::Contains(pattern) => Box::new(self.rows.iter().filter(
+                move |(key, _)| matches!(key, ValueKey::Utf8(s) if s.contains(pattern.as_str())),
+            )),
+            ValuePredicate::AnyOf(values) => {
+                return values
+                    .iter()
+                    .flat_map(|value| self.matching_rows(&ValuePredicate::Eq(value.clone())))
+                    .collect();
+            }
+        };
+
+        keys.flat_map(|(_, rows)| rows.iter().copied()).collect()
+    }
+}
+
+/// Same as `BTreeMap::range`, but doesn't panic on empty or inverted bounds.
+fn range<'a>(
+    rows: &'a BTreeMap<ValueKey, HashSet<RowIndex>>,
+    min: Option<Bound<ValueKey>>,
+    max: Option<Bound<ValueKey>>,
+) -> Box<dyn Iterator<Item = (&'a ValueKey, &'a HashSet<RowIndex>)> + 'a> {
+    let (Some(min), Some(max)) = (min, max) else { return Box::new(std::iter::empty()) };
+
+    let is_empty = match (&min, &max) {
+        (Bound::Included(min), Bound::Included(max)) => min > max,
+        (
+            Bound::Included(min) | Bound::Excluded(min),
+            Bound::Included(max) | Bound::Excluded(max),
+        ) => min >= max,
+        _ => false,
+    };
+    if is_empty {
+        return Box::new(std::iter::empty());
+    }
+
+    Box::new(rows.range((min, max)))
+}
+
+/// The kind of values held by an indexed component.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ValueKind {
+    Int,
+    Float,
+    Utf8,
+}
+
+impl ValueKind {
+    fn from_datatype(datatype: &DataType) -> Option<Self> {
+        match datatype.to_logical_type() {
+            DataType::Int8
+            | DataType::Int16
+            | DataType::Int32
+            | DataType::Int64
+            | DataType::UInt8
+            | DataType::UInt16
+            | DataType::UInt32
+            | DataType::UInt64 => Some(Self::Int),
+            DataType::Float32 | DataType::Float64 => Some(Self::Float),
+            DataType::Utf8 | DataType::LargeUtf8 => Some(Self::Utf8),
+            _ => None,
+        }
+    }
+}
+
+/// An indexed value.
+///
+/// All the values of a given component are of the same kind, so the ordering across kinds
+/// doesn't matter.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum ValueKey {
+    /// Wide enough for both `i64`s and `u64`s.
+    Int(i128),
+    Float(TotalF64),
+    Utf8(String),
+}
+
+impl ValueKey {
+    fn from_array(kind: ValueKind, array: &dyn Array) -> Vec<Self> {
+        macro_rules! collect {
+            ($A:ty, $f:expr) => {
+                array
+                    .as_any()
+                    .downcast_ref::<$A>()
+                    .unwrap()
+                    .iter()
+                    .flatten()
+                    .map($f)
+                    .collect()
+            };
+        }
+
+        match (kind, array.data_type().to_logical_type()) {
+            (ValueKind::Int, DataType::Int8) => {
+                collect!(PrimitiveArray<i8>, |v| Self::Int(*v as _))
+            }
+            (ValueKind::Int, DataType::Int16) => {
+                collect!(PrimitiveArray<i16>, |v| Self::Int(*v as _))
+            }
+            (ValueKind::Int, DataType::Int32) => {
+                collect!(PrimitiveArray<i32>, |v| Self::Int(*v as _))
+            }
+            (ValueKind::Int, DataType::Int64) => {
+                collect!(PrimitiveArray<i64>, |v| Self::Int(*v as _))
+            }
+            (ValueKind::Int, DataType::UInt8) => {
+                collect!(PrimitiveArray<u8>, |v| Self::Int(*v as _))
+            }
+            (ValueKind::Int, DataType::UInt16) => {
+                collect!(PrimitiveArray<u16>, |v| Self::Int(*v as _))
+            }
+            (ValueKind::Int, DataType::UInt32) => {
+                collect!(PrimitiveArray<u32>, |v| Self::Int(*v as _))
+            }
+            (ValueKind::Int, DataType::UInt64) => {
+                collect!(PrimitiveArray<u64>, |v| Self::Int(*v as _))
+            }
+            (ValueKind::Float, DataType::Float32) => {
+                collect!(PrimitiveArray<f32>, |v| Self::float(*v as _))
+            }
+            (ValueKind::Float, DataType::Float64) => {
+                collect!(PrimitiveArray<f64>, |v| Self::float(*v))
+            }
+            (ValueKind::Utf8, DataType::Utf8) => {
+                collect!(Utf8Array<i32>, |v| Self::Utf8(v.to_owned()))
+            }
+            (ValueKind::Utf8, DataType::LargeUtf8) => {
+                collect!(Utf8Array<i64>, |v| Self::Utf8(v.to_owned()))
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    fn float(v: f64) -> Self {
+        // `0.0 == -0.0`, but not as far as their total ordering is concerned.
+        Self::Float(TotalF64(if v == 0.0 { 0.0 } else { v }))
+    }
+
+    /// Converts a lower bound on the values of a component of the given `kind` into a bound on
+    /// its keys.
+    ///
+    /// Returns `None` if no value could ever satisfy the bound.
+    fn lower_bound(kind: ValueKind, bound: &Bound<ComponentValue>) -> Option<Bound<ValueKey>> {
+        Self::bound(kind, bound, true)
+    }
+
+    /// Same as [`Self::lower_bound`], for upper bounds.
+    fn upper_bound(kind: ValueKind, bound: &Bound<ComponentValue>) -> Option<Bound<ValueKey>> {
+        Self::bound(kind, bound, false)
+    }
+
+    fn bound(
+        kind: ValueKind,
+        bound: &Bound<ComponentValue>,
+        is_lower: bool,
+    ) -> Option<Bound<ValueKey>> {
+        let (value, is_inclusive) = match bound {
+            Bound::Included(value) => (value, true),
+            Bound::Excluded(value) => (value, false),
+            Bound::Unbounded => return Some(Bound::Unbounded),
+        };
+        let wrap = |key| {
+            if is_inclusive {
+                Bound::Included(key)
+            } else {
+                Bound::Excluded(key)
+            }
+        };
+
+        match (kind, value) {
+            (ValueKind::Int, ComponentValue::Int(v)) => Some(wrap(Self::Int(*v as _))),
+            (ValueKind::Int, ComponentValue::UInt(v)) => Some(wrap(Self::Int(*v as _))),
+            // Integers can't hold fractional values: round to the closest integer that still
+            // satisfies the bound.
+            (ValueKind::Int, ComponentValue::Float(v)) => {
+                if v.is_nan() {
+                    return None;
+                }
+                let v = match (is_lower, is_inclusive) {
+                    (true, true) => v.ceil(),
+                    (true, false) => v.floor() + 1.0,
+                    (false, true) => v.floor(),
+                    (false, false) => v.ceil() - 1.0,
+                };
+                Some(Bound::Included(Self::Int(v as _)))
+            }
+            (ValueKind::Float, ComponentValue::Int(v)) => Some(wrap(Self::float(*v as _))),
+            (ValueKind::Float, ComponentValue::UInt(v)) => Some(wrap(Self::float(*v as _))),
+            (ValueKind::Float, ComponentValue::Float(v)) => Some(wrap(Self::float(*v))),
+            (ValueKind::Utf8, ComponentValue::String(v)) => Some(wrap(Self::Utf8(v.clone()))),
+            _ => None,
+        }
+    }
+}
+
+/// A `f64` with a total ordering, so that it can be used as a key.
+#[derive(Debug, Clone, Copy)]
+struct TotalF64(f64);
+
+impl PartialEq for TotalF64 {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other).is_eq()
+    }
+}
+
+impl Eq for TotalF64 {}
+
+impl PartialOrd for TotalF64 {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for TotalF64 {
+    #[inline]
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.0.total_cmp(&other.0)
+    }
+}
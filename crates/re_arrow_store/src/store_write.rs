@@ -1,4 +1,7 @@
-use arrow2::datatypes::DataType;
+use arrow2::{
+    array::{Array, ListArray},
+    datatypes::DataType,
+};
 use itertools::Itertools as _;
 use nohash_hasher::IntMap;
 use parking_lot::RwLock;
@@ -10,8 +13,9 @@ use re_log_types::{
 };
 
 use crate::{
-    ComponentBucket, ComponentTable, DataStore, DataStoreConfig, IndexBucket, IndexBucketIndices,
-    IndexTable, PersistentComponentTable, PersistentIndexTable, RowIndex, RowIndexKind, TimeIndex,
+    store_spill::SpillTarget, ArrayExt, ComponentBucket, ComponentTable, DataStore,
+    DataStoreConfig, IndexBucket, IndexBucketIndices, IndexTable, PersistentComponentTable,
+    PersistentIndexTable, RowIndex, RowIndexKind, StoreEvent, StoreEventKind, TimeIndex,
 };
 
 // TODO(#1619):
@@ -123,13 +127,27 @@ impl DataStore {
                     .indices
                     .entry((*timeline, ent_path_hash))
                     .or_insert_with(|| IndexTable::new(self.cluster_key, *timeline, ent_path));
-                index.insert(&self.config, *time, &row_indices)?;
+                index.insert(&self.config, *time, *row_id, &row_indices)?;
             }
         }
 
         // This is valuable information, even for a timeless timepoint!
         self.messages.insert(*row_id, timepoint.clone());
 
+        if self.has_subscribers() {
+            let mut components = cells.iter().map(|cell| cell.component_name()).collect_vec();
+            if cluster_cell_pos.is_none() {
+                components.push(self.cluster_key); // auto-generated
+            }
+            self.notify_subscribers(&[StoreEvent {
+                kind: StoreEventKind::Addition,
+                msg_id: *row_id,
+                entity_path: ent_path.clone(),
+                timepoint: timepoint.clone(),
+                components,
+            }]);
+        }
+
         Ok(())
     }
 
@@ -160,9 +178,10 @@ impl DataStore {
             );
         }
 
+        let cluster_key = self.cluster_key;
         for cell in cells
             .iter()
-            .filter(|cell| cell.component_name() != self.cluster_key)
+            .filter(|cell| cell.component_name() != cluster_key)
         {
             let component = cell.component_name();
 
@@ -171,8 +190,10 @@ impl DataStore {
                 .entry(cell.component_name())
                 .or_insert_with(|| PersistentComponentTable::new(component, cell.datatype()));
 
-            let row_idx = table.push_cell(cell);
+            let row_idx = table.push_cell(&self.config, cell);
             row_indices.insert(component, row_idx);
+
+            self.update_value_index(row_idx, cell);
         }
 
         Ok(())
@@ -206,9 +227,10 @@ impl DataStore {
             );
         }
 
+        let cluster_key = self.cluster_key;
         for cell in cells
             .iter()
-            .filter(|cell| cell.component_name() != self.cluster_key)
+            .filter(|cell| cell.component_name() != cluster_key)
         {
             let component = cell.component_name();
 
@@ -217,8 +239,11 @@ impl DataStore {
                 .entry(component)
                 .or_insert_with(|| ComponentTable::new(component, cell.datatype()));
 
-            let row_idx = table.push_cell(&self.config, time_point, cell);
+            let row_idx =
+                table.push_cell(&self.config, self.spill_target.as_ref(), time_point, cell);
             row_indices.insert(component, row_idx);
+
+            self.update_value_index(row_idx, cell);
         }
 
         Ok(())
@@ -306,9 +331,10 @@ impl DataStore {
                     .or_insert_with(|| {
                         PersistentComponentTable::new(self.cluster_key, cell.datatype())
                     });
-                let row_idx = table.push_cell(&cell);
+                let row_idx = table.push_cell(&self.config, &cell);
 
                 self.cluster_comp_cache.insert(cluster_len, row_idx);
+                self.update_value_index(row_idx, &cell);
 
                 Ok(row_idx)
             }
@@ -323,20 +349,31 @@ impl DataStore {
                         .or_insert_with(|| {
                             PersistentComponentTable::new(self.cluster_key, cell.datatype())
                         });
-                    table.push_cell(cell)
+                    table.push_cell(&self.config, cell)
                 } else {
                     let table = self
                         .components
                         .entry(self.cluster_key)
                         .or_insert_with(|| ComponentTable::new(self.cluster_key, cell.datatype()));
-                    table.push_cell(&self.config, time_point, cell)
+                    table.push_cell(&self.config, self.spill_target.as_ref(), time_point, cell)
                 };
 
+                self.update_value_index(row_idx, cell);
+
                 Ok(row_idx)
             }
         }
     }
 
+    /// Indexes the values of `cell` as belonging to `row_idx`, if its component has a value
+    /// index.
+    fn update_value_index(&mut self, row_idx: RowIndex, cell: &DataCell) {
+        let component = cell.component_name();
+        if let Some(index) = self.value_indices.get_mut(&component) {
+            index.insert(component, row_idx, cell.as_arrow_ref());
+        }
+    }
+
     pub fn clear_msg_metadata(&mut self, drop_msg_ids: &ahash::HashSet<MsgId>) {
         crate::profile_function!();
 
@@ -411,6 +448,7 @@ impl IndexTable {
         &mut self,
         config: &DataStoreConfig,
         time: TimeInt,
+        msg_id: MsgId,
         indices: &IntMap<ComponentName, RowIndex>,
     ) -> anyhow::Result<()> {
         crate::profile_function!();
@@ -443,7 +481,7 @@ impl IndexTable {
                 );
 
                 self.buckets.insert(min, second_half);
-                return self.insert(config, time, indices);
+                return self.insert(config, time, msg_id, indices);
             }
 
             // We couldn't split the bucket, either because it's already too small, or because it
@@ -497,12 +535,13 @@ impl IndexTable {
                                 is_sorted: true,
                                 time_range: TimeRange::new(time, time),
                                 times: Default::default(),
+                                msg_ids: Default::default(),
                                 indices: Default::default(),
                             }),
                             cluster_key: self.cluster_key,
                         },
                     );
-                    return self.insert(config, time, indices);
+                    return self.insert(config, time, msg_id, indices);
                 }
             }
 
@@ -528,7 +567,7 @@ impl IndexTable {
             "inserted into index table"
         );
 
-        bucket.insert(time, indices)?;
+        bucket.insert(time, msg_id, indices)?;
 
         // Insert components last, only if bucket-insert succeeded.
         self.all_components.extend(indices.keys());
@@ -550,6 +589,7 @@ impl IndexBucket {
     pub fn insert(
         &mut self,
         time: TimeInt,
+        msg_id: MsgId,
         row_indices: &IntMap<ComponentName, RowIndex>,
     ) -> anyhow::Result<()> {
         crate::profile_function!();
@@ -559,11 +599,13 @@ impl IndexBucket {
             is_sorted,
             time_range,
             times,
+            msg_ids,
             indices,
         } = &mut *guard;
 
         // append time to primary index and update time range appropriately
         times.push(time.as_i64());
+        msg_ids.push(msg_id);
         *time_range = TimeRange::new(time_range.min.min(time), time_range.max.max(time));
 
         // append components to secondary indices (2-way merge)
@@ -682,6 +724,7 @@ impl IndexBucket {
             is_sorted: _,
             time_range: time_range1,
             times: times1,
+            msg_ids: msg_ids1,
             indices: indices1,
         } = &mut *indices;
 
@@ -710,6 +753,9 @@ impl IndexBucket {
             // this updates `times1` in-place!
             let times2 = times1.split_off(split_idx);
 
+            // this updates `msg_ids1` in-place!
+            let msg_ids2 = msg_ids1.split_off(split_idx);
+
             // this updates `indices1` in-place!
             let indices2: IntMap<_, _> = indices1
                 .iter_mut()
@@ -727,6 +773,7 @@ impl IndexBucket {
                         is_sorted: true,
                         time_range: time_range2,
                         times: times2,
+                        msg_ids: msg_ids2,
                         indices: indices2,
                     }),
                     cluster_key: self.cluster_key,
@@ -895,14 +942,19 @@ impl PersistentComponentTable {
             name,
             datatype: datatype.clone(),
             chunks,
+            dedup_index: Default::default(),
             total_rows,
             total_size_bytes,
+            deduplicated_size_bytes: 0,
         }
     }
 
     /// Pushes `cell` to the end of the bucket, returning the _global_ `RowIndex` of the
     /// freshly added row.
-    pub fn push_cell(&mut self, cell: &DataCell) -> RowIndex {
+    ///
+    /// If deduplication is enabled and an identical cell was pushed before, the new row shares
+    /// the data of the existing one.
+    pub fn push_cell(&mut self, config: &DataStoreConfig, cell: &DataCell) -> RowIndex {
         crate::profile_function!();
 
         debug_assert!(
@@ -913,17 +965,37 @@ impl PersistentComponentTable {
             cell.datatype(),
         );
 
+        let content_hash = content_hash(config, cell);
+        let duplicate = content_hash.and_then(|hash| {
+            let row_idx = *self.dedup_index.get(&hash)?;
+            let chunk = self.chunks.get(row_idx as usize)?;
+            is_same_data(&**chunk, cell).then(|| chunk.clone()) // shallow
+        });
+
         // TODO(#1619): don't use raw arrays
         let values = cell.as_arrow_monolist();
 
         self.total_rows += 1;
         // Warning: this is surprisingly costly!
-        self.total_size_bytes += arrow2::compute::aggregate::estimated_bytes_size(&*values) as u64;
+        let size_bytes = arrow2::compute::aggregate::estimated_bytes_size(&*values) as u64;
+        self.total_size_bytes += size_bytes;
+
+        let values = if let Some(duplicate) = duplicate {
+            self.deduplicated_size_bytes += size_bytes;
+            duplicate
+        } else {
+            values
+        };
 
         // TODO(#589): support for non-unit-length chunks
         self.chunks.push(values);
 
-        RowIndex::from_u63(RowIndexKind::Timeless, self.chunks.len() as u64 - 1)
+        let row_idx = self.chunks.len() as u64 - 1;
+        if let Some(hash) = content_hash {
+            self.dedup_index.entry(hash).or_insert(row_idx);
+        }
+
+        RowIndex::from_u63(RowIndexKind::Timeless, row_idx)
     }
 }
 
@@ -939,14 +1011,16 @@ impl ComponentTable {
             name,
             datatype: datatype.clone(),
             buckets: [ComponentBucket::new(name, datatype, 0u64)].into(),
+            dedup_index: Default::default(),
         }
     }
 
     /// Finds the appropriate bucket in this component table and pushes `cell` at the
     /// end of it, returning the _global_ `RowIndex` for this new row.
-    pub fn push_cell(
+    pub(crate) fn push_cell(
         &mut self,
         config: &DataStoreConfig,
+        spill_target: Option<&SpillTarget>,
         time_point: &TimePoint,
         cell: &DataCell,
     ) -> RowIndex {
@@ -983,10 +1057,21 @@ impl ComponentTable {
                 "allocating new component bucket, previous one overflowed"
             );
 
-            if config.enable_compaction {
+            // Spilled buckets must be compacted first: they are written to disk as a single
+            // contiguous array.
+            if config.enable_compaction || spill_target.is_some() {
                 active_bucket.archive();
             }
 
+            if let Some(spill_target) = spill_target {
+                if let Err(err) = active_bucket.spill(spill_target) {
+                    re_log::warn_once!(
+                        "Failed to spill component bucket to {}, keeping it in memory: {err}",
+                        spill_target.directory.display(),
+                    );
+                }
+            }
+
             let row_offset = active_bucket.row_offset + len;
             self.buckets
                 .push_back(ComponentBucket::new(self.name, &self.datatype, row_offset));
@@ -997,12 +1082,20 @@ impl ComponentTable {
         //   same reason as above: all component tables spawn with an initial bucket at row
         //   offset 0, thus this cannot fail.
         // - If the table has just overflowed, then we've just pushed a bucket to the dequeue.
+        let content_hash = content_hash(config, cell);
+        let duplicate = content_hash.and_then(|hash| self.find_duplicate(hash, cell));
+
         let active_bucket = self.buckets.back_mut().unwrap();
         let row_idx = RowIndex::from_u63(
             RowIndexKind::Temporal,
-            active_bucket.push_cell(time_point, cell) + active_bucket.row_offset,
+            active_bucket.push_cell(time_point, cell, duplicate) + active_bucket.row_offset,
         );
 
+        if let Some(hash) = content_hash {
+            // Always point to the latest copy: it's the one that will survive the longest.
+            self.dedup_index.insert(hash, row_idx.as_u64());
+        }
+
         trace!(
             kind = "insert",
             timelines = ?time_point.into_iter()
@@ -1015,6 +1108,46 @@ impl ComponentTable {
 
         row_idx
     }
+
+    /// Looks for a live row with the given content `hash` that holds the exact same data as
+    /// `cell`, and returns a shallow clone of it.
+    ///
+    /// Rows that live in archived buckets are never shared: these buckets are compacted into
+    /// a single array, which would be kept alive in its entirety by any such clone.
+    fn find_duplicate(&self, hash: u64, cell: &DataCell) -> Option<Box<dyn Array>> {
+        let row_idx = *self.dedup_index.get(&hash)?;
+        let bucket = &self.buckets[self.find_bucket_nr(row_idx)?];
+        if bucket.archived {
+            return None;
+        }
+
+        let chunk = bucket.chunks.get((row_idx - bucket.row_offset) as usize)?;
+        is_same_data(&**chunk, cell).then(|| chunk.clone()) // shallow
+    }
+}
+
+/// Hashes the data of `cell` if deduplication is enabled, see
+/// [`DataStoreConfig::enable_deduplication`].
+fn content_hash(config: &DataStoreConfig, cell: &DataCell) -> Option<u64> {
+    if !config.enable_deduplication {
+        return None;
+    }
+
+    crate::profile_function!();
+    cell.as_arrow_ref().content_hash()
+}
+
+/// Does the unit-length `chunk` hold the exact same data as `cell`?
+///
+/// Hashes can collide: this is the final word on whether two cells can share their data.
+fn is_same_data(chunk: &dyn Array, cell: &DataCell) -> bool {
+    crate::profile_function!();
+    let data = chunk
+        .as_any()
+        .downcast_ref::<ListArray<i32>>()
+        .unwrap()
+        .value(0);
+    *data == *cell.as_arrow_ref()
 }
 
 impl ComponentBucket {
@@ -1043,15 +1176,26 @@ impl ComponentBucket {
             row_offset,
             archived: false,
             time_ranges: Default::default(),
+            dead_rows: 0,
             chunks,
+            spilled: None,
             total_rows,
             total_size_bytes,
+            deduplicated_size_bytes: 0,
         }
     }
 
     /// Pushes `cell` to the end of the bucket, returning the _local_ index of the
     /// freshly added row.
-    pub fn push_cell(&mut self, timepoint: &TimePoint, cell: &DataCell) -> u64 {
+    ///
+    /// If `duplicate` is specified, it must hold the exact same data as `cell`, and is then
+    /// stored in its stead, sharing the underlying buffers.
+    pub fn push_cell(
+        &mut self,
+        timepoint: &TimePoint,
+        cell: &DataCell,
+        duplicate: Option<Box<dyn Array>>,
+    ) -> u64 {
         crate::profile_function!();
 
         // Keep track of all affected time ranges, for garbage collection purposes.
@@ -1069,7 +1213,15 @@ impl ComponentBucket {
 
         self.total_rows += 1;
         // Warning: this is surprisingly costly!
-        self.total_size_bytes += arrow2::compute::aggregate::estimated_bytes_size(&*values) as u64;
+        let size_bytes = arrow2::compute::aggregate::estimated_bytes_size(&*values) as u64;
+        self.total_size_bytes += size_bytes;
+
+        let values = if let Some(duplicate) = duplicate {
+            self.deduplicated_size_bytes += size_bytes;
+            duplicate
+        } else {
+            values
+        };
 
         // TODO(#589): support for non-unit-length chunks
         self.chunks.push(values);
@@ -1107,6 +1259,8 @@ impl ComponentBucket {
             // Recompute the size as we've just discarded a bunch of list headers.
             self.total_size_bytes =
                 arrow2::compute::aggregate::estimated_bytes_size(&*values) as u64;
+            // Concatenation copies everything, deduplicated rows included.
+            self.deduplicated_size_bytes = 0;
 
             self.chunks = vec![values];
         }
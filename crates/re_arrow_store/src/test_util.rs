@@ -60,6 +60,11 @@ pub fn all_configs() -> impl Iterator<Item = DataStoreConfig> {
             component_bucket_size_bytes: 64,
             ..DataStoreConfig::DEFAULT
         },
+        DataStoreConfig {
+            component_bucket_nb_rows: 2,
+            enable_deduplication: true,
+            ..DataStoreConfig::DEFAULT
+        },
     ];
 
     const INDEX_CONFIGS: &[DataStoreConfig] = &[
@@ -105,6 +110,7 @@ pub fn all_configs() -> impl Iterator<Item = DataStoreConfig> {
             index_bucket_nb_rows: idx.index_bucket_nb_rows,
             store_insert_ids: comp.store_insert_ids || idx.store_insert_ids,
             enable_compaction: comp.enable_compaction || idx.enable_compaction,
+            enable_deduplication: comp.enable_deduplication || idx.enable_deduplication,
         })
     })
 }
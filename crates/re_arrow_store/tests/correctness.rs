@@ -7,15 +7,18 @@ use std::sync::atomic::{AtomicBool, Ordering::SeqCst};
 use rand::Rng;
 
 use re_arrow_store::{
-    test_row, DataStore, DataStoreConfig, GarbageCollectionTarget, LatestAtQuery, WriteError,
+    test_row, AggregationError, DataStore, DataStoreConfig, DataStoreStats,
+    GarbageCollectionTarget, LatestAtQuery, RangeAggregationQuery, RangeQuery, StoreEvent,
+    StoreEventKind, StoreSubscriber, WriteError,
 };
 use re_log_types::{
-    component_types::InstanceKey,
+    component_types::{ColorRGBA, InstanceKey},
     datagen::{
         build_frame_nr, build_log_time, build_some_colors, build_some_instances, build_some_point2d,
     },
     external::arrow2_convert::deserialize::arrow_array_deserialize_iterator,
-    Component as _, DataCell, Duration, EntityPath, MsgId, Time, TimeType, Timeline,
+    Component as _, ComponentName, DataCell, Duration, EntityPath, MsgId, Time, TimeInt, TimeRange,
+    TimeType, Timeline,
 };
 
 // ---
@@ -370,6 +373,838 @@ fn gc_correct() {
     assert_eq!(2, store.total_temporal_component_rows());
 }
 
+#[test]
+fn gc_index_tables() {
+    init_logs();
+
+    let mut store = DataStore::new(
+        InstanceKey::name(),
+        DataStoreConfig {
+            component_bucket_nb_rows: 0,
+            index_bucket_nb_rows: 0,
+            ..Default::default()
+        },
+    );
+
+    let ent_path = EntityPath::from("this/that");
+    for frame_nr in 0..10 {
+        let row = test_row!(ent_path @ [
+            build_frame_nr(frame_nr.into()),
+        ] => 2; [
+            build_some_colors(2),
+        ]);
+        store.insert_row(&row).unwrap();
+    }
+
+    let stats_before = DataStoreStats::from_store(&store);
+    assert_eq!(10, stats_before.total_temporal_index_rows);
+
+    store.gc(
+        GarbageCollectionTarget::DropAtLeastPercentage(1.0),
+        Timeline::new("frame_nr", TimeType::Sequence),
+        ColorRGBA::name(),
+    );
+
+    if let err @ Err(_) = store.sanity_check() {
+        store.sort_indices_if_needed();
+        eprintln!("{store}");
+        err.unwrap();
+    }
+    check_still_readable(&store);
+
+    // Only the active component bucket survives the GC, and so does the one index row that
+    // points to it.
+    let stats = DataStoreStats::from_store(&store);
+    assert_eq!(1, stats.total_temporal_index_rows);
+    assert_eq!(9, stats.total_reclaimed_index_rows);
+    assert!(stats.total_reclaimed_index_size_bytes > 0);
+    assert!(stats.total_temporal_index_buckets < stats_before.total_temporal_index_buckets);
+}
+
+#[test]
+fn subscribers() {
+    init_logs();
+
+    #[derive(Default)]
+    struct AllEvents(Vec<StoreEvent>);
+
+    impl StoreSubscriber for AllEvents {
+        fn on_events(&mut self, events: &[StoreEvent]) {
+            self.0.extend_from_slice(events);
+        }
+
+        fn as_any(&self) -> &dyn std::any::Any {
+            self
+        }
+
+        fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+            self
+        }
+    }
+
+    let mut store = DataStore::new(
+        InstanceKey::name(),
+        DataStoreConfig {
+            component_bucket_nb_rows: 0,
+            index_bucket_nb_rows: 0,
+            ..Default::default()
+        },
+    );
+    let handle = store.register_subscriber(Box::<AllEvents>::default());
+
+    let ent_path = EntityPath::from("this/that");
+    let mut msg_ids = Vec::new();
+    for frame_nr in 0..10 {
+        let row = test_row!(ent_path @ [
+            build_frame_nr(frame_nr.into()),
+        ] => 2; [
+            build_some_colors(2),
+        ]);
+        msg_ids.push(row.row_id());
+        store.insert_row(&row).unwrap();
+    }
+
+    let events = store
+        .with_subscriber(handle, |events: &AllEvents| events.0.clone())
+        .unwrap();
+    assert_eq!(10, events.len());
+    for (event, msg_id) in events.iter().zip(&msg_ids) {
+        assert_eq!(StoreEventKind::Addition, event.kind);
+        assert_eq!(*msg_id, event.msg_id);
+        assert_eq!(ent_path, event.entity_path);
+        assert!(event.components.contains(&ColorRGBA::name()));
+        assert!(event.components.contains(&InstanceKey::name()));
+    }
+
+    store.gc(
+        GarbageCollectionTarget::DropAtLeastPercentage(1.0),
+        Timeline::new("frame_nr", TimeType::Sequence),
+        ColorRGBA::name(),
+    );
+
+    // Every row but the one living in the active component bucket gets collected.
+    let subscriber = store.unregister_subscriber(handle).unwrap();
+    let events = &subscriber.as_any().downcast_ref::<AllEvents>().unwrap().0[10..];
+    assert_eq!(9, events.len());
+    for (event, msg_id) in events.iter().zip(&msg_ids) {
+        assert_eq!(StoreEventKind::Deletion, event.kind);
+        assert_eq!(*msg_id, event.msg_id);
+        assert_eq!(1, event.timepoint.times().len());
+        assert!(event.components.contains(&ColorRGBA::name()));
+    }
+
+    assert!(store.unregister_subscriber(handle).is_none());
+}
+
+#[test]
+fn range_aggregate() {
+    init_logs();
+
+    for config in re_arrow_store::test_util::all_configs() {
+        let mut store = DataStore::new(InstanceKey::name(), config.clone());
+        range_aggregate_impl(&mut store);
+    }
+}
+fn range_aggregate_impl(store: &mut DataStore) {
+    use re_log_types::component_types::{Point2D, Scalar};
+
+    let ent_path = EntityPath::from("this/that");
+
+    // Inserted in reverse order to exercise the sorting logic.
+    for frame_nr in (0..100).rev() {
+        let row = test_row!(ent_path @ [build_frame_nr(frame_nr.into())] => 2; [
+            vec![Scalar(frame_nr as f64), Scalar(-frame_nr as f64)],
+        ]);
+        store.insert_row(&row).unwrap();
+    }
+
+    let timeline_frame_nr = Timeline::new("frame_nr", TimeType::Sequence);
+    let query = RangeAggregationQuery::new(
+        timeline_frame_nr,
+        TimeRange::new(5.into(), 54.into()),
+        10.into(),
+    );
+    let buckets = store
+        .range_aggregate(&query, &ent_path, Scalar::name())
+        .unwrap();
+
+    assert_eq!(6, buckets.len());
+
+    // The first and last buckets are truncated by the query's time range.
+    let first = buckets.first().unwrap();
+    assert_eq!(TimeRange::new(0.into(), 9.into()), first.time_range);
+    assert_eq!(10, first.count);
+    assert_eq!(-9.0, first.min);
+    assert_eq!(9.0, first.max);
+    assert_eq!(0.0, first.mean());
+
+    let last = buckets.last().unwrap();
+    assert_eq!(TimeRange::new(50.into(), 59.into()), last.time_range);
+    assert_eq!(10, last.count);
+    assert_eq!(-54.0, last.min);
+    assert_eq!(54.0, last.max);
+
+    for bucket in &buckets[1..5] {
+        assert_eq!(20, bucket.count);
+        assert_eq!(bucket.time_range.max.as_i64() as f64, bucket.max);
+    }
+
+    // Nothing was logged on that timeline.
+    let query = RangeAggregationQuery::new(
+        Timeline::log_time(),
+        TimeRange::new(TimeInt::MIN, TimeInt::MAX),
+        10.into(),
+    );
+    assert!(store
+        .range_aggregate(&query, &ent_path, Scalar::name())
+        .unwrap()
+        .is_empty());
+
+    // Points aren't primitive values.
+    let row = test_row!(ent_path @ [build_frame_nr(42.into())] => 2; [build_some_point2d(2)]);
+    store.insert_row(&row).unwrap();
+    let query = RangeAggregationQuery::new(
+        timeline_frame_nr,
+        TimeRange::new(TimeInt::MIN, TimeInt::MAX),
+        10.into(),
+    );
+    assert!(matches!(
+        store.range_aggregate(&query, &ent_path, Point2D::name()),
+        Err(AggregationError::NotPrimitive { .. })
+    ));
+}
+
+#[test]
+fn spill_to_disk() {
+    use re_log_types::component_types::Scalar;
+
+    init_logs();
+
+    let tempdir = tempfile::tempdir().unwrap();
+    let directory = tempdir.path().join("spill");
+    let nb_spill_files = || std::fs::read_dir(&directory).unwrap().count();
+
+    let mut store = DataStore::new(
+        InstanceKey::name(),
+        DataStoreConfig {
+            component_bucket_nb_rows: 2,
+            ..Default::default()
+        },
+    );
+    store.enable_spilling(&directory).unwrap();
+
+    let ent_path = EntityPath::from("this/that");
+    for frame_nr in 0..10 {
+        let row = test_row!(ent_path @ [build_frame_nr(frame_nr.into())] => 1; [
+            vec![Scalar(frame_nr as f64)],
+        ]);
+        store.insert_row(&row).unwrap();
+    }
+
+    if let err @ Err(_) = store.sanity_check() {
+        store.sort_indices_if_needed();
+        eprintln!("{store}");
+        err.unwrap();
+    }
+    assert!(nb_spill_files() > 0);
+
+    // Spilled data gets mapped back in, transparently.
+    let timeline_frame_nr = Timeline::new("frame_nr", TimeType::Sequence);
+    for frame_nr in 0..10 {
+        let query = LatestAtQuery::new(timeline_frame_nr, frame_nr.into());
+        let row_indices = store
+            .latest_at(&query, &ent_path, Scalar::name(), &[Scalar::name()])
+            .unwrap();
+        let [data] = store.get(&[Scalar::name()], &row_indices);
+        let values = arrow_array_deserialize_iterator::<Scalar>(&*data.unwrap())
+            .unwrap()
+            .map(|scalar| scalar.0)
+            .collect::<Vec<_>>();
+        assert_eq!(vec![frame_nr as f64], values);
+    }
+    check_still_readable(&store);
+
+    // Spill files go away along with their buckets...
+    let nb_spill_files_before = nb_spill_files();
+    store.gc(
+        GarbageCollectionTarget::DropAtLeastPercentage(0.5),
+        timeline_frame_nr,
+        MsgId::name(),
+    );
+    assert!(nb_spill_files() < nb_spill_files_before);
+
+    // ...or their store.
+    drop(store);
+    assert_eq!(0, nb_spill_files());
+
+    std::fs::remove_dir(&directory).unwrap();
+}
+
+/// Unions can't be memory-mapped, make sure they still make it back from disk.
+#[test]
+fn spill_to_disk_unions() {
+    use re_log_types::component_types::{
+        Quaternion, Rigid3, Tensor, TensorData, TensorDataMeaning, TensorDimension, TensorId,
+        Transform,
+    };
+
+    init_logs();
+
+    let tempdir = tempfile::tempdir().unwrap();
+    let directory = tempdir.path().join("spill");
+
+    let mut store = DataStore::new(
+        InstanceKey::name(),
+        DataStoreConfig {
+            component_bucket_nb_rows: 2,
+            ..Default::default()
+        },
+    );
+    store.enable_spilling(&directory).unwrap();
+
+    let transform = |frame_nr: i64| {
+        Transform::Rigid3(Rigid3 {
+            rotation: Quaternion::default(),
+            translation: [frame_nr as f32, 0.0, 0.0].into(),
+        })
+    };
+    let tensor = |frame_nr: i64| Tensor {
+        tensor_id: TensorId::random(),
+        shape: vec![TensorDimension::unnamed(2)],
+        data: TensorData::U16(vec![frame_nr as u16, 42].into()),
+        meaning: TensorDataMeaning::Unknown,
+        meter: None,
+    };
+
+    let tensors = (0..10).map(tensor).collect::<Vec<_>>();
+
+    let ent_path = EntityPath::from("this/that");
+    for frame_nr in 0..10 {
+        let row = test_row!(ent_path @ [build_frame_nr(frame_nr.into())] => 1; [
+            vec![transform(frame_nr)],
+            vec![tensors[frame_nr as usize].clone()],
+        ]);
+        store.insert_row(&row).unwrap();
+    }
+    assert!(std::fs::read_dir(&directory).unwrap().count() > 0);
+
+    let timeline_frame_nr = Timeline::new("frame_nr", TimeType::Sequence);
+    for frame_nr in 0..10 {
+        let query = LatestAtQuery::new(timeline_frame_nr, frame_nr.into());
+        let components = [Transform::name(), Tensor::name()];
+        let row_indices = store
+            .latest_at(&query, &ent_path, Transform::name(), &components)
+            .unwrap();
+        let [transforms, tensors_at] = store.get(&components, &row_indices);
+
+        let transforms = arrow_array_deserialize_iterator::<Transform>(&*transforms.unwrap())
+            .unwrap()
+            .collect::<Vec<_>>();
+        assert_eq!(vec![transform(frame_nr)], transforms);
+
+        let tensors_at = arrow_array_deserialize_iterator::<Tensor>(&*tensors_at.unwrap())
+            .unwrap()
+            .collect::<Vec<_>>();
+        assert_eq!(vec![tensors[frame_nr as usize].clone()], tensors_at);
+    }
+
+    drop(store);
+    std::fs::remove_dir(&directory).unwrap();
+}
+
+#[test]
+fn snapshot_roundtrip() {
+    init_logs();
+
+    let tempdir = tempfile::tempdir().unwrap();
+    for (i, config) in re_arrow_store::test_util::all_configs().enumerate() {
+        let mut store = DataStore::new(InstanceKey::name(), config.clone());
+        let directory = tempdir.path().join(format!("snapshot-{i}"));
+        snapshot_roundtrip_impl(&mut store, &directory, config);
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+}
+fn snapshot_roundtrip_impl(
+    store: &mut DataStore,
+    directory: &std::path::Path,
+    config: DataStoreConfig,
+) {
+    use re_log_types::component_types::Point2D;
+
+    let ent_paths = [EntityPath::from("this/that"), EntityPath::from("and/this")];
+
+    let mut msg_ids = Vec::new();
+    for ent_path in &ent_paths {
+        let row = test_row!(ent_path @ [] => 3; [build_some_colors(3)]);
+        msg_ids.push(row.row_id());
+        store.insert_row(&row).unwrap();
+
+        // Inserted in reverse order to exercise the sorting logic.
+        for frame_nr in (0..20).rev() {
+            let row = test_row!(ent_path @ [
+                build_frame_nr(frame_nr.into()),
+                build_log_time(Time::now()),
+            ] => 2; [build_some_colors(2), build_some_point2d(2)]);
+            msg_ids.push(row.row_id());
+            store.insert_row(&row).unwrap();
+        }
+    }
+
+    store.save_snapshot(directory).unwrap();
+    let mut loaded = DataStore::load_snapshot(directory, config).unwrap();
+
+    if let err @ Err(_) = loaded.sanity_check() {
+        loaded.sort_indices_if_needed();
+        eprintln!("{loaded}");
+        err.unwrap();
+    }
+    check_still_readable(&loaded);
+
+    let stats = DataStoreStats::from_store(store);
+    let loaded_stats = DataStoreStats::from_store(&loaded);
+    assert_eq!(stats.total_index_rows, loaded_stats.total_index_rows);
+    assert_eq!(
+        stats.total_component_rows,
+        loaded_stats.total_component_rows
+    );
+    assert_eq!(
+        stats.total_temporal_index_buckets,
+        loaded_stats.total_temporal_index_buckets
+    );
+    assert_eq!(
+        stats.total_temporal_component_buckets,
+        loaded_stats.total_temporal_component_buckets
+    );
+
+    for msg_id in &msg_ids {
+        assert_eq!(
+            store.get_msg_metadata(msg_id),
+            loaded.get_msg_metadata(msg_id)
+        );
+    }
+
+    let timeline_frame_nr = Timeline::new("frame_nr", TimeType::Sequence);
+    let components = [ColorRGBA::name(), Point2D::name()];
+    for ent_path in &ent_paths {
+        for frame_nr in [-1, 0, 5, 19, 20] {
+            let query = LatestAtQuery::new(timeline_frame_nr, frame_nr.into());
+            let get = |store: &DataStore| {
+                store
+                    .latest_at(&query, ent_path, ColorRGBA::name(), &components)
+                    .map(|row_indices| store.get(&components, &row_indices))
+            };
+            assert_eq!(get(store), get(&loaded), "{ent_path} @ {frame_nr}");
+        }
+    }
+
+    // The restored store keeps working as usual.
+    let ent_path = &ent_paths[0];
+    let row = test_row!(ent_path @ [build_frame_nr(42.into())] => 2; [build_some_colors(2)]);
+    loaded.insert_row(&row).unwrap();
+    loaded.sanity_check().unwrap();
+}
+
+#[test]
+fn subtree_queries() {
+    init_logs();
+
+    for config in re_arrow_store::test_util::all_configs() {
+        let mut store = DataStore::new(InstanceKey::name(), config.clone());
+        subtree_queries_impl(&mut store);
+    }
+}
+fn subtree_queries_impl(store: &mut DataStore) {
+    let ent_paths = [
+        EntityPath::from("world"),
+        EntityPath::from("world/points"),
+        EntityPath::from("world/points/left"),
+        EntityPath::from("worldly"),
+        EntityPath::from("other/points"),
+    ];
+    for (i, ent_path) in ent_paths.iter().enumerate() {
+        let row = test_row!(ent_path @ [build_frame_nr((i as i64).into())] => 2; [
+            build_some_colors(2),
+        ]);
+        store.insert_row(&row).unwrap();
+    }
+    // Timeless data only.
+    let ent_path = EntityPath::from("world/timeless");
+    let row = test_row!(ent_path @ [] => 2; [build_some_colors(2)]);
+    store.insert_row(&row).unwrap();
+
+    let timeline_frame_nr = Timeline::new("frame_nr", TimeType::Sequence);
+    let components = [ColorRGBA::name()];
+
+    let latest_at = |prefix: &str, frame_nr: i64| {
+        let query = LatestAtQuery::new(timeline_frame_nr, frame_nr.into());
+        store
+            .latest_at_subtree(&query, &prefix.into(), ColorRGBA::name(), &components)
+            .into_iter()
+            .map(|(ent_path, _)| ent_path.to_string())
+            .collect::<Vec<_>>()
+    };
+
+    // Prefixes and globs are one and the same, and match whole path parts only.
+    let expected = [
+        "world",
+        "world/points",
+        "world/points/left",
+        "world/timeless",
+    ];
+    assert_eq!(expected.to_vec(), latest_at("world", 10));
+    assert_eq!(expected.to_vec(), latest_at("world/**", 10));
+
+    // Entities that have no data yet are left out.
+    assert_eq!(
+        vec!["world", "world/points", "world/timeless"],
+        latest_at("world/**", 1)
+    );
+
+    assert_eq!(6, latest_at("**", 10).len());
+    assert!(latest_at("nope/**", 10).is_empty());
+
+    let query = RangeQuery::new(
+        timeline_frame_nr,
+        TimeRange::new(TimeInt::MIN, TimeInt::MAX),
+    );
+    let results = store
+        .range_subtree(&query, &"world/points/**".into(), components)
+        .map(|(ent_path, time, _, _)| (ent_path.to_string(), time))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        vec![
+            ("world/points".to_owned(), Some(1.into())),
+            ("world/points/left".to_owned(), Some(2.into())),
+        ],
+        results
+    );
+}
+
+#[test]
+fn deduplication() {
+    init_logs();
+
+    let ent_path = EntityPath::from("this/that");
+    let timeline_frame_nr = Timeline::new("frame_nr", TimeType::Sequence);
+
+    let colors = build_some_colors(3);
+    let fill = |store: &mut DataStore| {
+        for frame_nr in 0..10 {
+            let row = test_row!(ent_path @ [build_frame_nr(frame_nr.into())] => 3; [
+                colors.clone(),
+            ]);
+            store.insert_row(&row).unwrap();
+        }
+        for _ in 0..2 {
+            let row = test_row!(ent_path @ [] => 3; [colors.clone()]);
+            store.insert_row(&row).unwrap();
+        }
+
+        if let err @ Err(_) = store.sanity_check() {
+            store.sort_indices_if_needed();
+            eprintln!("{store}");
+            err.unwrap();
+        }
+    };
+
+    let config = DataStoreConfig {
+        component_bucket_nb_rows: 3,
+        ..Default::default()
+    };
+
+    let mut store = DataStore::new(InstanceKey::name(), config.clone());
+    fill(&mut store);
+    let stats = DataStoreStats::from_store(&store);
+    assert_eq!(0, stats.total_deduplicated_size_bytes);
+
+    let mut dedup_store = DataStore::new(
+        InstanceKey::name(),
+        DataStoreConfig {
+            enable_deduplication: true,
+            ..config
+        },
+    );
+    fill(&mut dedup_store);
+    let dedup_stats = DataStoreStats::from_store(&dedup_store);
+
+    // Deduplication is invisible, apart from the memory it saves.
+    assert_eq!(stats.total_component_rows, dedup_stats.total_component_rows);
+    assert_eq!(
+        stats.total_component_size_bytes,
+        dedup_stats.total_component_size_bytes
+    );
+    assert!(dedup_stats.total_deduplicated_size_bytes > 0);
+    assert!(dedup_stats.total_deduplicated_size_bytes < dedup_stats.total_component_size_bytes);
+
+    let expected: DataCell = colors.clone().try_into().unwrap();
+    for frame_nr in 0..10 {
+        let query = LatestAtQuery::new(timeline_frame_nr, frame_nr.into());
+        let row_indices = dedup_store
+            .latest_at(&query, &ent_path, ColorRGBA::name(), &[ColorRGBA::name()])
+            .unwrap();
+        let [data] = dedup_store.get(&[ColorRGBA::name()], &row_indices);
+        assert_eq!(expected.as_arrow_ref(), &*data.unwrap());
+    }
+    check_still_readable(&dedup_store);
+
+    // Shared data survives the garbage collection of the rows it was first inserted with.
+    dedup_store.gc(
+        GarbageCollectionTarget::DropAtLeastPercentage(0.5),
+        timeline_frame_nr,
+        ColorRGBA::name(),
+    );
+    let query = LatestAtQuery::new(timeline_frame_nr, 9.into());
+    let row_indices = dedup_store
+        .latest_at(&query, &ent_path, ColorRGBA::name(), &[ColorRGBA::name()])
+        .unwrap();
+    let [data] = dedup_store.get(&[ColorRGBA::name()], &row_indices);
+    assert_eq!(expected.as_arrow_ref(), &*data.unwrap());
+}
+
+#[test]
+fn concurrent_ingestion() {
+    use re_arrow_store::SharedDataStore;
+    use re_log_types::component_types::Scalar;
+
+    init_logs();
+
+    let store = SharedDataStore::new(DataStore::new(InstanceKey::name(), Default::default()));
+    let ingester = store.spawn_ingester().unwrap();
+
+    let ent_path = EntityPath::from("this/that");
+    let timeline_frame_nr = Timeline::new("frame_nr", TimeType::Sequence);
+
+    let reader = {
+        let store = store.clone();
+        let ent_path = ent_path.clone();
+        std::thread::Builder::new()
+            .name("reader".to_owned())
+            .spawn(move || {
+                let mut last_epoch = 0;
+                let mut last_frame_nr = None;
+                while last_frame_nr != Some(99) {
+                    let snapshot = store.read();
+                    assert!(snapshot.epoch() >= last_epoch);
+                    last_epoch = snapshot.epoch();
+
+                    // Rows are always visible in the order they were sent in.
+                    let query = LatestAtQuery::new(timeline_frame_nr, TimeInt::MAX);
+                    let frame_nr = snapshot
+                        .latest_at(&query, &ent_path, Scalar::name(), &[Scalar::name()])
+                        .map(|row_indices| {
+                            let [data] = snapshot.get(&[Scalar::name()], &row_indices);
+                            arrow_array_deserialize_iterator::<Scalar>(&*data.unwrap())
+                                .unwrap()
+                                .next()
+                                .unwrap()
+                                .0 as i64
+                        });
+                    assert!(frame_nr >= last_frame_nr);
+                    last_frame_nr = frame_nr;
+                }
+            })
+            .unwrap()
+    };
+
+    for frame_nr in 0..100 {
+        let row = test_row!(ent_path @ [build_frame_nr(frame_nr.into())] => 1; [
+            vec![Scalar(frame_nr as f64)],
+        ]);
+        ingester.insert_row(row);
+    }
+    ingester.flush();
+    reader.join().unwrap();
+
+    let epoch = store.epoch();
+    assert!(epoch > 0);
+
+    let snapshot = store.read();
+    assert_eq!(epoch, snapshot.epoch());
+    snapshot.sanity_check().unwrap();
+    assert_eq!(
+        100,
+        snapshot
+            .range(
+                &RangeQuery::new(
+                    timeline_frame_nr,
+                    TimeRange::new(TimeInt::MIN, TimeInt::MAX)
+                ),
+                &ent_path,
+                [Scalar::name()],
+            )
+            .count()
+    );
+}
+
+#[test]
+fn ingestion_epochs_are_bounded() {
+    use re_arrow_store::{DataStoreIngester, SharedDataStore};
+    use re_log_types::component_types::Scalar;
+
+    init_logs();
+
+    let store = SharedDataStore::new(DataStore::new(InstanceKey::name(), Default::default()));
+
+    let ent_path = EntityPath::from("this/that");
+    let nb_rows = 3 * DataStoreIngester::MAX_ROWS_PER_EPOCH;
+
+    // Hold the store while queueing everything up, so that the ingester has all the rows
+    // pending at once.
+    let ingester = {
+        let _snapshot = store.read();
+        let ingester = store.spawn_ingester().unwrap();
+        for frame_nr in 0..nb_rows {
+            let row = test_row!(ent_path @ [build_frame_nr((frame_nr as i64).into())] => 1; [
+                vec![Scalar(frame_nr as f64)],
+            ]);
+            ingester.insert_row(row);
+        }
+        assert!(ingester.has_pending_rows());
+        ingester
+    };
+    ingester.flush();
+
+    assert!(!ingester.has_pending_rows());
+    assert!(store.epoch() >= 3);
+}
+
+#[test]
+fn value_index_filters() {
+    init_logs();
+
+    for config in re_arrow_store::test_util::all_configs() {
+        let mut store = DataStore::new(InstanceKey::name(), config.clone());
+        value_index_filters_impl(&mut store);
+    }
+}
+fn value_index_filters_impl(store: &mut DataStore) {
+    use re_arrow_store::{ValueFilter, ValueIndexError};
+    use re_log_types::component_types::{ClassId, Label, Scalar};
+
+    let ent_path = EntityPath::from("this/that");
+    let timeline_frame_nr = Timeline::new("frame_nr", TimeType::Sequence);
+
+    let insert = |store: &mut DataStore, frame_nr: i64| {
+        let row = test_row!(ent_path @ [build_frame_nr(frame_nr.into())] => 2; [
+            vec![ClassId((frame_nr % 3) as u16), ClassId(10)],
+            vec![Label(format!("frame #{frame_nr}")), Label("frame".into())],
+        ]);
+        store.insert_row(&row).unwrap();
+        let row = test_row!(ent_path @ [build_frame_nr(frame_nr.into())] => 1; [
+            vec![Scalar(frame_nr as f64)],
+        ]);
+        store.insert_row(&row).unwrap();
+    };
+
+    // Existing data gets indexed too.
+    for frame_nr in 0..5 {
+        insert(store, frame_nr);
+    }
+    store.enable_value_index(ClassId::name()).unwrap();
+    store.enable_value_index(Label::name()).unwrap();
+    store.enable_value_index(Scalar::name()).unwrap();
+    for frame_nr in 5..10 {
+        insert(store, frame_nr);
+    }
+
+    assert!(matches!(
+        store.enable_value_index(ColorRGBA::name()),
+        Ok(()), // no data yet, cannot tell
+    ));
+    let row = test_row!(ent_path @ [build_frame_nr(0.into())] => 1; [
+        build_some_instances(1), build_some_point2d(1),
+    ]);
+    store.insert_row(&row).unwrap();
+    assert!(matches!(
+        store.enable_value_index(re_log_types::component_types::Point2D::name()),
+        Err(ValueIndexError::UnsupportedDatatype { .. }),
+    ));
+
+    let frames = |store: &DataStore, primary: ComponentName, filter: ValueFilter| {
+        let query = RangeQuery::new(
+            timeline_frame_nr,
+            TimeRange::new(TimeInt::MIN, TimeInt::MAX),
+        )
+        .with_filter(filter);
+        store
+            .range(&query, &ent_path, [primary])
+            .map(|(time, _, _)| time.unwrap().as_i64())
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        vec![1, 4, 7],
+        frames(
+            store,
+            ClassId::name(),
+            ValueFilter::eq(ClassId::name(), 1u16)
+        ),
+    );
+    // Any instance matching is enough.
+    assert_eq!(
+        10,
+        frames(
+            store,
+            ClassId::name(),
+            ValueFilter::eq(ClassId::name(), 10u16)
+        )
+        .len()
+    );
+    assert!(frames(
+        store,
+        ClassId::name(),
+        ValueFilter::eq(ClassId::name(), 1.5)
+    )
+    .is_empty());
+    assert_eq!(
+        vec![8, 9],
+        frames(store, Scalar::name(), ValueFilter::gt(Scalar::name(), 7.5)),
+    );
+    assert_eq!(
+        vec![0, 1, 2],
+        frames(store, Scalar::name(), ValueFilter::le(Scalar::name(), 2)),
+    );
+    assert_eq!(
+        vec![7],
+        frames(
+            store,
+            Label::name(),
+            ValueFilter::contains(Label::name(), "#7")
+        ),
+    );
+    assert_eq!(
+        vec![3],
+        frames(
+            store,
+            Label::name(),
+            ValueFilter::eq(Label::name(), "frame #3")
+        ),
+    );
+
+    // Components that aren't indexed, or aren't queried, never match.
+    assert!(frames(store, Scalar::name(), ValueFilter::eq(MsgId::name(), 1)).is_empty());
+    assert!(frames(store, Scalar::name(), ValueFilter::eq(ClassId::name(), 1)).is_empty());
+
+    // Collected rows go away.
+    store.gc(
+        GarbageCollectionTarget::DropEverythingBefore(5.into()),
+        timeline_frame_nr,
+        Scalar::name(),
+    );
+    assert_eq!(
+        vec![7],
+        frames(
+            store,
+            ClassId::name(),
+            ValueFilter::eq(ClassId::name(), 1u16)
+        ),
+    );
+    assert_eq!(
+        vec![5, 6],
+        frames(store, Scalar::name(), ValueFilter::lt(Scalar::name(), 7)),
+    );
+}
+
 fn check_still_readable(_store: &DataStore) {
     #[cfg(feature = "polars")]
     {
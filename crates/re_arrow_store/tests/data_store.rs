@@ -12,7 +12,7 @@ use polars_ops::prelude::DataFrameJoinOps;
 use rand::Rng;
 use re_arrow_store::{
     polars_util, test_row, DataStore, DataStoreConfig, GarbageCollectionTarget, LatestAtQuery,
-    RangeQuery, TimeInt, TimeRange,
+    RangeQuery, RetentionRule, TimeInt, TimeRange,
 };
 use re_log_types::{
     component_types::{ColorRGBA, InstanceKey, Point2D, Rect2D},
@@ -907,6 +907,77 @@ fn gc_impl(store: &mut DataStore) {
     }
 }
 
+#[test]
+fn gc_time_based() {
+    init_logs();
+
+    for config in re_arrow_store::test_util::all_configs() {
+        let mut store = DataStore::new(InstanceKey::name(), config.clone());
+        gc_time_based_impl(&mut store);
+    }
+}
+
+fn gc_time_based_impl(store: &mut DataStore) {
+    let timeline_frame_nr = Timeline::new("frame_nr", TimeType::Sequence);
+    let timeline_countdown = Timeline::new("countdown", TimeType::Sequence);
+
+    let camera = EntityPath::from("camera/left");
+    let odometry = EntityPath::from("odometry");
+    let misc = EntityPath::from("misc");
+
+    for frame_nr in 0..100 {
+        for ent_path in [&camera, &odometry, &misc] {
+            let row = test_row!(ent_path @ [
+                build_frame_nr(frame_nr.into()),
+                (timeline_countdown, (100 - frame_nr).into()),
+            ] => 2; [build_some_point2d(2)]);
+            store.insert_row(&row).unwrap();
+        }
+    }
+
+    let assert_nb_rows = |store: &DataStore, ent_path: &EntityPath, expected: usize| {
+        for timeline in [timeline_frame_nr, timeline_countdown] {
+            let query = RangeQuery::new(timeline, TimeRange::new(TimeInt::MIN, TimeInt::MAX));
+            let nb_rows = store.range(&query, ent_path, [Point2D::name()]).count();
+            assert_eq!(expected, nb_rows, "{ent_path} on {timeline:?}");
+        }
+    };
+
+    let check_gc = |store: &mut DataStore, target, expected_dropped| {
+        let nb_component_rows = store.total_temporal_component_rows();
+        let dropped = store.gc(target, timeline_frame_nr, Point2D::name());
+        assert_eq!(expected_dropped, dropped.len());
+
+        if let err @ Err(_) = store.sanity_check() {
+            store.sort_indices_if_needed();
+            eprintln!("{store}");
+            err.unwrap();
+        }
+        assert!(store.total_temporal_component_rows() <= nb_component_rows);
+    };
+
+    check_gc(
+        store,
+        GarbageCollectionTarget::KeepLatest(vec![
+            RetentionRule::new("camera", TimeInt::from(10)),
+            RetentionRule::new("odometry", TimeInt::from(50)),
+        ]),
+        89 + 49,
+    );
+    assert_nb_rows(store, &camera, 11);
+    assert_nb_rows(store, &odometry, 51);
+    assert_nb_rows(store, &misc, 100);
+
+    check_gc(
+        store,
+        GarbageCollectionTarget::DropEverythingBefore(95.into()),
+        (11 - 5) + (51 - 5) + (100 - 5),
+    );
+    assert_nb_rows(store, &camera, 5);
+    assert_nb_rows(store, &odometry, 5);
+    assert_nb_rows(store, &misc, 5);
+}
+
 // ---
 
 pub fn init_logs() {
@@ -69,6 +69,10 @@ pub struct EntityProperties {
 
     /// Used to scale the radii of the points in the resulting point cloud.
     pub backproject_radius_scale: EditableAutoValue<f32>,
+
+    /// Should rigid transforms be interpolated between the samples before and after the current
+    /// time, rather than stepping from one sample to the next?
+    pub interpolate_transforms: EditableAutoValue<bool>,
 }
 
 #[cfg(feature = "serde")]
@@ -96,6 +100,10 @@ impl EntityProperties {
                 .backproject_radius_scale
                 .or(&child.backproject_radius_scale)
                 .clone(),
+            interpolate_transforms: self
+                .interpolate_transforms
+                .or(&child.interpolate_transforms)
+                .clone(),
         }
     }
 }
@@ -112,6 +120,7 @@ impl Default for EntityProperties {
             backproject_depth: EditableAutoValue::Auto(true),
             depth_from_world_scale: EditableAutoValue::default(),
             backproject_radius_scale: EditableAutoValue::Auto(1.0),
+            interpolate_transforms: EditableAutoValue::Auto(false),
         }
     }
 }
@@ -209,7 +218,7 @@ where
     // Although it would be nice to use the `re_query` helpers for this, we would need to move
     // this out of re_data_store to avoid a circular dep. Since we don't need to do a join for
     // single components this is easy enough.
-    let data_store = &entity_db.data_store;
+    let data_store = &entity_db.data_store.read();
 
     let components = [C::name()];
 
@@ -1,6 +1,6 @@
 use nohash_hasher::IntMap;
 
-use re_arrow_store::{DataStoreConfig, GarbageCollectionTarget, TimeInt};
+use re_arrow_store::{DataStoreConfig, GarbageCollectionTarget, SharedDataStore, TimeInt};
 use re_log_types::{
     component_types::InstanceKey,
     external::arrow2_convert::deserialize::arrow_array_deserialize_iterator, ArrowMsg,
@@ -25,41 +25,65 @@ pub struct EntityDb {
     pub tree: crate::EntityTree,
 
     /// Stores all components for all entities for all timelines.
-    pub data_store: re_arrow_store::DataStore,
+    ///
+    /// Rows are inserted from a background thread (see [`Self::has_pending_rows`]): take a
+    /// snapshot with [`SharedDataStore::read`] to query it.
+    pub data_store: SharedDataStore,
+
+    /// Inserts incoming rows into [`Self::data_store`] off the calling thread.
+    ///
+    /// `None` if the ingester thread couldn't be spawned, in which case rows get inserted
+    /// synchronously instead.
+    #[cfg(not(target_arch = "wasm32"))]
+    ingester: Option<re_arrow_store::DataStoreIngester>,
 }
 
 impl Default for EntityDb {
     fn default() -> Self {
+        let data_store = SharedDataStore::new(re_arrow_store::DataStore::new(
+            InstanceKey::name(),
+            DataStoreConfig {
+                // Garbage collection of the datastore is currently driven by the `MsgId`
+                // component column, as a workaround for the `MsgId` mismatch issue.
+                //
+                // Since this component is only a few bytes large, trying to trigger a GC
+                // based on bucket size is a lost cause, so make sure to have a small enough
+                // row limit.
+                //
+                // TODO(cmc): Reasses once the whole `MsgId` mismatch issue is resolved
+                // (probably once batching is implemented).
+                component_bucket_nb_rows: 128,
+                component_bucket_size_bytes: 10 * 1024 * 1024, // 10 MiB
+                // We do not garbage collect index buckets at the moment, and so the size of
+                // individual index buckets is irrelevant, only their total number of rows
+                // matter.
+                // See https://github.com/rerun-io/rerun/pull/1558 for details.
+                //
+                // TODO(cmc): Bring back index GC once the whole `MsgId` mismatch issue is
+                // resolved (probably once batching is implemented).
+                index_bucket_size_bytes: u64::MAX,
+                index_bucket_nb_rows: 2048,
+                ..Default::default()
+            },
+        ));
+
+        #[cfg(not(target_arch = "wasm32"))]
+        let ingester = data_store
+            .spawn_ingester()
+            .map_err(|err| {
+                re_log::warn!(
+                    "Failed to spawn data store ingester, ingesting synchronously: {err}"
+                );
+            })
+            .ok();
+
         Self {
             entity_path_from_hash: Default::default(),
             times_per_timeline: Default::default(),
             tree: crate::EntityTree::root(),
-            data_store: re_arrow_store::DataStore::new(
-                InstanceKey::name(),
-                DataStoreConfig {
-                    // Garbage collection of the datastore is currently driven by the `MsgId`
-                    // component column, as a workaround for the `MsgId` mismatch issue.
-                    //
-                    // Since this component is only a few bytes large, trying to trigger a GC
-                    // based on bucket size is a lost cause, so make sure to have a small enough
-                    // row limit.
-                    //
-                    // TODO(cmc): Reasses once the whole `MsgId` mismatch issue is resolved
-                    // (probably once batching is implemented).
-                    component_bucket_nb_rows: 128,
-                    component_bucket_size_bytes: 10 * 1024 * 1024, // 10 MiB
-                    // We do not garbage collect index buckets at the moment, and so the size of
-                    // individual index buckets is irrelevant, only their total number of rows
-                    // matter.
-                    // See https://github.com/rerun-io/rerun/pull/1558 for details.
-                    //
-                    // TODO(cmc): Bring back index GC once the whole `MsgId` mismatch issue is
-                    // resolved (probably once batching is implemented).
-                    index_bucket_size_bytes: u64::MAX,
-                    index_bucket_nb_rows: 2048,
-                    ..Default::default()
-                },
-            ),
+            data_store,
+            #[cfg(not(target_arch = "wasm32"))]
+            ingester,
         }
     }
 }
@@ -70,24 +94,48 @@ impl EntityDb {
         self.entity_path_from_hash.get(entity_path_hash)
     }
 
+    /// Are there rows that have been received but aren't visible in [`Self::data_store`] yet?
+    pub fn has_pending_rows(&self) -> bool {
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(ingester) = &self.ingester {
+            return ingester.has_pending_rows();
+        }
+        false
+    }
+
     fn register_entity_path(&mut self, entity_path: &EntityPath) {
         self.entity_path_from_hash
             .entry(entity_path.hash())
             .or_insert_with(|| entity_path.clone());
     }
 
+    /// Inserts `row` into the store, in the background if possible.
+    ///
+    /// Failures to insert are logged.
+    fn insert_row(&self, row: DataRow) {
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(ingester) = &self.ingester {
+            ingester.insert_row(row);
+            return;
+        }
+
+        if let Err(err) = self.data_store.write().insert_row(&row) {
+            re_log::error!("Failed to insert incoming row: {err}");
+        }
+    }
+
     fn try_add_arrow_msg(&mut self, msg: &ArrowMsg) -> Result<(), Error> {
         let table: DataTable = msg.try_into()?;
 
         // TODO(#1619): batch all of this
         for row in table.as_rows() {
-            self.try_add_data_row(&row)?;
+            self.add_data_row(row);
         }
 
         Ok(())
     }
 
-    fn try_add_data_row(&mut self, row: &DataRow) -> Result<(), Error> {
+    fn add_data_row(&mut self, row: DataRow) {
         for (&timeline, &time_int) in row.timepoint().iter() {
             self.times_per_timeline.insert(timeline, time_int);
         }
@@ -115,28 +163,35 @@ impl EntityDb {
                     cell.num_instances(),
                     cell,
                 );
-                self.data_store.insert_row(&row).ok();
+                self.insert_row(row);
 
                 // Also update the tree with the clear-event
                 self.tree.add_data_msg(&time_point, &component_path);
             }
         }
 
-        self.data_store.insert_row(row).map_err(Into::into)
+        self.insert_row(row);
     }
 
     fn add_path_op(&mut self, msg_id: MsgId, time_point: &TimePoint, path_op: &PathOp) {
         let cleared_paths = self.tree.add_path_op(msg_id, time_point, path_op);
 
+        // The datatypes of the cleared components are looked up in the store.
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(ingester) = &self.ingester {
+            ingester.flush();
+        }
+
         for component_path in cleared_paths {
-            if let Some(data_type) = self
+            let data_type = self
                 .data_store
+                .read()
                 .lookup_data_type(&component_path.component_name)
-            {
+                .cloned();
+            if let Some(data_type) = data_type {
                 // Create and insert an empty component into the arrow store
                 // TODO(jleibs): Faster empty-array creation
-                let cell =
-                    DataCell::from_arrow_empty(component_path.component_name, data_type.clone());
+                let cell = DataCell::from_arrow_empty(component_path.component_name, data_type);
                 let row = DataRow::from_cells1(
                     msg_id,
                     component_path.entity_path.clone(),
@@ -144,7 +199,7 @@ impl EntityDb {
                     cell.num_instances(),
                     cell,
                 );
-                self.data_store.insert_row(&row).ok();
+                self.insert_row(row);
                 // Also update the tree with the clear-event
                 self.tree.add_data_msg(time_point, &component_path);
             }
@@ -163,6 +218,8 @@ impl EntityDb {
             times_per_timeline,
             tree,
             data_store: _, // purged before this function is called
+            #[cfg(not(target_arch = "wasm32"))]
+                ingester: _,
         } = self;
 
         {
@@ -280,8 +337,10 @@ impl LogDb {
         crate::profile_function!();
         assert!((0.0..=1.0).contains(&fraction_to_purge));
 
+        let mut data_store = self.entity_db.data_store.write();
+
         let drop_msg_ids = {
-            let msg_id_chunks = self.entity_db.data_store.gc(
+            let msg_id_chunks = data_store.gc(
                 GarbageCollectionTarget::DropAtLeastPercentage(fraction_to_purge as _),
                 Timeline::log_time(),
                 MsgId::name(),
@@ -296,7 +355,8 @@ impl LogDb {
                 .collect::<ahash::HashSet<_>>()
         };
 
-        let cutoff_times = self.entity_db.data_store.oldest_time_per_timeline();
+        let cutoff_times = data_store.oldest_time_per_timeline();
+        drop(data_store);
 
         let Self {
             chronological_message_ids,
@@ -9,6 +9,8 @@ use re_log_types::{
     entity_path, ArrowMsg, DataRow, DataTable, Index, LogMsg, MsgId, RecordingId,
 };
 
+use re_log_encoding::{EncodingOptions, Layout, Serializer};
+
 use criterion::{criterion_group, criterion_main, Criterion};
 
 #[cfg(not(debug_assertions))]
@@ -26,9 +28,14 @@ criterion_group!(
 );
 criterion_main!(benches);
 
-fn encode_log_msgs(messages: &[LogMsg]) -> Vec<u8> {
+fn encode_log_msgs(messages: &[LogMsg], serializer: Serializer) -> Vec<u8> {
     let mut bytes = vec![];
-    re_log_encoding::encoder::encode(messages.iter(), &mut bytes).unwrap();
+    let options = EncodingOptions {
+        layout: Layout::Stream,
+        serializer,
+        compression: Default::default(),
+    };
+    re_log_encoding::encoder::encode_with_options(messages.iter(), &mut bytes, options).unwrap();
     assert!(bytes.len() > messages.len());
     bytes
 }
@@ -93,13 +100,21 @@ fn mono_points_arrow(c: &mut Criterion) {
         });
         let messages = generate_messages(recording_id, &tables);
         group.bench_function("encode_log_msg", |b| {
-            b.iter(|| encode_log_msgs(&messages));
+            b.iter(|| encode_log_msgs(&messages, Serializer::MsgPack));
+        });
+        group.bench_function("encode_log_msg_arrow_ipc", |b| {
+            b.iter(|| encode_log_msgs(&messages, Serializer::ArrowIpc));
         });
         group.bench_function("encode_total", |b| {
-            b.iter(|| encode_log_msgs(&generate_messages(recording_id, &generate_tables())));
+            b.iter(|| {
+                encode_log_msgs(
+                    &generate_messages(recording_id, &generate_tables()),
+                    Serializer::MsgPack,
+                )
+            });
         });
 
-        let encoded = encode_log_msgs(&messages);
+        let encoded = encode_log_msgs(&messages, Serializer::MsgPack);
         group.bench_function("decode_log_msg", |b| {
             b.iter(|| {
                 let decoded = decode_log_msgs(&encoded);
@@ -107,6 +122,14 @@ fn mono_points_arrow(c: &mut Criterion) {
                 decoded
             });
         });
+        let encoded_arrow_ipc = encode_log_msgs(&messages, Serializer::ArrowIpc);
+        group.bench_function("decode_log_msg_arrow_ipc", |b| {
+            b.iter(|| {
+                let decoded = decode_log_msgs(&encoded_arrow_ipc);
+                assert_eq!(decoded.len(), messages.len());
+                decoded
+            });
+        });
         group.bench_function("decode_message_bundles", |b| {
             b.iter(|| {
                 let tables = decode_tables(&messages);
@@ -149,13 +172,21 @@ fn mono_points_arrow_batched(c: &mut Criterion) {
         });
         let messages = generate_messages(recording_id, &tables);
         group.bench_function("encode_log_msg", |b| {
-            b.iter(|| encode_log_msgs(&messages));
+            b.iter(|| encode_log_msgs(&messages, Serializer::MsgPack));
+        });
+        group.bench_function("encode_log_msg_arrow_ipc", |b| {
+            b.iter(|| encode_log_msgs(&messages, Serializer::ArrowIpc));
         });
         group.bench_function("encode_total", |b| {
-            b.iter(|| encode_log_msgs(&generate_messages(recording_id, &[generate_table()])));
+            b.iter(|| {
+                encode_log_msgs(
+                    &generate_messages(recording_id, &[generate_table()]),
+                    Serializer::MsgPack,
+                )
+            });
         });
 
-        let encoded = encode_log_msgs(&messages);
+        let encoded = encode_log_msgs(&messages, Serializer::MsgPack);
         group.bench_function("decode_log_msg", |b| {
             b.iter(|| {
                 let decoded = decode_log_msgs(&encoded);
@@ -163,6 +194,14 @@ fn mono_points_arrow_batched(c: &mut Criterion) {
                 decoded
             });
         });
+        let encoded_arrow_ipc = encode_log_msgs(&messages, Serializer::ArrowIpc);
+        group.bench_function("decode_log_msg_arrow_ipc", |b| {
+            b.iter(|| {
+                let decoded = decode_log_msgs(&encoded_arrow_ipc);
+                assert_eq!(decoded.len(), messages.len());
+                decoded
+            });
+        });
         group.bench_function("decode_message_bundles", |b| {
             b.iter(|| {
                 let bundles = decode_tables(&messages);
@@ -206,13 +245,21 @@ fn batch_points_arrow(c: &mut Criterion) {
         });
         let messages = generate_messages(recording_id, &tables);
         group.bench_function("encode_log_msg", |b| {
-            b.iter(|| encode_log_msgs(&messages));
+            b.iter(|| encode_log_msgs(&messages, Serializer::MsgPack));
+        });
+        group.bench_function("encode_log_msg_arrow_ipc", |b| {
+            b.iter(|| encode_log_msgs(&messages, Serializer::ArrowIpc));
         });
         group.bench_function("encode_total", |b| {
-            b.iter(|| encode_log_msgs(&generate_messages(recording_id, &generate_tables())));
+            b.iter(|| {
+                encode_log_msgs(
+                    &generate_messages(recording_id, &generate_tables()),
+                    Serializer::MsgPack,
+                )
+            });
         });
 
-        let encoded = encode_log_msgs(&messages);
+        let encoded = encode_log_msgs(&messages, Serializer::MsgPack);
         group.bench_function("decode_log_msg", |b| {
             b.iter(|| {
                 let decoded = decode_log_msgs(&encoded);
@@ -220,6 +267,14 @@ fn batch_points_arrow(c: &mut Criterion) {
                 decoded
             });
         });
+        let encoded_arrow_ipc = encode_log_msgs(&messages, Serializer::ArrowIpc);
+        group.bench_function("decode_log_msg_arrow_ipc", |b| {
+            b.iter(|| {
+                let decoded = decode_log_msgs(&encoded_arrow_ipc);
+                assert_eq!(decoded.len(), messages.len());
+                decoded
+            });
+        });
         group.bench_function("decode_message_bundles", |b| {
             b.iter(|| {
                 let tables = decode_tables(&messages);
@@ -1,25 +1,17 @@
 //! Decoding [`LogMsg`]:es from `.rrd` files/streams.
 
-use re_log_types::LogMsg;
+use re_build_info::CrateVersion;
+use re_log_types::{LogMsg, TimeRange, Timeline};
 
-// ----------------------------------------------------------------------------
-
-fn warn_on_version_mismatch(encoded_version: [u8; 4]) {
-    use re_build_info::CrateVersion;
-
-    // We used 0000 for all .rrd files up until 2023-02-27, post 0.2.0 release:
-    let encoded_version = if encoded_version == [0, 0, 0, 0] {
-        CrateVersion::new(0, 2, 0)
-    } else {
-        CrateVersion::from_bytes(encoded_version)
-    };
+use crate::{
+    index::{RrdIndex, FRAME_HEADER_LEN, INDEX_MAGIC, TRAILER_LEN},
+    migration::Migration,
+    options::{FIRST_FORMAT_VERSION_WITH_CHECKSUMS, LEGACY_MAGIC, VERSIONED_MAGIC},
+    Compression, EncodingOptions, Layout, FORMAT_VERSION,
+};
 
-    let local_version = CrateVersion::parse(env!("CARGO_PKG_VERSION"));
-
-    if !encoded_version.is_compatible_with(local_version) {
-        re_log::warn!("Found log stream with Rerun version {encoded_version}, which is incompatible with the local Rerun version {local_version}. Loading will try to continue, but might fail in subtle ways.");
-    }
-}
+#[cfg(test)]
+use crate::Serializer;
 
 // ----------------------------------------------------------------------------
 
@@ -44,124 +36,654 @@ pub enum DecodeError {
     #[error("Zstd read error: {0}")]
     RuzstdRead(std::io::Error),
 
+    #[error("Lz4 error: {0}")]
+    Lz4(std::io::Error),
+
+    #[error("Unsupported encoding options: {0:?}")]
+    UnsupportedOptions([u8; 4]),
+
+    #[error("Unsupported format version {0}: the data was written by a newer version of Rerun")]
+    UnsupportedFormatVersion(u32),
+
+    #[error("Failed to migrate data logged with an older version of Rerun: {0}")]
+    Migration(String),
+
     #[error("MsgPack error: {0}")]
     MsgPack(#[from] rmp_serde::decode::Error),
+
+    #[error("Arrow error: {0}")]
+    Arrow(#[from] re_log_types::external::arrow2::error::Error),
+
+    #[error("Malformed message: {0}")]
+    Malformed(&'static str),
+
+    #[error("The data ends in the middle of a message")]
+    Truncated,
+
+    #[error("Checksum mismatch")]
+    ChecksumMismatch,
+}
+
+// ----------------------------------------------------------------------------
+
+/// What a [`Decoder`] created with [`Decoder::new_recovering`] had to skip over to get past
+/// corrupt or truncated data.
+///
+/// All sizes are of the uncompressed data, i.e. of the messages that were lost.
+/// Data that could not even be decompressed, such as the unfinished end of a compressed stream,
+/// cannot be accounted for.
+#[derive(Debug, Default)]
+pub struct RecoveryReport {
+    /// How many messages were decoded successfully.
+    pub num_messages: u64,
+
+    /// Everything that was skipped, in the order it was encountered.
+    pub skipped: Vec<SkippedData>,
+}
+
+impl RecoveryReport {
+    /// Did everything decode fine?
+    pub fn is_clean(&self) -> bool {
+        self.skipped.is_empty()
+    }
+
+    /// Total number of (uncompressed) bytes that were skipped.
+    pub fn num_bytes_skipped(&self) -> u64 {
+        self.skipped.iter().map(|skipped| skipped.num_bytes).sum()
+    }
+}
+
+/// A region of an `.rrd` file that could not be decoded, see [`RecoveryReport`].
+#[derive(Debug)]
+pub struct SkippedData {
+    /// Number of (uncompressed) bytes that were lost.
+    pub num_bytes: u64,
+
+    /// Why they couldn't be decoded.
+    pub error: DecodeError,
+}
+
+/// A failure to read the next message.
+struct ReadFailure {
+    error: DecodeError,
+
+    /// Number of (uncompressed) bytes that were lost.
+    num_bytes: u64,
+
+    /// If set, there is no way to read on past this failure.
+    fatal: bool,
+}
+
+impl ReadFailure {
+    fn fatal(error: DecodeError, num_bytes: u64) -> Self {
+        Self {
+            error,
+            num_bytes,
+            fatal: true,
+        }
+    }
+
+    fn skippable(error: DecodeError, num_bytes: u64) -> Self {
+        Self {
+            error,
+            num_bytes,
+            fatal: false,
+        }
+    }
+}
+
+/// Reads exactly `len` bytes into `buffer`, returning the bytes read so far on failure.
+///
+/// Unlike [`std::io::Read::read_exact`], this only ever allocates as much as is actually read,
+/// so a corrupt length cannot make us run out of memory.
+fn read_exactly(
+    read: &mut impl std::io::Read,
+    len: u64,
+    buffer: &mut Vec<u8>,
+) -> Result<(), (usize, Option<std::io::Error>)> {
+    use std::io::Read as _;
+
+    buffer.clear();
+    let result = read.take(len).read_to_end(buffer);
+    if buffer.len() as u64 == len {
+        Ok(())
+    } else {
+        Err((buffer.len(), result.err()))
+    }
 }
 
 // ----------------------------------------------------------------------------
-// native decode:
+// native/wasm specifics:
 
 #[cfg(not(target_arch = "wasm32"))]
-pub struct Decoder<'r, R: std::io::BufRead> {
-    zdecoder: zstd::stream::Decoder<'r, R>,
-    buffer: Vec<u8>,
+type StreamDecoder<R> = zstd::stream::Decoder<'static, std::io::BufReader<R>>;
+
+#[cfg(target_arch = "wasm32")]
+type StreamDecoder<R> = ruzstd::StreamingDecoder<R>;
+
+#[cfg(not(target_arch = "wasm32"))]
+fn new_stream_decoder<R: std::io::Read>(read: R) -> Result<StreamDecoder<R>, DecodeError> {
+    zstd::stream::read::Decoder::new(read).map_err(DecodeError::Zstd)
+}
+
+#[cfg(target_arch = "wasm32")]
+fn new_stream_decoder<R: std::io::Read>(read: R) -> Result<StreamDecoder<R>, DecodeError> {
+    ruzstd::StreamingDecoder::new(read).map_err(DecodeError::RuzstdInit)
 }
 
 #[cfg(not(target_arch = "wasm32"))]
-impl<'r, R: std::io::Read> Decoder<'r, std::io::BufReader<R>> {
-    pub fn new(mut read: R) -> Result<Self, DecodeError> {
-        crate::profile_function!();
+fn stream_read_error(err: std::io::Error) -> DecodeError {
+    DecodeError::Zstd(err)
+}
 
-        let mut header = [0_u8; 4];
-        read.read_exact(&mut header).map_err(DecodeError::Read)?;
-        if &header != b"RRF0" {
-            return Err(DecodeError::NotAnRrd);
+#[cfg(target_arch = "wasm32")]
+fn stream_read_error(err: std::io::Error) -> DecodeError {
+    DecodeError::RuzstdRead(err)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn decompress_zstd(compressed: &[u8], uncompressed_len: usize) -> Result<Vec<u8>, DecodeError> {
+    zstd::bulk::decompress(compressed, uncompressed_len).map_err(DecodeError::Zstd)
+}
+
+#[cfg(target_arch = "wasm32")]
+fn decompress_zstd(compressed: &[u8], uncompressed_len: usize) -> Result<Vec<u8>, DecodeError> {
+    use std::io::Read as _;
+
+    let mut decoder = ruzstd::StreamingDecoder::new(compressed).map_err(DecodeError::RuzstdInit)?;
+    let mut uncompressed = Vec::with_capacity(uncompressed_len);
+    decoder
+        .read_to_end(&mut uncompressed)
+        .map_err(DecodeError::RuzstdRead)?;
+    Ok(uncompressed)
+}
+
+/// Decompresses a single frame of a chunked `.rrd` file, or its index.
+fn decompress_frame(
+    compression: Compression,
+    compressed: Vec<u8>,
+    uncompressed_len: usize,
+) -> Result<Vec<u8>, DecodeError> {
+    crate::profile_function!();
+    match compression {
+        Compression::Off => Ok(compressed),
+        Compression::Zstd { .. } => decompress_zstd(&compressed, uncompressed_len),
+        Compression::Lz4 => {
+            lz4_flex::block::decompress(&compressed, uncompressed_len).map_err(|err| {
+                DecodeError::Lz4(std::io::Error::new(std::io::ErrorKind::InvalidData, err))
+            })
         }
-        read.read_exact(&mut header).map_err(DecodeError::Read)?;
-        warn_on_version_mismatch(header);
+    }
+}
 
-        let zdecoder = zstd::stream::read::Decoder::new(read).map_err(DecodeError::Zstd)?;
-        Ok(Self {
-            zdecoder,
-            buffer: vec![],
+/// Decompresses all messages of an `.rrd` file using the stream layout.
+enum StreamReader<R: std::io::Read> {
+    Off(std::io::BufReader<R>),
+    Zstd(StreamDecoder<R>),
+    Lz4(lz4_flex::frame::FrameDecoder<R>),
+}
+
+impl<R: std::io::Read> StreamReader<R> {
+    fn new(read: R, compression: Compression) -> Result<Self, DecodeError> {
+        Ok(match compression {
+            Compression::Off => Self::Off(std::io::BufReader::new(read)),
+            Compression::Zstd { .. } => Self::Zstd(new_stream_decoder(read)?),
+            Compression::Lz4 => Self::Lz4(lz4_flex::frame::FrameDecoder::new(read)),
         })
     }
+
+    /// Wraps an error that occurred while reading from this stream.
+    fn error(&self, err: std::io::Error) -> DecodeError {
+        match self {
+            Self::Off(_) => DecodeError::Read(err),
+            Self::Zstd(_) => stream_read_error(err),
+            Self::Lz4(_) => DecodeError::Lz4(err),
+        }
+    }
 }
 
-#[cfg(not(target_arch = "wasm32"))]
-impl<'r, R: std::io::BufRead> Iterator for Decoder<'r, R> {
-    type Item = Result<LogMsg, DecodeError>;
+impl<R: std::io::Read> std::io::Read for StreamReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        match self {
+            Self::Off(read) => read.read(buf),
+            Self::Zstd(decoder) => decoder.read(buf),
+            Self::Lz4(decoder) => decoder.read(buf),
+        }
+    }
+}
 
-    fn next(&mut self) -> Option<Self::Item> {
-        crate::profile_function!();
-        use std::io::Read as _;
+// ----------------------------------------------------------------------------
+
+/// Reads the frames of a chunked `.rrd` file, one after the other.
+struct FrameReader<R: std::io::Read> {
+    read: R,
+    compression: Compression,
+
+    /// If set, only the frames at these offsets are read, using the given function to seek to
+    /// each of them.
+    selected_frames: Option<(SeekFn<R>, std::collections::VecDeque<u64>)>,
 
-        let mut len = [0_u8; 8];
-        self.zdecoder.read_exact(&mut len).ok()?;
-        let len = u64::from_le_bytes(len) as usize;
+    /// The uncompressed contents of the current frame.
+    frame: std::io::Cursor<Vec<u8>>,
 
-        self.buffer.resize(len, 0);
+    /// Set once we've reached the end of the frames.
+    done: bool,
+}
+
+type SeekFn<R> = fn(&mut R, u64) -> std::io::Result<()>;
+
+impl<R: std::io::Read> FrameReader<R> {
+    /// Loads the next frame, returning `Ok(false)` once there are none left.
+    fn next_frame(&mut self) -> Result<bool, ReadFailure> {
+        if self.done {
+            return Ok(false);
+        }
+
+        if let Some((seek, offsets)) = &mut self.selected_frames {
+            let Some(offset) = offsets.pop_front() else {
+                self.done = true;
+                return Ok(false);
+            };
+            seek(&mut self.read, offset)
+                .map_err(|err| ReadFailure::fatal(DecodeError::Read(err), 0))?;
+        }
 
-        {
-            crate::profile_scope!("zstd");
-            if let Err(err) = self.zdecoder.read_exact(&mut self.buffer) {
-                return Some(Err(DecodeError::Zstd(err)));
+        let mut header = Vec::with_capacity(FRAME_HEADER_LEN as usize);
+        match read_exactly(&mut self.read, FRAME_HEADER_LEN, &mut header) {
+            Ok(()) => {}
+            Err((0, None)) => {
+                // The file was cut short right before a frame header, e.g. because the process
+                // writing it crashed: all frames so far are complete, so yield what we have.
+                self.done = true;
+                return Ok(false);
+            }
+            Err((_, err)) => {
+                self.done = true;
+                let error = err.map_or(DecodeError::Truncated, DecodeError::Read);
+                return Err(ReadFailure::fatal(error, 0));
             }
         }
+        let compressed_len = u64::from_le_bytes(header[..8].try_into().unwrap());
+        let uncompressed_len = u64::from_le_bytes(header[8..].try_into().unwrap());
 
-        crate::profile_scope!("MsgPack deser");
-        match rmp_serde::from_read(&mut self.buffer.as_slice()) {
-            Ok(msg) => Some(Ok(msg)),
-            Err(err) => Some(Err(err.into())),
+        if compressed_len == 0 {
+            self.done = true;
+            return Ok(false);
         }
+
+        let mut compressed = Vec::new();
+        if let Err((_, err)) = read_exactly(&mut self.read, compressed_len, &mut compressed) {
+            self.done = true;
+            let error = err.map_or(DecodeError::Truncated, DecodeError::Read);
+            return Err(ReadFailure::fatal(error, uncompressed_len));
+        }
+
+        // We know where the next frame starts, so a corrupt frame can be skipped.
+        let frame = decompress_frame(self.compression, compressed, uncompressed_len as usize)
+            .map_err(|err| ReadFailure::skippable(err, uncompressed_len))?;
+        self.frame = std::io::Cursor::new(frame);
+
+        Ok(true)
     }
 }
 
-// ----------------------------------------------------------------------------
-// wasm decode:
+enum Body<R: std::io::Read> {
+    Stream(StreamReader<R>),
+    Chunked(FrameReader<R>),
+}
 
-#[cfg(target_arch = "wasm32")]
+/// Decodes [`LogMsg`]es from an `.rrd` file or stream, whatever its [`EncodingOptions`].
+///
+/// Messages written by older versions of Rerun are upgraded to today's layout as they are decoded.
 pub struct Decoder<R: std::io::Read> {
-    zdecoder: ruzstd::StreamingDecoder<R>,
+    /// As read from the header.
+    options: EncodingOptions,
+
+    /// Is every message preceded by a checksum?
+    checksums: bool,
+
+    /// Set when decoding messages written by an older version of Rerun.
+    migration: Option<Migration>,
+
+    body: Body<R>,
     buffer: Vec<u8>,
+
+    /// Set after a failure that there is no reading past.
+    done: bool,
+
+    /// Set when decoding in recovery mode, see [`Self::new_recovering`].
+    recovery: Option<RecoveryReport>,
 }
 
-#[cfg(target_arch = "wasm32")]
-impl<R: std::io::Read> Decoder<R> {
-    pub fn new(mut read: R) -> Result<Self, DecodeError> {
-        crate::profile_function!();
+/// What the header at the start of an `.rrd` file tells us.
+struct Header {
+    /// How messages are framed, see [`FORMAT_VERSION`].
+    format_version: u32,
 
-        let mut header = [0_u8; 4];
-        read.read_exact(&mut header).map_err(DecodeError::Read)?;
-        if &header != b"RRF0" {
+    /// The version of Rerun that wrote the file, which dictates the layout of the messages.
+    rerun_version: CrateVersion,
+
+    options: EncodingOptions,
+}
+
+impl Header {
+    /// Reads the magic bytes, versions and [`EncodingOptions`] at the start of an `.rrd` file.
+    fn read(read: &mut impl std::io::Read) -> Result<Self, DecodeError> {
+        let mut bytes = [0_u8; 4];
+        read.read_exact(&mut bytes).map_err(DecodeError::Read)?;
+        let magic = bytes;
+        if &magic != LEGACY_MAGIC && &magic != VERSIONED_MAGIC {
             return Err(DecodeError::NotAnRrd);
         }
-        read.read_exact(&mut header).map_err(DecodeError::Read)?;
-        warn_on_version_mismatch(header);
 
-        let zdecoder = ruzstd::StreamingDecoder::new(read).map_err(DecodeError::RuzstdInit)?;
+        read.read_exact(&mut bytes).map_err(DecodeError::Read)?;
+        // We used 0000 for all .rrd files up until 2023-02-27, post 0.2.0 release:
+        let rerun_version = if bytes == [0, 0, 0, 0] {
+            CrateVersion::new(0, 2, 0)
+        } else {
+            CrateVersion::from_bytes(bytes)
+        };
+
+        let format_version = if &magic == LEGACY_MAGIC {
+            0
+        } else {
+            read.read_exact(&mut bytes).map_err(DecodeError::Read)?;
+            u32::from_le_bytes(bytes)
+        };
+        if format_version > FORMAT_VERSION {
+            return Err(DecodeError::UnsupportedFormatVersion(format_version));
+        }
+
+        let options = if format_version == 0 {
+            EncodingOptions::DEFAULT
+        } else {
+            read.read_exact(&mut bytes).map_err(DecodeError::Read)?;
+            EncodingOptions::from_bytes(bytes).ok_or(DecodeError::UnsupportedOptions(bytes))?
+        };
+
         Ok(Self {
-            zdecoder,
-            buffer: vec![],
+            format_version,
+            rerun_version,
+            options,
         })
     }
 }
 
-#[cfg(target_arch = "wasm32")]
+impl<R: std::io::Read> Decoder<R> {
+    pub fn new(mut read: R) -> Result<Self, DecodeError> {
+        crate::profile_function!();
+
+        let header = Header::read(&mut read)?;
+        let body = match header.options.layout {
+            Layout::Stream => Body::Stream(StreamReader::new(read, header.options.compression)?),
+            Layout::Chunked { .. } => Body::Chunked(FrameReader {
+                read,
+                compression: header.options.compression,
+                selected_frames: None,
+                frame: Default::default(),
+                done: false,
+            }),
+        };
+
+        Ok(Self::with_body(&header, body))
+    }
+
+    fn with_body(header: &Header, body: Body<R>) -> Self {
+        Self {
+            options: header.options,
+            checksums: header.format_version >= FIRST_FORMAT_VERSION_WITH_CHECKSUMS,
+            migration: Migration::for_version(header.rerun_version),
+            body,
+            buffer: vec![],
+            done: false,
+            recovery: None,
+        }
+    }
+
+    /// Like [`Self::new`], but skips over corrupt or truncated data instead of failing on it.
+    ///
+    /// This yields every complete message up to the point where the data becomes unreadable,
+    /// e.g. because the process writing the file crashed, as well as every message past
+    /// corruption that can be skipped over. The iterator never yields any errors: what was
+    /// skipped and why is recorded in the [`Self::recovery_report`] instead.
+    ///
+    /// Only fails if the data isn't an `.rrd` file to begin with.
+    pub fn new_recovering(read: R) -> Result<Self, DecodeError> {
+        Ok(Self::new(read)?.recovering())
+    }
+
+    /// Makes this decoder skip over corrupt or truncated data, see [`Self::new_recovering`].
+    pub fn recovering(mut self) -> Self {
+        self.recovery = Some(RecoveryReport::default());
+        self
+    }
+
+    /// The options the data was encoded with, as read from its header.
+    pub fn options(&self) -> EncodingOptions {
+        self.options
+    }
+
+    /// What has been skipped so far, if created with [`Self::new_recovering`].
+    ///
+    /// Use [`Iterator::by_ref`] to keep hold of the decoder while iterating.
+    pub fn recovery_report(&self) -> Option<&RecoveryReport> {
+        self.recovery.as_ref()
+    }
+
+    /// Reads the next length-prefixed message into `self.buffer`.
+    ///
+    /// Returns `Ok(false)` once there are no messages left.
+    fn read_next(&mut self) -> Result<bool, ReadFailure> {
+        use std::io::Read as _;
+
+        // The length of the message, followed by its checksum if there is one.
+        let mut prefix = [0_u8; 12];
+        let prefix = if self.checksums {
+            &mut prefix[..]
+        } else {
+            &mut prefix[..8]
+        };
+        let prefix_len = prefix.len() as u64;
+
+        match &mut self.body {
+            Body::Stream(stream) => {
+                match read_exactly(stream, prefix_len, &mut self.buffer) {
+                    Ok(()) => prefix.copy_from_slice(&self.buffer),
+                    Err((0, None)) => return Ok(false),
+                    Err((num_bytes, err)) => {
+                        let error = err.map_or(DecodeError::Truncated, |err| stream.error(err));
+                        return Err(ReadFailure::fatal(error, num_bytes as u64));
+                    }
+                }
+                let len = u64::from_le_bytes(prefix[..8].try_into().unwrap());
+
+                crate::profile_scope!("decompress");
+                if let Err((num_bytes, err)) = read_exactly(stream, len, &mut self.buffer) {
+                    // Without its end, there is no telling where the next message starts.
+                    let error = err.map_or(DecodeError::Truncated, |err| stream.error(err));
+                    return Err(ReadFailure::fatal(error, prefix_len + num_bytes as u64));
+                }
+            }
+
+            Body::Chunked(frames) => {
+                while frames.frame.read_exact(prefix).is_err() {
+                    if !frames.next_frame()? {
+                        return Ok(false);
+                    }
+                }
+                let len = u64::from_le_bytes(prefix[..8].try_into().unwrap());
+
+                if read_exactly(&mut frames.frame, len, &mut self.buffer).is_err() {
+                    // The message claims to be longer than what is left of the frame, which we've
+                    // now read in full: carry on with the next frame.
+                    let num_bytes = prefix_len + self.buffer.len() as u64;
+                    return Err(ReadFailure::skippable(DecodeError::Truncated, num_bytes));
+                }
+            }
+        }
+
+        if self.checksums {
+            let checksum = u32::from_le_bytes(prefix[8..].try_into().unwrap());
+            if crc32fast::hash(&self.buffer) != checksum {
+                // We know where the message ends, so we can move on to the next one.
+                let num_bytes = prefix_len + self.buffer.len() as u64;
+                return Err(ReadFailure::skippable(
+                    DecodeError::ChecksumMismatch,
+                    num_bytes,
+                ));
+            }
+        }
+
+        Ok(true)
+    }
+
+    /// Deserializes the message in `self.buffer`, upgrading it to today's layout if needed.
+    fn decode_buffer(&mut self) -> Result<LogMsg, DecodeError> {
+        match &mut self.migration {
+            Some(migration) => migration.decode(&self.buffer),
+            None => crate::serializer::decode_log_msg(self.options.serializer, &self.buffer),
+        }
+    }
+}
+
+impl<R: std::io::Read + std::io::Seek> Decoder<R> {
+    /// Reads the index footer of a chunked `.rrd` file.
+    ///
+    /// Returns `None` for files using the stream layout, which have no index, as well as for
+    /// chunked files that were cut short before their index could be written.
+    pub fn read_index(read: &mut R) -> Result<Option<RrdIndex>, DecodeError> {
+        crate::profile_function!();
+        use std::io::SeekFrom;
+
+        read.rewind().map_err(DecodeError::Read)?;
+        let options = Header::read(read)?.options;
+        if options.layout == Layout::Stream {
+            return Ok(None);
+        }
+
+        let file_len = read.seek(SeekFrom::End(0)).map_err(DecodeError::Read)?;
+        if file_len < TRAILER_LEN {
+            return Ok(None);
+        }
+        read.seek(SeekFrom::Start(file_len - TRAILER_LEN))
+            .map_err(DecodeError::Read)?;
+        let mut trailer = [0_u8; TRAILER_LEN as usize];
+        read.read_exact(&mut trailer).map_err(DecodeError::Read)?;
+        if &trailer[24..] != INDEX_MAGIC {
+            return Ok(None);
+        }
+
+        let index_offset = u64::from_le_bytes(trailer[..8].try_into().unwrap());
+        let compressed_len = u64::from_le_bytes(trailer[8..16].try_into().unwrap());
+        let uncompressed_len = u64::from_le_bytes(trailer[16..24].try_into().unwrap()) as usize;
+
+        // Don't trust the trailer with the size of an allocation: the index must fit in the file.
+        let index_end = index_offset.checked_add(compressed_len);
+        if index_end.map_or(true, |index_end| index_end > file_len - TRAILER_LEN) {
+            return Err(DecodeError::Malformed(
+                "index out of the bounds of the file",
+            ));
+        }
+
+        read.seek(SeekFrom::Start(index_offset))
+            .map_err(DecodeError::Read)?;
+        let mut compressed = vec![0_u8; compressed_len as usize];
+        read.read_exact(&mut compressed)
+            .map_err(DecodeError::Read)?;
+        let index = decompress_frame(options.compression, compressed, uncompressed_len)?;
+
+        Ok(Some(rmp_serde::from_slice(&index)?))
+    }
+
+    /// Only decodes the messages needed to get all the data within `time_range` on `timeline`.
+    ///
+    /// For chunked `.rrd` files, this reads the index footer and then jumps straight to the frames
+    /// that intersect the given time range, the ones holding timeless data such as
+    /// [`LogMsg::BeginRecordingMsg`], and the ones holding the latest state of every entity right
+    /// before the start of the time range (see [`RrdIndex::frames_in_time_range`]).
+    /// Everything else is skipped without being read.
+    /// Note that frames can hold messages that fall outside the time range.
+    ///
+    /// Files without an index are decoded in full.
+    pub fn new_in_time_range(
+        mut read: R,
+        timeline: &Timeline,
+        time_range: TimeRange,
+    ) -> Result<Self, DecodeError> {
+        crate::profile_function!();
+
+        fn seek<R: std::io::Seek>(read: &mut R, offset: u64) -> std::io::Result<()> {
+            read.seek(std::io::SeekFrom::Start(offset)).map(|_| ())
+        }
+
+        let Some(index) = Self::read_index(&mut read)? else {
+            read.rewind().map_err(DecodeError::Read)?;
+            return Self::new(read);
+        };
+
+        read.rewind().map_err(DecodeError::Read)?;
+        let header = Header::read(&mut read)?;
+
+        let offsets = index
+            .frames_in_time_range(timeline, time_range)
+            .map(|frame| frame.offset)
+            .collect();
+
+        Ok(Self::with_body(
+            &header,
+            Body::Chunked(FrameReader {
+                read,
+                compression: header.options.compression,
+                selected_frames: Some((seek::<R> as SeekFn<R>, offsets)),
+                frame: Default::default(),
+                done: false,
+            }),
+        ))
+    }
+}
+
 impl<R: std::io::Read> Iterator for Decoder<R> {
     type Item = Result<LogMsg, DecodeError>;
 
     fn next(&mut self) -> Option<Self::Item> {
         crate::profile_function!();
-        use std::io::Read as _;
 
-        let mut len = [0_u8; 8];
-        self.zdecoder.read_exact(&mut len).ok()?;
-        let len = u64::from_le_bytes(len) as usize;
+        loop {
+            if self.done {
+                return None;
+            }
 
-        self.buffer.resize(len, 0);
+            let failure = match self.read_next() {
+                Ok(true) => {
+                    match self.decode_buffer() {
+                        Ok(msg) => {
+                            if let Some(recovery) = &mut self.recovery {
+                                recovery.num_messages += 1;
+                            }
+                            return Some(Ok(msg));
+                        }
+                        // Messages are length-prefixed, so we can always move on to the next one.
+                        Err(error) => ReadFailure::skippable(error, self.buffer.len() as u64),
+                    }
+                }
+                Ok(false) => return None,
+                Err(failure) => failure,
+            };
 
-        {
-            crate::profile_scope!("ruzstd");
-            if let Err(err) = self.zdecoder.read_exact(&mut self.buffer) {
-                return Some(Err(DecodeError::RuzstdRead(err)));
-            }
-        }
+            self.done = failure.fatal;
 
-        crate::profile_scope!("MsgPack deser");
-        match rmp_serde::from_read(&mut self.buffer.as_slice()) {
-            Ok(msg) => Some(Ok(msg)),
-            Err(err) => Some(Err(err.into())),
+            let Some(recovery) = &mut self.recovery else {
+                return Some(Err(failure.error));
+            };
+            re_log::debug!(
+                "Skipping {} bytes of undecodable data: {}",
+                failure.num_bytes,
+                failure.error
+            );
+            recovery.skipped.push(SkippedData {
+                num_bytes: failure.num_bytes,
+                error: failure.error,
+            });
         }
     }
 }
@@ -200,3 +722,391 @@ fn test_encode_decode() {
 
     assert_eq!(messages, decoded_messages);
 }
+
+#[cfg(all(test, feature = "decoder", feature = "encoder"))]
+pub(crate) fn frame_messages(num_frames: i64) -> Vec<LogMsg> {
+    use re_log_types::{
+        datagen::{build_frame_nr, build_some_point2d},
+        ApplicationId, BeginRecordingMsg, DataRow, MsgId, RecordingId, RecordingInfo,
+        RecordingSource, Time,
+    };
+
+    let recording_id = RecordingId::random();
+
+    let begin = LogMsg::BeginRecordingMsg(BeginRecordingMsg {
+        msg_id: MsgId::random(),
+        info: RecordingInfo {
+            application_id: ApplicationId("test".to_owned()),
+            recording_id,
+            is_official_example: true,
+            started: Time::now(),
+            recording_source: RecordingSource::RustSdk {
+                rustc_version: String::new(),
+                llvm_version: String::new(),
+            },
+        },
+    });
+
+    std::iter::once(begin)
+        .chain((0..num_frames).map(|frame_nr| {
+            let row = DataRow::from_cells1(
+                MsgId::random(),
+                "points",
+                [build_frame_nr(frame_nr.into())],
+                2,
+                build_some_point2d(2),
+            );
+            LogMsg::ArrowMsg(recording_id, (&row.into_table()).try_into().unwrap())
+        }))
+        .collect()
+}
+
+#[cfg(all(feature = "decoder", feature = "encoder"))]
+#[test]
+fn test_encode_decode_chunked() {
+    let messages = frame_messages(10);
+
+    for frame_size_bytes in [1, 1024, u64::MAX] {
+        let mut file = vec![];
+        crate::encoder::encode_with_options(
+            messages.iter(),
+            &mut file,
+            EncodingOptions {
+                layout: Layout::Chunked { frame_size_bytes },
+                serializer: Serializer::MsgPack,
+                compression: Compression::DEFAULT,
+            },
+        )
+        .unwrap();
+
+        let decoded_messages = Decoder::new(&mut file.as_slice())
+            .unwrap()
+            .collect::<Result<Vec<LogMsg>, DecodeError>>()
+            .unwrap();
+        assert_eq!(messages, decoded_messages);
+
+        let index = Decoder::read_index(&mut std::io::Cursor::new(&file))
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            messages.len() as u64,
+            index
+                .frames
+                .iter()
+                .map(|frame| frame.num_messages)
+                .sum::<u64>()
+        );
+    }
+
+    // The stream layout has no index.
+    let mut file = vec![];
+    crate::encoder::encode(messages.iter(), &mut file).unwrap();
+    assert!(Decoder::read_index(&mut std::io::Cursor::new(&file))
+        .unwrap()
+        .is_none());
+
+    // A corrupt trailer cannot request arbitrarily large allocations.
+    for compressed_len in [1 << 40, u64::MAX] {
+        let mut file = vec![];
+        crate::encoder::encode_with_options(
+            messages.iter(),
+            &mut file,
+            EncodingOptions {
+                layout: Layout::Chunked {
+                    frame_size_bytes: 1024,
+                },
+                serializer: Serializer::MsgPack,
+                compression: Compression::DEFAULT,
+            },
+        )
+        .unwrap();
+        let trailer_start = file.len() - TRAILER_LEN as usize;
+        file[trailer_start + 8..trailer_start + 16].copy_from_slice(&compressed_len.to_le_bytes());
+        assert!(matches!(
+            Decoder::read_index(&mut std::io::Cursor::new(&file)),
+            Err(DecodeError::Malformed(_))
+        ));
+    }
+}
+
+#[cfg(all(feature = "decoder", feature = "encoder"))]
+#[test]
+fn test_decode_in_time_range() {
+    use re_log_types::datagen::build_frame_nr;
+
+    let messages = frame_messages(10);
+    let (timeline, _) = build_frame_nr(0.into());
+    let time_range = TimeRange::new(5.into(), 7.into());
+
+    // One message per frame, so we get exactly what we asked for, plus the `BeginRecordingMsg` and
+    // the state right before the time range.
+    let mut file = vec![];
+    crate::encoder::encode_with_options(
+        messages.iter(),
+        &mut file,
+        EncodingOptions {
+            layout: Layout::Chunked {
+                frame_size_bytes: 1,
+            },
+            serializer: Serializer::MsgPack,
+            compression: Compression::DEFAULT,
+        },
+    )
+    .unwrap();
+
+    let decoded_messages =
+        Decoder::new_in_time_range(std::io::Cursor::new(&file), &timeline, time_range)
+            .unwrap()
+            .collect::<Result<Vec<LogMsg>, DecodeError>>()
+            .unwrap();
+    let expected = [&messages[0..1], &messages[5..9]].concat();
+    assert_eq!(expected, decoded_messages);
+
+    // Without an index, everything gets decoded.
+    let mut file = vec![];
+    crate::encoder::encode(messages.iter(), &mut file).unwrap();
+
+    let decoded_messages =
+        Decoder::new_in_time_range(std::io::Cursor::new(&file), &timeline, time_range)
+            .unwrap()
+            .collect::<Result<Vec<LogMsg>, DecodeError>>()
+            .unwrap();
+    assert_eq!(messages, decoded_messages);
+}
+
+#[cfg(all(feature = "decoder", feature = "encoder"))]
+#[test]
+fn test_encode_decode_arrow_ipc() {
+    let messages = frame_messages(10);
+
+    for layout in [Layout::Stream, Layout::CHUNKED] {
+        let options = EncodingOptions {
+            layout,
+            serializer: Serializer::ArrowIpc,
+            compression: Compression::DEFAULT,
+        };
+
+        let mut file = vec![];
+        crate::encoder::encode_with_options(messages.iter(), &mut file, options).unwrap();
+        assert_eq!(VERSIONED_MAGIC, &file[..4]);
+
+        let decoded_messages = Decoder::new(&mut file.as_slice())
+            .unwrap()
+            .collect::<Result<Vec<LogMsg>, DecodeError>>()
+            .unwrap();
+        assert_eq!(messages, decoded_messages);
+    }
+
+    // A single message, as sent over TCP.
+    let mut bytes = vec![];
+    crate::serializer::encode_log_msg(Serializer::ArrowIpc, &messages[1], &mut bytes).unwrap();
+    let decoded = crate::serializer::decode_log_msg(Serializer::ArrowIpc, &bytes).unwrap();
+    assert_eq!(messages[1], decoded);
+}
+
+#[cfg(all(feature = "decoder", feature = "encoder"))]
+#[test]
+fn test_encode_decode_compression() {
+    let messages = frame_messages(10);
+
+    for layout in [Layout::Stream, Layout::CHUNKED] {
+        for compression in [
+            Compression::Off,
+            Compression::Zstd { level: 1 },
+            Compression::Zstd { level: 19 },
+            Compression::Lz4,
+        ] {
+            let options = EncodingOptions {
+                layout,
+                serializer: Serializer::MsgPack,
+                compression,
+            };
+
+            let mut file = vec![];
+            crate::encoder::encode_with_options(messages.iter(), &mut file, options).unwrap();
+
+            let decoded_messages = Decoder::new(&mut file.as_slice())
+                .unwrap()
+                .collect::<Result<Vec<LogMsg>, DecodeError>>()
+                .unwrap();
+            assert_eq!(messages, decoded_messages, "{options:?}");
+        }
+    }
+
+    assert_eq!(Ok(Compression::Lz4), "lz4".parse());
+    assert_eq!(Ok(Compression::Zstd { level: 19 }), "zstd:19".parse());
+    assert!("zstd:max".parse::<Compression>().is_err());
+}
+
+#[cfg(all(feature = "decoder", feature = "encoder"))]
+#[test]
+fn test_decode_truncated() {
+    let messages = frame_messages(100);
+
+    for layout in [
+        Layout::Stream,
+        Layout::Chunked {
+            frame_size_bytes: 1024,
+        },
+    ] {
+        let options = EncodingOptions {
+            layout,
+            serializer: Serializer::MsgPack,
+            compression: Compression::DEFAULT,
+        };
+        let mut file = vec![];
+        crate::encoder::encode_with_options(messages.iter(), &mut file, options).unwrap();
+
+        // Cut off the end, as if the process writing the file had crashed.
+        let truncated = &file[..file.len() * 2 / 3];
+
+        let results = Decoder::new(truncated).unwrap().collect::<Vec<_>>();
+        assert!(results.last().unwrap().is_err(), "{layout:?}");
+
+        let mut decoder = Decoder::new_recovering(truncated).unwrap();
+        let decoded_messages = decoder
+            .by_ref()
+            .collect::<Result<Vec<LogMsg>, DecodeError>>()
+            .unwrap();
+        assert!(!decoded_messages.is_empty());
+        assert_eq!(&messages[..decoded_messages.len()], &decoded_messages);
+
+        let report = decoder.recovery_report().unwrap();
+        assert_eq!(decoded_messages.len() as u64, report.num_messages);
+        assert!(!report.is_clean(), "{layout:?}");
+    }
+}
+
+#[cfg(all(feature = "decoder", feature = "encoder"))]
+#[test]
+fn test_decode_checksum_mismatch() {
+    let messages = frame_messages(3);
+
+    let mut file = vec![];
+    crate::encoder::encode(messages.iter(), &mut file).unwrap();
+
+    // Flip a byte in the middle of the second message.
+    let header_len = 16;
+    let mut body = zstd::decode_all(&file[header_len..]).unwrap();
+    let first_len = u64::from_le_bytes(body[..8].try_into().unwrap()) as usize;
+    let second_start = 12 + first_len;
+    let second_len =
+        u64::from_le_bytes(body[second_start..second_start + 8].try_into().unwrap()) as usize;
+    body[second_start + 12 + second_len / 2] ^= 0xff;
+    file.truncate(header_len);
+    file.extend(zstd::encode_all(body.as_slice(), 0).unwrap());
+
+    let mut decoder = Decoder::new_recovering(file.as_slice()).unwrap();
+    let decoded_messages = decoder
+        .by_ref()
+        .collect::<Result<Vec<LogMsg>, DecodeError>>()
+        .unwrap();
+    assert_eq!(
+        vec![
+            messages[0].clone(),
+            messages[2].clone(),
+            messages[3].clone()
+        ],
+        decoded_messages
+    );
+
+    let report = decoder.recovery_report().unwrap();
+    assert_eq!(report.skipped.len(), 1);
+    assert!(matches!(
+        report.skipped[0].error,
+        DecodeError::ChecksumMismatch
+    ));
+}
+
+/// An `.rrd` file of format version 0, as written before checksums and [`EncodingOptions`].
+#[cfg(all(test, feature = "decoder", feature = "encoder"))]
+fn legacy_rrd(rerun_version: CrateVersion, messages: &[Vec<u8>]) -> Vec<u8> {
+    let mut body = vec![];
+    for msg in messages {
+        body.extend_from_slice(&(msg.len() as u64).to_le_bytes());
+        body.extend_from_slice(msg);
+    }
+
+    let mut file = LEGACY_MAGIC.to_vec();
+    file.extend_from_slice(&rerun_version.to_bytes());
+    file.extend(zstd::encode_all(body.as_slice(), 0).unwrap());
+    file
+}
+
+#[cfg(all(feature = "decoder", feature = "encoder"))]
+#[test]
+fn test_decode_legacy_format() {
+    let messages = frame_messages(3);
+    let serialized = messages
+        .iter()
+        .map(|msg| rmp_serde::to_vec_named(msg).unwrap())
+        .collect::<Vec<_>>();
+
+    let local_version = CrateVersion::parse(env!("CARGO_PKG_VERSION"));
+    let file = legacy_rrd(local_version, &serialized);
+    let decoded_messages = Decoder::new(file.as_slice())
+        .unwrap()
+        .collect::<Result<Vec<LogMsg>, DecodeError>>()
+        .unwrap();
+    assert_eq!(messages, decoded_messages);
+
+    // Files written before versions were recorded are loaded as they are, on a best-effort basis.
+    let mut file = legacy_rrd(local_version, &serialized);
+    file[4..8].copy_from_slice(&[0, 0, 0, 0]);
+    let decoded_messages = Decoder::new(file.as_slice())
+        .unwrap()
+        .collect::<Result<Vec<LogMsg>, DecodeError>>()
+        .unwrap();
+    assert_eq!(messages, decoded_messages);
+}
+
+#[cfg(all(feature = "decoder", feature = "encoder"))]
+#[test]
+fn test_migrate_v0_3() {
+    use re_log_types::{EntityPathOpMsg, MsgId, PathOp, TimePoint};
+
+    /// The parts of the 0.3 layout that are easy to produce.
+    #[allow(clippy::enum_variant_names)] // The names are part of the format.
+    #[derive(serde::Serialize)]
+    enum LogMsgV0_3 {
+        BeginRecordingMsg(re_log_types::BeginRecordingMsg),
+        EntityPathOpMsg(EntityPathOpMsg),
+        ArrowMsg((MsgId, Vec<u8>)),
+    }
+
+    let LogMsg::BeginRecordingMsg(begin) = frame_messages(0).remove(0) else {
+        unreachable!();
+    };
+    let recording_id = begin.info.recording_id;
+    let path_op = EntityPathOpMsg {
+        msg_id: MsgId::random(),
+        time_point: TimePoint::timeless(),
+        path_op: PathOp::ClearRecursive("points".into()),
+    };
+
+    let serialized = [
+        LogMsgV0_3::BeginRecordingMsg(begin.clone()),
+        LogMsgV0_3::EntityPathOpMsg(path_op.clone()),
+        LogMsgV0_3::ArrowMsg((MsgId::random(), vec![])),
+    ]
+    .iter()
+    .map(|msg| rmp_serde::to_vec_named(msg).unwrap())
+    .collect::<Vec<_>>();
+
+    let file = legacy_rrd(CrateVersion::new(0, 3, 1), &serialized);
+    let mut decoded_messages = Decoder::new(file.as_slice()).unwrap();
+    assert_eq!(
+        LogMsg::BeginRecordingMsg(begin),
+        decoded_messages.next().unwrap().unwrap()
+    );
+    assert_eq!(
+        LogMsg::EntityPathOpMsg(recording_id, path_op),
+        decoded_messages.next().unwrap().unwrap()
+    );
+
+    // We don't know the layout of the Arrow payloads of 0.3 well enough to migrate them.
+    assert!(matches!(
+        decoded_messages.next(),
+        Some(Err(DecodeError::Migration(_)))
+    ));
+}
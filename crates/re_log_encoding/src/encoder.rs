@@ -4,6 +4,12 @@ use std::io::Write as _;
 
 use re_log_types::LogMsg;
 
+use crate::{
+    index::{FrameInfo, RrdIndex, FRAME_HEADER_LEN, INDEX_MAGIC},
+    options::VERSIONED_MAGIC,
+    Compression, EncodingOptions, Layout, FORMAT_VERSION,
+};
+
 /// On failure to encode or serialize a [`LogMsg`].
 #[derive(thiserror::Error, Debug)]
 pub enum EncodeError {
@@ -13,23 +19,194 @@ pub enum EncodeError {
     #[error("Zstd error: {0}")]
     Zstd(std::io::Error),
 
+    #[error("Lz4 error: {0}")]
+    Lz4(std::io::Error),
+
     #[error("MsgPack error: {0}")]
     MsgPack(#[from] rmp_serde::encode::Error),
 
+    #[error("Arrow error: {0}")]
+    Arrow(#[from] re_log_types::external::arrow2::error::Error),
+
+    #[error("Failed to index message: {0}")]
+    Index(#[from] re_log_types::DataTableError),
+
     #[error("Called append on already finished encoder")]
     AlreadyFinished,
 }
 
+/// Compresses a single frame of a chunked `.rrd` file, or its index.
+fn compress_frame(compression: Compression, uncompressed: &[u8]) -> Result<Vec<u8>, EncodeError> {
+    crate::profile_function!();
+    match compression {
+        Compression::Off => Ok(uncompressed.to_vec()),
+        Compression::Zstd { level } => {
+            zstd::bulk::compress(uncompressed, level).map_err(EncodeError::Zstd)
+        }
+        Compression::Lz4 => Ok(lz4_flex::block::compress(uncompressed)),
+    }
+}
+
+/// Writes independently compressed frames, keeping track of their contents for the index footer.
+struct ChunkedWriter<W: std::io::Write> {
+    write: W,
+    compression: Compression,
+
+    /// How many bytes have been written to `write` so far.
+    offset: u64,
+
+    frame_size_bytes: u64,
+
+    /// Uncompressed, length-prefixed and checksummed messages of the current frame.
+    frame: Vec<u8>,
+    frame_info: FrameInfo,
+
+    index: RrdIndex,
+}
+
+impl<W: std::io::Write> ChunkedWriter<W> {
+    fn append(&mut self, msg: &LogMsg, encoded: &[u8]) -> Result<(), EncodeError> {
+        self.frame_info.add(msg)?;
+        self.frame
+            .extend_from_slice(&(encoded.len() as u64).to_le_bytes());
+        self.frame
+            .extend_from_slice(&crc32fast::hash(encoded).to_le_bytes());
+        self.frame.extend_from_slice(encoded);
+
+        if self.frame.len() as u64 >= self.frame_size_bytes {
+            self.flush_frame()?;
+        }
+
+        Ok(())
+    }
+
+    fn write_all(&mut self, bytes: &[u8]) -> Result<(), EncodeError> {
+        self.write.write_all(bytes).map_err(EncodeError::Write)?;
+        self.offset += bytes.len() as u64;
+        Ok(())
+    }
+
+    fn flush_frame(&mut self) -> Result<(), EncodeError> {
+        crate::profile_function!();
+
+        if self.frame.is_empty() {
+            return Ok(());
+        }
+
+        let compressed = compress_frame(self.compression, &self.frame)?;
+
+        let mut frame_info = std::mem::take(&mut self.frame_info);
+        frame_info.offset = self.offset;
+        self.index.frames.push(frame_info);
+
+        self.write_all(&(compressed.len() as u64).to_le_bytes())?;
+        self.write_all(&(self.frame.len() as u64).to_le_bytes())?;
+        self.write_all(&compressed)?;
+
+        self.frame.clear();
+
+        Ok(())
+    }
+
+    fn finish(mut self) -> Result<(), EncodeError> {
+        self.flush_frame()?;
+
+        // End-of-frames marker:
+        self.write_all(&[0; FRAME_HEADER_LEN as usize])?;
+
+        let index = rmp_serde::encode::to_vec_named(&self.index)?;
+        let compressed = compress_frame(self.compression, &index)?;
+        let index_offset = self.offset;
+        self.write_all(&compressed)?;
+
+        self.write_all(&index_offset.to_le_bytes())?;
+        self.write_all(&(compressed.len() as u64).to_le_bytes())?;
+        self.write_all(&(index.len() as u64).to_le_bytes())?;
+        self.write_all(INDEX_MAGIC)?;
+
+        self.write.flush().map_err(EncodeError::Write)
+    }
+}
+
+/// Compresses all messages as a single stream.
+enum StreamWriter<W: std::io::Write> {
+    Off(std::io::BufWriter<W>),
+    Zstd(zstd::stream::Encoder<'static, W>),
+    Lz4(Box<lz4_flex::frame::FrameEncoder<W>>),
+}
+
+impl<W: std::io::Write> StreamWriter<W> {
+    fn new(write: W, compression: Compression) -> Result<Self, EncodeError> {
+        Ok(match compression {
+            Compression::Off => Self::Off(std::io::BufWriter::new(write)),
+            Compression::Zstd { level } => {
+                Self::Zstd(zstd::stream::Encoder::new(write, level).map_err(EncodeError::Zstd)?)
+            }
+            Compression::Lz4 => Self::Lz4(Box::new(lz4_flex::frame::FrameEncoder::new(write))),
+        })
+    }
+
+    fn write_all(&mut self, bytes: &[u8]) -> Result<(), EncodeError> {
+        match self {
+            Self::Off(write) => write.write_all(bytes).map_err(EncodeError::Write),
+            Self::Zstd(encoder) => encoder.write_all(bytes).map_err(EncodeError::Zstd),
+            Self::Lz4(encoder) => encoder.write_all(bytes).map_err(EncodeError::Lz4),
+        }
+    }
+
+    fn get_ref(&self) -> &W {
+        match self {
+            Self::Off(write) => write.get_ref(),
+            Self::Zstd(encoder) => encoder.get_ref(),
+            Self::Lz4(encoder) => encoder.get_ref(),
+        }
+    }
+
+    fn flush(&mut self) -> Result<(), EncodeError> {
+        match self {
+            Self::Off(write) => write.flush().map_err(EncodeError::Write),
+            Self::Zstd(encoder) => encoder.flush().map_err(EncodeError::Zstd),
+            Self::Lz4(encoder) => encoder.flush().map_err(EncodeError::Lz4),
+        }
+    }
+
+    fn finish(self) -> Result<(), EncodeError> {
+        match self {
+            Self::Off(write) => {
+                write
+                    .into_inner()
+                    .map_err(|err| EncodeError::Write(err.into_error()))?;
+            }
+            Self::Zstd(encoder) => {
+                encoder.finish().map_err(EncodeError::Zstd)?;
+            }
+            Self::Lz4(encoder) => {
+                encoder
+                    .finish()
+                    .map_err(|err| EncodeError::Lz4(err.into()))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+enum Body<W: std::io::Write> {
+    Stream(StreamWriter<W>),
+    Chunked(ChunkedWriter<W>),
+}
+
 /// Encode a stream of [`LogMsg`] into an `.rrd` file.
 pub struct Encoder<W: std::io::Write> {
+    options: EncodingOptions,
+
     /// Set to None when finished.
-    zstd_encoder: Option<zstd::stream::Encoder<'static, W>>,
+    body: Option<Body<W>>,
     buffer: Vec<u8>,
 }
 
 impl<W: std::io::Write> Drop for Encoder<W> {
     fn drop(&mut self) {
-        if self.zstd_encoder.is_some() {
+        if self.body.is_some() {
             re_log::warn!("Encoder dropped without calling finish()!");
             if let Err(err) = self.finish() {
                 re_log::error!("Failed to finish encoding: {err}");
@@ -39,47 +216,98 @@ impl<W: std::io::Write> Drop for Encoder<W> {
 }
 
 impl<W: std::io::Write> Encoder<W> {
-    pub fn new(mut write: W) -> Result<Self, EncodeError> {
+    /// Encodes using [`EncodingOptions::DEFAULT`].
+    pub fn new(write: W) -> Result<Self, EncodeError> {
+        Self::with_options(write, EncodingOptions::DEFAULT)
+    }
+
+    pub fn with_options(mut write: W, options: EncodingOptions) -> Result<Self, EncodeError> {
         let rerun_version = re_build_info::CrateVersion::parse(env!("CARGO_PKG_VERSION"));
 
-        write.write_all(b"RRF0").map_err(EncodeError::Write)?;
-        write
-            .write_all(&rerun_version.to_bytes())
-            .map_err(EncodeError::Write)?;
+        let mut header = Vec::with_capacity(16);
+        header.extend_from_slice(VERSIONED_MAGIC);
+        header.extend_from_slice(&rerun_version.to_bytes());
+        header.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+        header.extend_from_slice(&options.to_bytes());
+        write.write_all(&header).map_err(EncodeError::Write)?;
 
-        let level = 3;
-        let zstd_encoder = zstd::stream::Encoder::new(write, level).map_err(EncodeError::Zstd)?;
+        let body = match options.layout {
+            Layout::Stream => Body::Stream(StreamWriter::new(write, options.compression)?),
+            Layout::Chunked { frame_size_bytes } => Body::Chunked(ChunkedWriter {
+                write,
+                compression: options.compression,
+                offset: header.len() as u64,
+                frame_size_bytes,
+                frame: Vec::new(),
+                frame_info: FrameInfo::default(),
+                index: RrdIndex::default(),
+            }),
+        };
 
         Ok(Self {
-            zstd_encoder: Some(zstd_encoder),
+            options,
+            body: Some(body),
             buffer: vec![],
         })
     }
 
     pub fn append(&mut self, message: &LogMsg) -> Result<(), EncodeError> {
         let Self {
-            zstd_encoder,
+            options,
+            body,
             buffer,
         } = self;
 
-        if let Some(zstd_encoder) = zstd_encoder {
-            buffer.clear();
-            rmp_serde::encode::write_named(buffer, message)?;
+        buffer.clear();
 
-            zstd_encoder
-                .write_all(&(buffer.len() as u64).to_le_bytes())
-                .map_err(EncodeError::Zstd)?;
-            zstd_encoder.write_all(buffer).map_err(EncodeError::Zstd)?;
+        match body {
+            Some(Body::Stream(writer)) => {
+                crate::serializer::encode_log_msg(options.serializer, message, buffer)?;
 
-            Ok(())
-        } else {
-            Err(EncodeError::AlreadyFinished)
+                writer.write_all(&(buffer.len() as u64).to_le_bytes())?;
+                writer.write_all(&crc32fast::hash(buffer).to_le_bytes())?;
+                writer.write_all(buffer)
+            }
+            Some(Body::Chunked(writer)) => {
+                crate::serializer::encode_log_msg(options.serializer, message, buffer)?;
+                writer.append(message, buffer)
+            }
+            None => Err(EncodeError::AlreadyFinished),
+        }
+    }
+
+    /// The writer the encoded data ends up in, until [`Self::finish`] is called.
+    ///
+    /// Data is compressed in blocks, so it lags behind what has been appended.
+    pub fn get_ref(&self) -> Option<&W> {
+        match &self.body {
+            Some(Body::Stream(writer)) => Some(writer.get_ref()),
+            Some(Body::Chunked(writer)) => Some(&writer.write),
+            None => None,
+        }
+    }
+
+    /// Writes out everything appended so far, so that a reader on the other end of e.g. a pipe
+    /// can decode it right away.
+    ///
+    /// This hurts the compression ratio, so don't call it after every message.
+    pub fn flush(&mut self) -> Result<(), EncodeError> {
+        match &mut self.body {
+            Some(Body::Stream(writer)) => writer.flush(),
+            Some(Body::Chunked(writer)) => {
+                writer.flush_frame()?;
+                writer.write.flush().map_err(EncodeError::Write)
+            }
+            None => Err(EncodeError::AlreadyFinished),
         }
     }
 
     pub fn finish(&mut self) -> Result<(), EncodeError> {
-        if let Some(zstd_encoder) = self.zstd_encoder.take() {
-            zstd_encoder.finish().map_err(EncodeError::Zstd)?;
+        if let Some(body) = self.body.take() {
+            match body {
+                Body::Stream(writer) => writer.finish()?,
+                Body::Chunked(writer) => writer.finish()?,
+            }
             Ok(())
         } else {
             re_log::warn!("Encoder::finish called twice");
@@ -92,7 +320,15 @@ pub fn encode<'a>(
     messages: impl Iterator<Item = &'a LogMsg>,
     write: impl std::io::Write,
 ) -> Result<(), EncodeError> {
-    let mut encoder = Encoder::new(write)?;
+    encode_with_options(messages, write, EncodingOptions::DEFAULT)
+}
+
+pub fn encode_with_options<'a>(
+    messages: impl Iterator<Item = &'a LogMsg>,
+    write: impl std::io::Write,
+    options: EncodingOptions,
+) -> Result<(), EncodeError> {
+    let mut encoder = Encoder::with_options(write, options)?;
     for message in messages {
         encoder.append(message)?;
     }
@@ -1,78 +1,510 @@
-use std::{path::PathBuf, sync::mpsc::Sender};
+use std::{
+    collections::{BTreeMap, HashSet, VecDeque},
+    path::{Path, PathBuf},
+};
 
-use parking_lot::Mutex;
+use re_log_types::{ComponentName, DataRow, DataTable, EntityPath, LogMsg, MsgId, RecordingId};
 
-use re_log_types::LogMsg;
+use crate::{
+    encoder::Encoder,
+    writer_sink::{MessageWriter, WriterSink},
+    EncodingOptions, SinkError,
+};
 
-/// Errors that can occur when creating a [`FileSink`].
-#[derive(thiserror::Error, Debug)]
-pub enum FileSinkError {
-    /// Error creating the file.
-    #[error("Failed to create file {0}: {1}")]
-    CreateFile(PathBuf, std::io::Error),
+/// When a [`FileSink`] should move on to a new file, and how many of the old ones to keep.
+///
+/// With rotation, the files are named after the given path with a running index, e.g.
+/// `recording_0000.rrd`, `recording_0001.rrd`, …
+/// Each of them starts with the [`re_log_types::BeginRecordingMsg`] and the latest timeless data
+/// of every recording, so that it can be opened on its own.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FileRotation {
+    /// Move on to a new file once the current one has grown to this many bytes.
+    ///
+    /// Data is compressed in blocks, so files end up slightly larger than this.
+    pub max_bytes: Option<u64>,
 
-    /// Error spawning the file writer thread.
-    #[error("Failed to spawn thread: {0}")]
-    SpawnThread(std::io::Error),
+    /// Move on to a new file once the current one has been written to for this long.
+    ///
+    /// This is checked whenever a message comes in.
+    pub max_duration: Option<std::time::Duration>,
 
-    /// Error encoding a log message.
-    #[error("Failed to encode LogMsg: {0}")]
-    LogMsgEncode(#[from] crate::encoder::EncodeError),
+    /// Delete the oldest files written by the sink so that at most this many remain.
+    pub max_files: Option<usize>,
 }
 
-/// Stream log messages to an `.rrd` file.
-pub struct FileSink {
-    // None = quit
-    tx: Mutex<Sender<Option<LogMsg>>>,
-    join_handle: Option<std::thread::JoinHandle<()>>,
+impl FileRotation {
+    /// Will there ever be more than one file?
+    pub fn is_enabled(&self) -> bool {
+        self.max_bytes.is_some() || self.max_duration.is_some()
+    }
 }
 
-impl Drop for FileSink {
-    fn drop(&mut self) {
-        self.tx.lock().send(None).ok();
-        if let Some(join_handle) = self.join_handle.take() {
-            join_handle.join().ok();
-        }
-    }
+/// Stream log messages to an `.rrd` file.
+pub struct FileSink {
+    sink: WriterSink,
 }
 
 impl FileSink {
     /// Start writing log messages to a file at the given path.
-    pub fn new(path: impl Into<std::path::PathBuf>) -> Result<Self, FileSinkError> {
-        let (tx, rx) = std::sync::mpsc::channel();
+    pub fn new(path: impl Into<std::path::PathBuf>) -> Result<Self, SinkError> {
+        Self::with_rotation(path, FileRotation::default())
+    }
 
+    /// Start writing log messages to files named after the given path, moving on to a new file
+    /// as dictated by `rotation`.
+    pub fn with_rotation(
+        path: impl Into<std::path::PathBuf>,
+        rotation: FileRotation,
+    ) -> Result<Self, SinkError> {
+        Self::with_options(path, EncodingOptions::DEFAULT, rotation)
+    }
+
+    /// Like [`Self::with_rotation`], but encodes the files with the given options, e.g. to pick
+    /// a cheaper [`crate::Compression`].
+    pub fn with_options(
+        path: impl Into<std::path::PathBuf>,
+        options: EncodingOptions,
+        rotation: FileRotation,
+    ) -> Result<Self, SinkError> {
         let path = path.into();
 
         re_log::debug!("Saving file to {path:?}…");
 
-        let file = std::fs::File::create(&path)
-            .map_err(|err| FileSinkError::CreateFile(path.clone(), err))?;
-        let mut encoder = crate::encoder::Encoder::new(file)?;
-
-        let join_handle = std::thread::Builder::new()
-            .name("file_writer".into())
-            .spawn(move || {
-                while let Ok(Some(log_msg)) = rx.recv() {
-                    if let Err(err) = encoder.append(&log_msg) {
-                        re_log::error!("Failed to save log stream to {path:?}: {err}");
-                        return;
-                    }
-                }
-                if let Err(err) = encoder.finish() {
-                    re_log::error!("Failed to save log stream to {path:?}: {err}");
-                } else {
-                    re_log::debug!("Log stream saved to {path:?}");
-                }
-            })
-            .map_err(FileSinkError::SpawnThread)?;
+        let writer = FileWriter::new(path.clone(), options, rotation)?;
+        let sink = WriterSink::spawn("file_writer", format!("{path:?}"), writer)?;
+
+        Ok(Self { sink })
+    }
+
+    /// Queues a message for writing.
+    ///
+    /// Failures to write are reported by [`Self::finish`].
+    pub fn send(&self, log_msg: LogMsg) {
+        self.sink.send(log_msg);
+    }
+
+    /// Has the sink stopped writing, because of an error?
+    ///
+    /// If so, [`Self::finish`] tells what went wrong.
+    pub fn has_failed(&self) -> bool {
+        self.sink.has_failed()
+    }
+
+    /// Waits for everything sent so far to be written, and reports the first error if any.
+    pub fn finish(self) -> Result<(), SinkError> {
+        self.sink.finish()
+    }
+}
+
+// ----------------------------------------------------------------------------
+
+/// Keeps track of how many bytes have been written to a file.
+struct CountingWriter {
+    file: std::fs::File,
+    num_bytes: u64,
+}
+
+impl std::io::Write for CountingWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let num_bytes = self.file.write(buf)?;
+        self.num_bytes += num_bytes as u64;
+        Ok(num_bytes)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.file.flush()
+    }
+}
+
+/// The file currently being written to.
+struct Segment {
+    path: PathBuf,
+    encoder: Encoder<CountingWriter>,
+    started: std::time::Instant,
+
+    /// Messages appended to this file, not counting the repeated recordings and timeless data.
+    num_messages: usize,
+}
 
+impl Segment {
+    fn create(path: PathBuf, options: EncodingOptions) -> Result<Self, SinkError> {
+        let file =
+            std::fs::File::create(&path).map_err(|err| SinkError::CreateFile(path.clone(), err))?;
+        let encoder = Encoder::with_options(CountingWriter { file, num_bytes: 0 }, options)?;
         Ok(Self {
-            tx: tx.into(),
-            join_handle: Some(join_handle),
+            path,
+            encoder,
+            started: std::time::Instant::now(),
+            num_messages: 0,
         })
     }
+}
 
-    pub fn send(&self, log_msg: LogMsg) {
-        self.tx.lock().send(Some(log_msg)).ok();
+/// Writes the log stream to one or more files, see [`FileRotation`].
+struct FileWriter {
+    path: PathBuf,
+    options: EncodingOptions,
+    rotation: FileRotation,
+
+    segment: Segment,
+
+    /// Earlier files that haven't been deleted yet, oldest first.
+    old_segments: VecDeque<PathBuf>,
+    next_index: usize,
+
+    /// The latest `BeginRecordingMsg` of each recording, to repeat at the start of each file.
+    begin_recordings: BTreeMap<RecordingId, LogMsg>,
+
+    /// The latest timeless row of each component, to repeat at the start of each file.
+    timeless_rows: BTreeMap<(RecordingId, EntityPath, ComponentName), DataRow>,
+}
+
+impl MessageWriter for FileWriter {
+    fn append(&mut self, log_msg: &LogMsg) -> Result<(), SinkError> {
+        if self.rotation.is_enabled() {
+            if self.should_rotate() {
+                self.rotate()?;
+            }
+            self.remember(log_msg)?;
+        }
+
+        self.segment.encoder.append(log_msg)?;
+        self.segment.num_messages += 1;
+        Ok(())
     }
+
+    fn finish(mut self) -> Result<(), SinkError> {
+        self.segment.encoder.finish()?;
+        Ok(())
+    }
+}
+
+impl FileWriter {
+    fn new(
+        path: PathBuf,
+        options: EncodingOptions,
+        rotation: FileRotation,
+    ) -> Result<Self, SinkError> {
+        let segment = if rotation.is_enabled() {
+            Segment::create(segment_path(&path, 0), options)?
+        } else {
+            Segment::create(path.clone(), options)?
+        };
+
+        Ok(Self {
+            path,
+            options,
+            rotation,
+            segment,
+            old_segments: Default::default(),
+            next_index: 1,
+            begin_recordings: Default::default(),
+            timeless_rows: Default::default(),
+        })
+    }
+
+    fn num_bytes_in_segment(&self) -> u64 {
+        self.segment
+            .encoder
+            .get_ref()
+            .map_or(0, |writer| writer.num_bytes)
+    }
+
+    fn should_rotate(&self) -> bool {
+        let FileRotation {
+            max_bytes,
+            max_duration,
+            max_files: _,
+        } = &self.rotation;
+
+        // Never leave behind a file with nothing but what was repeated from the previous one.
+        if self.segment.num_messages == 0 {
+            return false;
+        }
+
+        max_bytes.map_or(false, |max_bytes| self.num_bytes_in_segment() >= max_bytes)
+            || max_duration.map_or(false, |max_duration| {
+                self.segment.started.elapsed() >= max_duration
+            })
+    }
+
+    /// Finishes the current file and starts a new one.
+    fn rotate(&mut self) -> Result<(), SinkError> {
+        crate::profile_function!();
+
+        let path = segment_path(&self.path, self.next_index);
+        self.next_index += 1;
+
+        let mut segment = Segment::create(path, self.options)?;
+
+        for begin_recording in self.begin_recordings.values() {
+            segment.encoder.append(begin_recording)?;
+        }
+
+        // A single row can be the latest one for several components: only write it once.
+        let mut rows_per_recording = BTreeMap::<RecordingId, Vec<DataRow>>::new();
+        let mut row_ids = HashSet::new();
+        for ((recording_id, _, _), row) in &self.timeless_rows {
+            if row_ids.insert(row.row_id()) {
+                rows_per_recording
+                    .entry(*recording_id)
+                    .or_default()
+                    .push(row.clone());
+            }
+        }
+        for (recording_id, rows) in rows_per_recording {
+            let table = DataTable::from_rows(MsgId::random(), rows);
+            segment
+                .encoder
+                .append(&LogMsg::ArrowMsg(recording_id, (&table).try_into()?))?;
+        }
+
+        let mut old_segment = std::mem::replace(&mut self.segment, segment);
+        old_segment.encoder.finish()?;
+        re_log::debug!("Log stream continues in {:?}", self.segment.path);
+        self.old_segments.push_back(old_segment.path);
+
+        if let Some(max_files) = self.rotation.max_files {
+            while self.old_segments.len() + 1 > max_files.max(1) {
+                let Some(path) = self.old_segments.pop_front() else { break; };
+                re_log::debug!("Deleting {path:?}");
+                if let Err(err) = std::fs::remove_file(&path) {
+                    re_log::warn!("Failed to delete {path:?}: {err}");
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Keeps track of what needs repeating at the start of each file.
+    fn remember(&mut self, log_msg: &LogMsg) -> Result<(), SinkError> {
+        match log_msg {
+            LogMsg::BeginRecordingMsg(msg) => {
+                self.begin_recordings
+                    .insert(msg.info.recording_id, log_msg.clone());
+            }
+
+            LogMsg::EntityPathOpMsg(recording_id, msg) => {
+                if msg.time_point.is_timeless() {
+                    let (path, recursive) = match &msg.path_op {
+                        re_log_types::PathOp::ClearComponents(path) => (path, false),
+                        re_log_types::PathOp::ClearRecursive(path) => (path, true),
+                    };
+                    self.timeless_rows
+                        .retain(|(row_recording_id, row_path, _), _| {
+                            row_recording_id != recording_id
+                                || !(row_path == path
+                                    || (recursive && row_path.is_descendant_of(path)))
+                        });
+                }
+            }
+
+            LogMsg::ArrowMsg(recording_id, msg) => {
+                // A table can mix timeless and temporal rows, so look at each of them.
+                let table = DataTable::try_from(msg)?;
+                for row in table.as_rows() {
+                    if !row.timepoint().is_timeless() {
+                        continue;
+                    }
+                    for component in row.components() {
+                        let key = (*recording_id, row.entity_path().clone(), component);
+                        self.timeless_rows.insert(key, row.clone());
+                    }
+                }
+            }
+
+            LogMsg::Goodbye(_) => {}
+        }
+
+        Ok(())
+    }
+}
+
+/// `recording.rrd` -> `recording_0042.rrd`
+fn segment_path(path: &Path, index: usize) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let extension = path
+        .extension()
+        .map_or_else(|| "rrd".into(), |extension| extension.to_string_lossy());
+    path.with_file_name(format!("{stem}_{index:04}.{extension}"))
+}
+
+// ----------------------------------------------------------------------------
+
+#[cfg(all(test, feature = "decoder"))]
+fn test_messages() -> Vec<LogMsg> {
+    use re_log_types::{datagen::build_some_point2d, TimePoint};
+
+    let mut messages = crate::decoder::frame_messages(5);
+    let LogMsg::BeginRecordingMsg(begin) = &messages[0] else {
+        unreachable!()
+    };
+    let row = DataRow::from_cells1(
+        MsgId::random(),
+        "points",
+        TimePoint::timeless(),
+        2,
+        build_some_point2d(2),
+    );
+    let timeless = LogMsg::ArrowMsg(
+        begin.info.recording_id,
+        (&row.into_table()).try_into().unwrap(),
+    );
+    messages.insert(1, timeless);
+    messages
+}
+
+#[cfg(all(test, feature = "decoder"))]
+fn write_rotated(messages: &[LogMsg], rotation: FileRotation) -> tempfile::TempDir {
+    let directory = tempfile::tempdir().unwrap();
+
+    let mut writer = FileWriter::new(
+        directory.path().join("recording.rrd"),
+        EncodingOptions::DEFAULT,
+        rotation,
+    )
+    .unwrap();
+    for log_msg in messages {
+        writer.append(log_msg).unwrap();
+    }
+    writer.finish().unwrap();
+
+    directory
+}
+
+#[cfg(all(test, feature = "decoder"))]
+fn read_file(path: &Path) -> Vec<LogMsg> {
+    crate::decoder::Decoder::new(std::fs::File::open(path).unwrap())
+        .unwrap()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap()
+}
+
+#[cfg(all(test, feature = "decoder"))]
+fn files_in(directory: &Path) -> Vec<PathBuf> {
+    let mut paths = std::fs::read_dir(directory)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect::<Vec<_>>();
+    paths.sort();
+    paths
+}
+
+#[cfg(feature = "decoder")]
+#[test]
+fn test_file_rotation() {
+    use re_log_types::ArrowMsg;
+
+    let messages = test_messages();
+
+    // Every file is over the limit as soon as a message has been written to it,
+    // so each message gets a file of its own.
+    let rotation = FileRotation {
+        max_bytes: Some(1),
+        ..Default::default()
+    };
+    let directory = write_rotated(&messages, rotation);
+
+    let paths = files_in(directory.path());
+    let names = paths
+        .iter()
+        .map(|path| path.file_name().unwrap().to_string_lossy().into_owned())
+        .collect::<Vec<_>>();
+    let expected = (0..messages.len())
+        .map(|index| format!("recording_{index:04}.rrd"))
+        .collect::<Vec<_>>();
+    assert_eq!(expected, names);
+
+    assert_eq!(messages[..1], read_file(&paths[0]));
+    assert_eq!(messages[..2], read_file(&paths[1]));
+
+    // Each file starts over with the recording and its timeless data.
+    for (path, log_msg) in paths[2..].iter().zip(&messages[2..]) {
+        let decoded = read_file(path);
+        assert_eq!(3, decoded.len());
+        assert_eq!(messages[0], decoded[0]);
+        assert!(matches!(
+            &decoded[1],
+            LogMsg::ArrowMsg(_, ArrowMsg { timepoint_max, .. }) if timepoint_max.is_timeless()
+        ));
+        assert_eq!(log_msg, &decoded[2]);
+    }
+}
+
+#[cfg(feature = "decoder")]
+#[test]
+fn test_file_rotation_mixed_table() {
+    use re_log_types::{datagen::build_some_point2d, TimePoint};
+
+    let mut messages = crate::decoder::frame_messages(2);
+    let LogMsg::BeginRecordingMsg(begin) = &messages[0] else {
+        unreachable!()
+    };
+    let recording_id = begin.info.recording_id;
+
+    // The temporal row gives the table a `timepoint_max`, the timeless one must still carry over.
+    let timeless_row = DataRow::from_cells1(
+        MsgId::random(),
+        "timeless",
+        TimePoint::timeless(),
+        2,
+        build_some_point2d(2),
+    );
+    let temporal_row = DataRow::from_cells1(
+        MsgId::random(),
+        "temporal",
+        [re_log_types::datagen::build_frame_nr(42.into())],
+        2,
+        build_some_point2d(2),
+    );
+    let table = DataTable::from_rows(MsgId::random(), [timeless_row.clone(), temporal_row]);
+    messages.insert(
+        1,
+        LogMsg::ArrowMsg(recording_id, (&table).try_into().unwrap()),
+    );
+
+    let rotation = FileRotation {
+        max_bytes: Some(1),
+        ..Default::default()
+    };
+    let directory = write_rotated(&messages, rotation);
+
+    let paths = files_in(directory.path());
+    let decoded = read_file(paths.last().unwrap());
+    assert_eq!(3, decoded.len());
+    let LogMsg::ArrowMsg(_, msg) = &decoded[1] else {
+        panic!("expected the repeated timeless data, got {:?}", decoded[1]);
+    };
+    let rows = DataTable::try_from(msg)
+        .unwrap()
+        .as_rows()
+        .collect::<Vec<_>>();
+    assert_eq!(1, rows.len());
+    assert_eq!(timeless_row.row_id(), rows[0].row_id());
+    assert!(rows[0].timepoint().is_timeless());
+}
+
+#[cfg(feature = "decoder")]
+#[test]
+fn test_file_rotation_retention() {
+    let messages = test_messages();
+
+    let rotation = FileRotation {
+        max_bytes: Some(1),
+        max_files: Some(2),
+        ..Default::default()
+    };
+    let directory = write_rotated(&messages, rotation);
+
+    let names = files_in(directory.path())
+        .iter()
+        .map(|path| path.file_name().unwrap().to_string_lossy().into_owned())
+        .collect::<Vec<_>>();
+    let num_files = messages.len();
+    let expected = (num_files - 2..num_files)
+        .map(|index| format!("recording_{index:04}.rrd"))
+        .collect::<Vec<_>>();
+    assert_eq!(expected, names);
 }
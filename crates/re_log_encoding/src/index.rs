@@ -0,0 +1,278 @@
+//! The index footer of `.rrd` files using the chunked layout.
+//!
+//! A chunked `.rrd` file looks like this:
+//! ```text
+//! "RRF1" | rerun version (4 bytes) | format version (4 bytes) | encoding options (4 bytes)
+//! frame header | compressed frame of length-prefixed, checksummed serialized messages
+//! frame header | compressed frame of length-prefixed, checksummed serialized messages
+//! …
+//! end-of-frames marker (an empty frame header)
+//! compressed index
+//! trailer: index offset, index length, uncompressed index length (all u64 LE) | "RRFI"
+//! ```
+//!
+//! Each frame is compressed independently, so that a reader can use the [`RrdIndex`] at the end
+//! of the file to jump straight to the frames it cares about.
+//! The frames can still be read one after the other without ever looking at the index, e.g. if
+//! the file was cut short before the index could be written.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use re_log_types::{ComponentName, EntityPath, TimeInt, TimeRange, Timeline};
+
+// ----------------------------------------------------------------------------
+
+/// Magic bytes at the very end of `.rrd` files using the chunked layout.
+pub(crate) const INDEX_MAGIC: &[u8; 4] = b"RRFI";
+
+/// Size of a frame header: compressed length and uncompressed length, both as u64 LE.
+///
+/// A header where both are zero marks the end of the frames.
+pub(crate) const FRAME_HEADER_LEN: u64 = 16;
+
+/// The names under which [`re_log_types::PathOp`]s are accounted for in
+/// [`FrameInfo::components`], as if they were components of the entity they apply to.
+pub const CLEAR_COMPONENTS: &str = "rerun.path_op.clear_components";
+pub const CLEAR_RECURSIVE: &str = "rerun.path_op.clear_recursive";
+
+/// Size of the trailer at the very end of the file: index offset, compressed index length and
+/// uncompressed index length (all u64 LE), followed by [`INDEX_MAGIC`].
+#[cfg(feature = "decoder")]
+pub(crate) const TRAILER_LEN: u64 = 3 * 8 + 4;
+
+// ----------------------------------------------------------------------------
+
+/// What we know about the contents of a single frame of a chunked `.rrd` file.
+#[derive(Clone, Debug, Default, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct FrameInfo {
+    /// Offset of the frame header, in bytes from the start of the file.
+    pub offset: u64,
+
+    /// Number of [`LogMsg`](re_log_types::LogMsg)s in the frame.
+    pub num_messages: u64,
+
+    /// Does this frame contain anything that isn't tied to a point in time?
+    ///
+    /// E.g. [`re_log_types::BeginRecordingMsg`] or timeless components. Such frames are needed no
+    /// matter which time window one is interested in.
+    pub has_timeless: bool,
+
+    /// The time range covered by the messages of the frame, for each timeline.
+    pub time_ranges: BTreeMap<Timeline, TimeRange>,
+
+    /// All the entity paths that have data or operations in the frame.
+    pub entity_paths: BTreeSet<EntityPath>,
+
+    /// The time range covered by each component of each entity in the frame, for each timeline.
+    ///
+    /// Path operations are accounted for as [`CLEAR_COMPONENTS`] and [`CLEAR_RECURSIVE`].
+    /// This is what makes it possible to find the latest state of an entity at any point in time
+    /// without reading the whole file.
+    #[serde(default)]
+    pub components: BTreeMap<EntityPath, BTreeMap<ComponentName, BTreeMap<Timeline, TimeRange>>>,
+}
+
+impl FrameInfo {
+    /// Does this frame contain any data in the given time range on the given timeline?
+    #[inline]
+    pub fn intersects(&self, timeline: &Timeline, time_range: TimeRange) -> bool {
+        self.time_ranges.get(timeline).map_or(false, |range| {
+            range.min <= time_range.max && time_range.min <= range.max
+        })
+    }
+}
+
+#[cfg(feature = "encoder")]
+#[cfg(not(target_arch = "wasm32"))]
+impl FrameInfo {
+    /// Accounts for a message that is about to be written in this frame.
+    pub(crate) fn add(
+        &mut self,
+        msg: &re_log_types::LogMsg,
+    ) -> Result<(), re_log_types::DataTableError> {
+        use re_log_types::{DataTable, LogMsg, PathOp};
+
+        self.num_messages += 1;
+
+        match msg {
+            LogMsg::BeginRecordingMsg(_) | LogMsg::Goodbye(_) => self.has_timeless = true,
+
+            LogMsg::EntityPathOpMsg(_, msg) => {
+                let ent_path = msg.path_op.entity_path();
+                self.entity_paths.insert(ent_path.clone());
+                if msg.time_point.is_timeless() {
+                    self.has_timeless = true;
+                }
+
+                let op = match &msg.path_op {
+                    PathOp::ClearComponents(_) => CLEAR_COMPONENTS,
+                    PathOp::ClearRecursive(_) => CLEAR_RECURSIVE,
+                };
+                let op_time_ranges = self
+                    .components
+                    .entry(ent_path.clone())
+                    .or_default()
+                    .entry(ComponentName::from(op))
+                    .or_default();
+                for (timeline, time) in msg.time_point.iter() {
+                    extend_time_range(&mut self.time_ranges, timeline, *time);
+                    extend_time_range(op_time_ranges, timeline, *time);
+                }
+            }
+
+            LogMsg::ArrowMsg(_, msg) => {
+                let table = DataTable::try_from(msg)?;
+
+                self.entity_paths.extend(table.entity_path.iter().cloned());
+
+                for (timeline, times) in &table.col_timelines {
+                    for time in times.iter().flatten() {
+                        extend_time_range(&mut self.time_ranges, timeline, TimeInt::from(*time));
+                    }
+                }
+
+                for (component, cells) in &table.columns {
+                    for (i, _) in cells.iter().enumerate().filter(|(_, cell)| cell.is_some()) {
+                        let time_ranges = self
+                            .components
+                            .entry(table.entity_path[i].clone())
+                            .or_default()
+                            .entry(*component)
+                            .or_default();
+                        for (timeline, times) in &table.col_timelines {
+                            if let Some(time) = times[i] {
+                                extend_time_range(time_ranges, timeline, TimeInt::from(time));
+                            }
+                        }
+                    }
+                }
+
+                let has_timeless_rows = (0..table.num_rows() as usize)
+                    .any(|i| table.col_timelines.values().all(|times| times[i].is_none()));
+                if has_timeless_rows {
+                    self.has_timeless = true;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "encoder")]
+#[cfg(not(target_arch = "wasm32"))]
+fn extend_time_range(
+    time_ranges: &mut BTreeMap<Timeline, TimeRange>,
+    timeline: &Timeline,
+    time: TimeInt,
+) {
+    time_ranges
+        .entry(*timeline)
+        .and_modify(|range| *range = range.union(TimeRange::point(time)))
+        .or_insert_with(|| TimeRange::point(time));
+}
+
+/// The index footer of a chunked `.rrd` file, describing every frame in the file.
+#[derive(Clone, Debug, Default, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct RrdIndex {
+    /// All frames, in the order they appear in the file.
+    pub frames: Vec<FrameInfo>,
+}
+
+impl RrdIndex {
+    /// The frames one needs to read in order to get all the data in the given time range on the
+    /// given timeline.
+    ///
+    /// This includes all frames that contain timeless data, as well as the frames holding the
+    /// latest state of every component and the latest path operations of every entity right
+    /// before the start of the time range: without these, the state at the start of the time
+    /// range would be wrong.
+    pub fn frames_in_time_range<'a>(
+        &'a self,
+        timeline: &'a Timeline,
+        time_range: TimeRange,
+    ) -> impl Iterator<Item = &'a FrameInfo> + 'a {
+        let latest_before = self.frames_with_latest_state_before(timeline, time_range.min);
+        self.frames
+            .iter()
+            .enumerate()
+            .filter_map(move |(i, frame)| {
+                let is_needed = frame.has_timeless
+                    || frame.intersects(timeline, time_range)
+                    || latest_before.contains(&i);
+                is_needed.then_some(frame)
+            })
+    }
+
+    /// The indices of the frames that might hold the latest value of a component, or the latest
+    /// path operation on an entity, strictly before `time` on `timeline`.
+    ///
+    /// We only know the time range covered by each component in each frame, so this errs on the
+    /// side of caution: every frame that could hold the latest value is returned.
+    fn frames_with_latest_state_before(
+        &self,
+        timeline: &Timeline,
+        time: TimeInt,
+    ) -> BTreeSet<usize> {
+        if time == TimeInt::MIN {
+            return Default::default();
+        }
+        let before = TimeInt::from(time.as_i64() - 1);
+
+        // For each component: the frames that have values before `time`, along with the bounds
+        // of the latest such value within each frame.
+        let mut candidates = BTreeMap::<_, Vec<(usize, TimeInt, TimeInt)>>::new();
+        for (i, frame) in self.frames.iter().enumerate() {
+            for (ent_path, components) in &frame.components {
+                for (component, time_ranges) in components {
+                    let Some(range) = time_ranges.get(timeline) else { continue };
+                    if range.min <= before {
+                        candidates.entry((ent_path, component)).or_default().push((
+                            i,
+                            range.min,
+                            range.max.min(before),
+                        ));
+                    }
+                }
+            }
+        }
+
+        let mut frames = BTreeSet::new();
+        for candidates in candidates.values() {
+            // The latest value is at least as recent as the earliest value of any frame, which
+            // rules out all the frames that end before that.
+            let lower_bound = candidates.iter().map(|(_, min, _)| *min).max();
+            frames.extend(
+                candidates
+                    .iter()
+                    .filter(|(_, _, max)| Some(*max) >= lower_bound)
+                    .map(|(i, _, _)| *i),
+            );
+        }
+        frames
+    }
+
+    /// The frames that contain data or operations for the given entity path.
+    pub fn frames_with_entity<'a>(
+        &'a self,
+        ent_path: &'a EntityPath,
+    ) -> impl Iterator<Item = &'a FrameInfo> + 'a {
+        self.frames
+            .iter()
+            .filter(move |frame| frame.entity_paths.contains(ent_path))
+    }
+
+    /// The time range covered by the whole file, for each timeline.
+    pub fn time_ranges(&self) -> BTreeMap<Timeline, TimeRange> {
+        let mut time_ranges = BTreeMap::<Timeline, TimeRange>::new();
+        for frame in &self.frames {
+            for (timeline, range) in &frame.time_ranges {
+                time_ranges
+                    .entry(*timeline)
+                    .and_modify(|r| *r = r.union(*range))
+                    .or_insert(*range);
+            }
+        }
+        time_ranges
+    }
+}
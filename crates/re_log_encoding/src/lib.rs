@@ -10,6 +10,28 @@ pub mod encoder;
 #[cfg(not(target_arch = "wasm32"))]
 mod file_sink;
 
+#[cfg(feature = "encoder")]
+#[cfg(not(target_arch = "wasm32"))]
+mod stdout_sink;
+
+#[cfg(feature = "encoder")]
+#[cfg(not(target_arch = "wasm32"))]
+mod writer_sink;
+
+#[cfg(any(feature = "decoder", feature = "encoder"))]
+pub mod index;
+
+#[cfg(feature = "decoder")]
+mod migration;
+
+mod options;
+
+#[cfg(any(
+    feature = "decoder",
+    all(feature = "encoder", not(target_arch = "wasm32"))
+))]
+pub mod serializer;
+
 #[cfg(feature = "decoder")]
 pub mod stream_rrd_from_http;
 
@@ -17,7 +39,23 @@ pub mod stream_rrd_from_http;
 
 #[cfg(feature = "encoder")]
 #[cfg(not(target_arch = "wasm32"))]
-pub use file_sink::{FileSink, FileSinkError};
+pub use file_sink::{FileRotation, FileSink};
+
+#[cfg(feature = "encoder")]
+#[cfg(not(target_arch = "wasm32"))]
+pub use stdout_sink::StdoutSink;
+
+#[cfg(feature = "encoder")]
+#[cfg(not(target_arch = "wasm32"))]
+pub use writer_sink::SinkError;
+
+#[cfg(feature = "encoder")]
+#[cfg(not(target_arch = "wasm32"))]
+/// The error type of [`FileSink`], now shared with [`StdoutSink`].
+#[deprecated = "renamed to `SinkError`, which is shared with `StdoutSink`"]
+pub type FileSinkError = SinkError;
+
+pub use options::{Compression, EncodingOptions, Layout, Serializer, FORMAT_VERSION};
 
 // ---------------------------------------------------------------------------
 
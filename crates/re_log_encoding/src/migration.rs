@@ -0,0 +1,103 @@
+//! Upgrading [`LogMsg`]s written by older versions of Rerun.
+//!
+//! The header of every `.rrd` file records the version of Rerun that wrote it.
+//! Whenever the layout of [`LogMsg`] changes in a way that old data can no longer be deserialized
+//! with, a frozen copy of the old layout is kept in here, along with the code to turn it into
+//! today's [`LogMsg`].
+//!
+//! Supported layouts:
+//! * 0.3: messages carry no [`RecordingId`].
+//!   Their Arrow payloads are not migrated: every `ArrowMsg` fails to decode.
+//! * 0.4 and later: today's layout.
+//!
+//! Data logged with other versions is decoded as is, which might fail in subtle ways.
+
+use re_build_info::CrateVersion;
+use re_log_types::{BeginRecordingMsg, EntityPathOpMsg, LogMsg, MsgId, RecordingId};
+
+use crate::decoder::DecodeError;
+
+// ----------------------------------------------------------------------------
+
+/// Decodes messages written by an older version of Rerun, upgrading them to today's [`LogMsg`].
+pub(crate) enum Migration {
+    /// Written by Rerun 0.3.
+    V0_3(MigrationV0_3),
+}
+
+impl Migration {
+    /// What it takes to decode messages written by the given version of Rerun.
+    ///
+    /// `None` means they can be decoded as they are.
+    pub(crate) fn for_version(version: CrateVersion) -> Option<Self> {
+        let local_version = CrateVersion::parse(env!("CARGO_PKG_VERSION"));
+        if version.is_compatible_with(local_version) {
+            return None;
+        }
+
+        if let [0, 3, ..] = version.to_bytes() {
+            re_log::debug!("Migrating log stream from Rerun {version} to {local_version}");
+            return Some(Self::V0_3(MigrationV0_3::default()));
+        }
+
+        // Older, newer, or an alpha of a supported version: all we can do is try.
+        re_log::warn!("Found log stream with Rerun version {version}, which is incompatible with the local Rerun version {local_version}. Loading will try to continue, but might fail in subtle ways.");
+        None
+    }
+
+    /// Deserializes a single message, and upgrades it to today's layout.
+    pub(crate) fn decode(&mut self, bytes: &[u8]) -> Result<LogMsg, DecodeError> {
+        crate::profile_function!();
+
+        match self {
+            Self::V0_3(migration) => migration.decode(bytes),
+        }
+    }
+}
+
+fn migration_error(reason: impl Into<String>) -> DecodeError {
+    DecodeError::Migration(reason.into())
+}
+
+// ----------------------------------------------------------------------------
+// 0.3 -> 0.4
+
+/// [`LogMsg`] as of Rerun 0.3.
+#[derive(serde::Deserialize)]
+enum LogMsgV0_3 {
+    BeginRecordingMsg(BeginRecordingMsg),
+    EntityPathOpMsg(EntityPathOpMsg),
+    /// The layout of the Arrow payload is not migrated, see the module docs.
+    ArrowMsg(serde::de::IgnoredAny),
+    Goodbye(MsgId),
+}
+
+/// Upgrades messages written by Rerun 0.3.
+#[derive(Default)]
+pub(crate) struct MigrationV0_3 {
+    /// Rerun 0.3 messages belong to the most recently begun recording.
+    recording_id: Option<RecordingId>,
+}
+
+impl MigrationV0_3 {
+    fn decode(&mut self, bytes: &[u8]) -> Result<LogMsg, DecodeError> {
+        match rmp_serde::from_slice::<LogMsgV0_3>(bytes)? {
+            LogMsgV0_3::BeginRecordingMsg(msg) => {
+                self.recording_id = Some(msg.info.recording_id);
+                Ok(LogMsg::BeginRecordingMsg(msg))
+            }
+            LogMsgV0_3::EntityPathOpMsg(msg) => {
+                Ok(LogMsg::EntityPathOpMsg(self.recording_id()?, msg))
+            }
+            LogMsgV0_3::ArrowMsg(_) => Err(migration_error(
+                "Arrow data logged with Rerun 0.3 is not supported",
+            )),
+            LogMsgV0_3::Goodbye(msg_id) => Ok(LogMsg::Goodbye(msg_id)),
+        }
+    }
+
+    fn recording_id(&self) -> Result<RecordingId, DecodeError> {
+        self.recording_id
+            .ok_or_else(|| migration_error("data logged before any recording was begun"))
+    }
+}
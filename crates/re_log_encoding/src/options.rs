@@ -0,0 +1,205 @@
+//! How `.rrd` files are encoded, as recorded in their header.
+//!
+//! Every `.rrd` file starts with 4 magic bytes followed by the 4-byte version of Rerun that
+//! wrote it:
+//! * `RRF0`: [`Layout::Stream`] and [`Serializer::MsgPack`]. This is what all files looked like
+//!   before [`EncodingOptions`] were introduced. This is format version 0.
+//! * `RRF1`: followed by the format version (u32 LE) and 4 more bytes of [`EncodingOptions`].
+//!   This is what is written today, see [`FORMAT_VERSION`].
+//!
+//! The format version describes how messages are framed, and is bumped whenever that changes.
+//! Since format version 1, every message is prefixed by its length (u64 LE) and a CRC32
+//! checksum (u32 LE) of its serialized bytes.
+//!
+//! Changes to the layout of the messages themselves are tracked by the Rerun version instead,
+//! and old layouts are upgraded as they are decoded.
+
+/// Magic bytes at the start of `.rrd` files of format version 0.
+#[cfg(feature = "decoder")]
+pub(crate) const LEGACY_MAGIC: &[u8; 4] = b"RRF0";
+
+/// Magic bytes at the start of `.rrd` files whose header includes the format version.
+#[cfg(any(
+    feature = "decoder",
+    all(feature = "encoder", not(target_arch = "wasm32"))
+))]
+pub(crate) const VERSIONED_MAGIC: &[u8; 4] = b"RRF1";
+
+/// The format version written by this version of Rerun.
+///
+/// Files of this or any earlier format version can be decoded.
+/// This only covers how messages are framed: whether the messages themselves can be read
+/// depends on the version of Rerun that wrote them, see [`crate::decoder::Decoder`].
+pub const FORMAT_VERSION: u32 = 1;
+
+/// The first format version in which every message carries a checksum.
+#[cfg(feature = "decoder")]
+pub(crate) const FIRST_FORMAT_VERSION_WITH_CHECKSUMS: u32 = 1;
+
+/// How the messages are laid out in an encoded `.rrd` file/stream.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Layout {
+    /// A single compressed stream of messages.
+    ///
+    /// Can be sent over the network as it is being produced, but can only be read from the start.
+    #[default]
+    Stream,
+
+    /// Independently compressed frames of messages, followed by an index footer describing the
+    /// time ranges and entity paths of every frame.
+    ///
+    /// This lets a reader jump straight to the part of the file it is interested in.
+    /// See [`crate::index`] for details.
+    Chunked {
+        /// A frame is compressed and written out as soon as its uncompressed size reaches this.
+        ///
+        /// Only matters when encoding.
+        frame_size_bytes: u64,
+    },
+}
+
+impl Layout {
+    /// The chunked layout, with a sensible frame size.
+    pub const CHUNKED: Self = Self::Chunked {
+        frame_size_bytes: 1024 * 1024,
+    };
+}
+
+/// How each [`re_log_types::LogMsg`] is serialized, before compression.
+///
+/// See [`crate::serializer`] for details.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Serializer {
+    /// Everything is serialized with MsgPack, with Arrow payloads embedded as opaque blobs.
+    #[default]
+    MsgPack,
+
+    /// The payloads of [`re_log_types::ArrowMsg`]s are written as native Arrow IPC streams,
+    /// right next to a small MsgPack header.
+    ///
+    /// All other messages are serialized with MsgPack.
+    ArrowIpc,
+}
+
+/// How the serialized messages are compressed.
+///
+/// With [`Layout::Chunked`], this also applies to the index footer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Compression {
+    /// No compression at all.
+    Off,
+
+    /// Zstandard at the given level, from 1 (fastest) to 19 (smallest), or up to 22 with a lot
+    /// of memory. Negative levels are even faster.
+    ///
+    /// The level only matters when encoding.
+    Zstd { level: i32 },
+
+    /// LZ4, which is a lot cheaper to encode than zstd, at the cost of larger files.
+    Lz4,
+}
+
+impl Compression {
+    pub const DEFAULT: Self = Self::Zstd { level: 3 };
+}
+
+impl Default for Compression {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+impl std::fmt::Display for Compression {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Off => "off".fmt(f),
+            Self::Zstd { level } => write!(f, "zstd:{level}"),
+            Self::Lz4 => "lz4".fmt(f),
+        }
+    }
+}
+
+/// Parses `off`, `lz4`, `zstd`, or `zstd:<level>`, e.g. `zstd:19`.
+impl std::str::FromStr for Compression {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "off" | "none" => Ok(Self::Off),
+            "lz4" => Ok(Self::Lz4),
+            "zstd" => Ok(Self::DEFAULT),
+            _ => {
+                let level = s
+                    .strip_prefix("zstd:")
+                    .ok_or_else(|| format!("Expected off, lz4, zstd or zstd:<level>, got {s:?}"))?;
+                let level = level
+                    .parse()
+                    .map_err(|err| format!("Bad zstd level {level:?}: {err}"))?;
+                Ok(Self::Zstd { level })
+            }
+        }
+    }
+}
+
+/// Everything one needs to know to decode an `.rrd` file, as recorded in its header.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct EncodingOptions {
+    pub layout: Layout,
+    pub serializer: Serializer,
+    pub compression: Compression,
+}
+
+impl EncodingOptions {
+    pub const DEFAULT: Self = Self {
+        layout: Layout::Stream,
+        serializer: Serializer::MsgPack,
+        compression: Compression::DEFAULT,
+    };
+
+    #[cfg(feature = "encoder")]
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) fn to_bytes(self) -> [u8; 4] {
+        let layout = match self.layout {
+            Layout::Stream => 0,
+            Layout::Chunked { .. } => 1,
+        };
+        let serializer = match self.serializer {
+            Serializer::MsgPack => 0,
+            Serializer::ArrowIpc => 1,
+        };
+        // The level is only recorded for reference: decoding doesn't need it.
+        let (compression, level) = match self.compression {
+            Compression::Zstd { level } => (0, level.clamp(i8::MIN.into(), i8::MAX.into()) as i8),
+            Compression::Off => (1, 0),
+            Compression::Lz4 => (2, 0),
+        };
+        [layout, serializer, compression, level as u8]
+    }
+
+    #[cfg(feature = "decoder")]
+    pub(crate) fn from_bytes(bytes: [u8; 4]) -> Option<Self> {
+        let layout = match bytes[0] {
+            0 => Layout::Stream,
+            1 => Layout::CHUNKED,
+            _ => return None,
+        };
+        let serializer = match bytes[1] {
+            0 => Serializer::MsgPack,
+            1 => Serializer::ArrowIpc,
+            _ => return None,
+        };
+        let compression = match bytes[2] {
+            0 => Compression::Zstd {
+                level: (bytes[3] as i8).into(),
+            },
+            1 => Compression::Off,
+            2 => Compression::Lz4,
+            _ => return None,
+        };
+        Some(Self {
+            layout,
+            serializer,
+            compression,
+        })
+    }
+}
@@ -0,0 +1,95 @@
+//! Serialization of individual [`LogMsg`]s, before compression.
+//!
+//! With [`Serializer::MsgPack`], a serialized message is simply the MsgPack encoding of the
+//! [`LogMsg`].
+//!
+//! With [`Serializer::ArrowIpc`], a serialized message starts with a one-byte tag:
+//! ```text
+//! 0 | MsgPack-encoded LogMsg
+//! 1 | header length (u64 LE) | MsgPack-encoded (RecordingId, table_id, timepoint_max) | Arrow IPC stream
+//! ```
+//! Tag `1` is used for [`LogMsg::ArrowMsg`], tag `0` for everything else.
+//! The Arrow IPC stream runs until the end of the serialized message, and can be read by any
+//! Arrow implementation.
+
+use re_log_types::LogMsg;
+
+use crate::Serializer;
+
+const TAG_MSGPACK: u8 = 0;
+const TAG_ARROW_IPC: u8 = 1;
+
+/// Serializes a single [`LogMsg`], appending it to `buffer`.
+#[cfg(feature = "encoder")]
+#[cfg(not(target_arch = "wasm32"))]
+pub fn encode_log_msg(
+    serializer: Serializer,
+    msg: &LogMsg,
+    buffer: &mut Vec<u8>,
+) -> Result<(), crate::encoder::EncodeError> {
+    crate::profile_function!();
+
+    match (serializer, msg) {
+        (Serializer::MsgPack, _) => {
+            rmp_serde::encode::write_named(buffer, msg)?;
+        }
+        (Serializer::ArrowIpc, LogMsg::ArrowMsg(recording_id, arrow_msg)) => {
+            buffer.push(TAG_ARROW_IPC);
+
+            let header_len_pos = buffer.len();
+            buffer.extend_from_slice(&0_u64.to_le_bytes());
+            rmp_serde::encode::write_named(
+                buffer,
+                &(recording_id, arrow_msg.table_id, &arrow_msg.timepoint_max),
+            )?;
+            let header_len = (buffer.len() - header_len_pos - 8) as u64;
+            buffer[header_len_pos..header_len_pos + 8].copy_from_slice(&header_len.to_le_bytes());
+
+            arrow_msg.write_arrow_ipc(buffer)?;
+        }
+        (Serializer::ArrowIpc, _) => {
+            buffer.push(TAG_MSGPACK);
+            rmp_serde::encode::write_named(buffer, msg)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Deserializes a single [`LogMsg`] that was serialized with [`encode_log_msg`].
+#[cfg(feature = "decoder")]
+pub fn decode_log_msg(
+    serializer: Serializer,
+    bytes: &[u8],
+) -> Result<LogMsg, crate::decoder::DecodeError> {
+    use crate::decoder::DecodeError;
+    use re_log_types::{ArrowMsg, MsgId, RecordingId, TimePoint};
+
+    crate::profile_function!();
+
+    match serializer {
+        Serializer::MsgPack => Ok(rmp_serde::from_slice(bytes)?),
+        Serializer::ArrowIpc => match bytes.split_first() {
+            Some((&TAG_MSGPACK, msg)) => Ok(rmp_serde::from_slice(msg)?),
+            Some((&TAG_ARROW_IPC, rest)) => {
+                if rest.len() < 8 {
+                    return Err(DecodeError::Malformed("truncated Arrow message header"));
+                }
+                let (header_len, rest) = rest.split_at(8);
+                let header_len = u64::from_le_bytes(header_len.try_into().unwrap()) as usize;
+                if rest.len() < header_len {
+                    return Err(DecodeError::Malformed("truncated Arrow message header"));
+                }
+                let (header, mut ipc) = rest.split_at(header_len);
+
+                let (recording_id, table_id, timepoint_max): (RecordingId, MsgId, TimePoint) =
+                    rmp_serde::from_slice(header)?;
+                let arrow_msg = ArrowMsg::read_arrow_ipc(table_id, timepoint_max, &mut ipc)?;
+
+                Ok(LogMsg::ArrowMsg(recording_id, arrow_msg))
+            }
+            Some(_) => Err(DecodeError::Malformed("unknown message tag")),
+            None => Err(DecodeError::Malformed("empty message")),
+        },
+    }
+}
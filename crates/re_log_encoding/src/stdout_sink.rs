@@ -0,0 +1,61 @@
+use re_log_types::LogMsg;
+
+use crate::{
+    encoder::Encoder,
+    writer_sink::{MessageWriter, WriterSink},
+    SinkError,
+};
+
+/// Stream log messages as an `.rrd` stream to standard output, e.g. to pipe them into `rerun -`.
+///
+/// Data is flushed whenever the sink has caught up with its input, so that whoever is reading
+/// the other end of the pipe sees it without delay.
+pub struct StdoutSink {
+    sink: WriterSink,
+}
+
+impl StdoutSink {
+    /// Start writing log messages to standard output.
+    pub fn new() -> Result<Self, SinkError> {
+        re_log::debug!("Writing log stream to stdout…");
+
+        let encoder = Encoder::new(std::io::stdout())?;
+        let sink = WriterSink::spawn("stdout_writer", "stdout".to_owned(), encoder)?;
+
+        Ok(Self { sink })
+    }
+
+    /// Queues a message for writing.
+    ///
+    /// Failures to write are reported by [`Self::finish`].
+    pub fn send(&self, log_msg: LogMsg) {
+        self.sink.send(log_msg);
+    }
+
+    /// Has the sink stopped writing, because of an error?
+    ///
+    /// If so, [`Self::finish`] tells what went wrong.
+    pub fn has_failed(&self) -> bool {
+        self.sink.has_failed()
+    }
+
+    /// Waits for everything sent so far to be written, and reports the first error if any.
+    pub fn finish(self) -> Result<(), SinkError> {
+        self.sink.finish()
+    }
+}
+
+impl MessageWriter for Encoder<std::io::Stdout> {
+    fn append(&mut self, log_msg: &LogMsg) -> Result<(), SinkError> {
+        Ok(Encoder::append(self, log_msg)?)
+    }
+
+    /// Only flush once we've caught up, as flushing after every message would hurt compression.
+    fn caught_up(&mut self) -> Result<(), SinkError> {
+        Ok(self.flush()?)
+    }
+
+    fn finish(mut self) -> Result<(), SinkError> {
+        Ok(Encoder::finish(&mut self)?)
+    }
+}
@@ -0,0 +1,125 @@
+use std::sync::mpsc::{Receiver, Sender, TryRecvError};
+
+use parking_lot::Mutex;
+
+use re_log_types::LogMsg;
+
+/// Errors that can occur when creating a [`crate::FileSink`] or [`crate::StdoutSink`], or
+/// writing to one.
+#[derive(thiserror::Error, Debug)]
+pub enum SinkError {
+    /// Error creating the file.
+    #[error("Failed to create file {0}: {1}")]
+    CreateFile(std::path::PathBuf, std::io::Error),
+
+    /// Error spawning the writer thread.
+    #[error("Failed to spawn thread: {0}")]
+    SpawnThread(std::io::Error),
+
+    /// Error encoding a log message.
+    #[error("Failed to encode LogMsg: {0}")]
+    LogMsgEncode(#[from] crate::encoder::EncodeError),
+
+    /// Error collecting the timeless data to repeat at the start of each file.
+    #[error("Failed to read LogMsg: {0}")]
+    LogMsgDecode(#[from] re_log_types::DataTableError),
+}
+
+/// Where a [`WriterSink`] writes its messages to.
+pub(crate) trait MessageWriter: Send + 'static {
+    fn append(&mut self, log_msg: &LogMsg) -> Result<(), SinkError>;
+
+    /// Called whenever the sink has caught up with its input.
+    fn caught_up(&mut self) -> Result<(), SinkError> {
+        Ok(())
+    }
+
+    fn finish(self) -> Result<(), SinkError>;
+}
+
+/// Writes log messages on a thread of its own, so that sending them never blocks.
+pub(crate) struct WriterSink {
+    // None = quit
+    tx: Mutex<Sender<Option<LogMsg>>>,
+    join_handle: Option<std::thread::JoinHandle<Result<(), SinkError>>>,
+}
+
+impl Drop for WriterSink {
+    fn drop(&mut self) {
+        self.tx.lock().send(None).ok();
+        if let Some(join_handle) = self.join_handle.take() {
+            // Any error has already been logged by the writer thread.
+            join_handle.join().ok();
+        }
+    }
+}
+
+impl WriterSink {
+    /// `target` names where the messages end up, for the logs.
+    pub fn spawn(
+        thread_name: &str,
+        target: String,
+        writer: impl MessageWriter,
+    ) -> Result<Self, SinkError> {
+        let (tx, rx) = std::sync::mpsc::channel();
+
+        let join_handle = std::thread::Builder::new()
+            .name(thread_name.to_owned())
+            .spawn(move || {
+                let result = write_all(writer, &rx);
+                match &result {
+                    Ok(()) => re_log::debug!("Log stream written to {target}"),
+                    Err(err) => re_log::error!("Failed to write log stream to {target}: {err}"),
+                }
+                result
+            })
+            .map_err(SinkError::SpawnThread)?;
+
+        Ok(Self {
+            tx: tx.into(),
+            join_handle: Some(join_handle),
+        })
+    }
+
+    pub fn send(&self, log_msg: LogMsg) {
+        self.tx.lock().send(Some(log_msg)).ok();
+    }
+
+    pub fn has_failed(&self) -> bool {
+        self.join_handle
+            .as_ref()
+            .map_or(true, |join_handle| join_handle.is_finished())
+    }
+
+    pub fn finish(mut self) -> Result<(), SinkError> {
+        self.tx.lock().send(None).ok();
+        match self.join_handle.take() {
+            Some(join_handle) => join_handle
+                .join()
+                .unwrap_or_else(|panic| std::panic::resume_unwind(panic)),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Writes messages until the sender asks us to quit, or goes away.
+fn write_all(
+    mut writer: impl MessageWriter,
+    rx: &Receiver<Option<LogMsg>>,
+) -> Result<(), SinkError> {
+    'outer: while let Ok(Some(log_msg)) = rx.recv() {
+        writer.append(&log_msg)?;
+
+        loop {
+            match rx.try_recv() {
+                Ok(Some(log_msg)) => writer.append(&log_msg)?,
+                Ok(None) | Err(TryRecvError::Disconnected) => break 'outer,
+                Err(TryRecvError::Empty) => break,
+            }
+        }
+
+        writer.caught_up()?;
+    }
+
+    writer.finish()
+}
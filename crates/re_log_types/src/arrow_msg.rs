@@ -29,25 +29,64 @@ pub struct ArrowMsg {
     pub chunk: Chunk<Box<dyn Array>>,
 }
 
+impl ArrowMsg {
+    /// Writes the [`Schema`] and [`Chunk`] as a native Arrow IPC stream: a schema message, a
+    /// single record batch, and an end-of-stream marker.
+    ///
+    /// Any Arrow implementation can read this back, e.g. `pyarrow.ipc.open_stream`.
+    pub fn write_arrow_ipc(&self, write: &mut impl std::io::Write) -> arrow2::error::Result<()> {
+        use arrow2::io::ipc::write::StreamWriter;
+
+        let mut writer = StreamWriter::new(write, Default::default());
+        writer.start(&self.schema, None)?;
+        writer.write(&self.chunk, None)?;
+        writer.finish()
+    }
+
+    /// Reads back an Arrow IPC stream written with [`Self::write_arrow_ipc`].
+    pub fn read_arrow_ipc(
+        table_id: MsgId,
+        timepoint_max: TimePoint,
+        read: &mut impl std::io::Read,
+    ) -> arrow2::error::Result<Self> {
+        use arrow2::{
+            error::Error,
+            io::ipc::read::{read_stream_metadata, StreamReader, StreamState},
+        };
+
+        let metadata = read_stream_metadata(read)?;
+        let mut stream = StreamReader::new(read, metadata, None);
+        let chunk = stream
+            .next()
+            .map(|state| match state {
+                Ok(StreamState::Some(chunk)) => Ok(chunk),
+                // We can only be waiting for more data if the stream was cut short.
+                Ok(StreamState::Waiting) => {
+                    Err(Error::OutOfSpec("Truncated Arrow IPC stream".to_owned()))
+                }
+                Err(err) => Err(err),
+            })
+            .ok_or_else(|| Error::OutOfSpec("No Chunk found in stream".to_owned()))??;
+
+        Ok(Self {
+            table_id,
+            timepoint_max,
+            schema: stream.metadata().schema.clone(),
+            chunk,
+        })
+    }
+}
+
 #[cfg(feature = "serde")]
 impl serde::Serialize for ArrowMsg {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: serde::Serializer,
     {
-        use arrow2::io::ipc::write::StreamWriter;
         use serde::ser::SerializeTuple;
 
         let mut buf = Vec::<u8>::new();
-        let mut writer = StreamWriter::new(&mut buf, Default::default());
-        writer
-            .start(&self.schema, None)
-            .map_err(|e| serde::ser::Error::custom(e.to_string()))?;
-        writer
-            .write(&self.chunk, None)
-            .map_err(|e| serde::ser::Error::custom(e.to_string()))?;
-        writer
-            .finish()
+        self.write_arrow_ipc(&mut buf)
             .map_err(|e| serde::ser::Error::custom(e.to_string()))?;
 
         let mut inner = serializer.serialize_tuple(3)?;
@@ -64,8 +103,6 @@ impl<'de> serde::Deserialize<'de> for ArrowMsg {
     where
         D: serde::Deserializer<'de>,
     {
-        use arrow2::io::ipc::read::{read_stream_metadata, StreamReader, StreamState};
-
         struct FieldVisitor;
 
         impl<'de> serde::de::Visitor<'de> for FieldVisitor {
@@ -86,32 +123,9 @@ impl<'de> serde::Deserialize<'de> for ArrowMsg {
                 if let (Some(table_id), Some(timepoint_min), Some(buf)) =
                     (table_id, timepoint_min, buf)
                 {
-                    let mut cursor = std::io::Cursor::new(buf);
-                    let metadata = match read_stream_metadata(&mut cursor) {
-                        Ok(metadata) => metadata,
-                        Err(err) => {
-                            return Err(serde::de::Error::custom(format!(
-                                "Failed to read stream metadata: {err}"
-                            )))
-                        }
-                    };
-                    let mut stream = StreamReader::new(cursor, metadata, None);
-                    let chunk = stream
-                        .find_map(|state| match state {
-                            Ok(StreamState::Some(chunk)) => Some(chunk),
-                            Ok(StreamState::Waiting) => {
-                                unreachable!("cannot be waiting on a fixed buffer")
-                            }
-                            _ => None,
-                        })
-                        .ok_or_else(|| serde::de::Error::custom("No Chunk found in stream"))?;
-
-                    Ok(ArrowMsg {
-                        table_id,
-                        timepoint_max: timepoint_min,
-                        schema: stream.metadata().schema.clone(),
-                        chunk,
-                    })
+                    ArrowMsg::read_arrow_ipc(table_id, timepoint_min, &mut buf.as_slice()).map_err(
+                        |err| serde::de::Error::custom(format!("Failed to read Arrow IPC: {err}")),
+                    )
                 } else {
                     Err(serde::de::Error::custom(
                         "Expected (table_id, timepoint, buf)",
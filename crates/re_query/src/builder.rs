@@ -0,0 +1,471 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use itertools::Itertools as _;
+use re_arrow_store::{DataStore, EntityPathPrefix, LatestAtQuery, RangeQuery, RowIndex, TimeInt};
+use re_log_types::{ComponentName, EntityPath, EntityPathPart};
+
+use crate::{get_component_with_instances, ComponentWithInstances};
+
+// --- Entities ---
+
+/// Which entities a [`QueryBuilder`] should look at.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum EntitySelector {
+    /// This exact entity.
+    Entity(EntityPath),
+
+    /// An entity along with all of its descendants.
+    Subtree(EntityPathPrefix),
+
+    /// All the entities that match a glob.
+    Glob(EntityPathGlob),
+}
+
+impl EntitySelector {
+    /// Parses either an exact entity path (`foo/bar`), a subtree (`foo/bar/**`, or `**` for all
+    /// entities), or any other glob (`foo/*/bar`, `**/bar`…), see [`EntityPathGlob`].
+    ///
+    /// Panics if the path itself is malformed, just like `EntityPath::from`.
+    pub fn parse(path: &str) -> Self {
+        let path = path.trim();
+        let segments = path
+            .split('/')
+            .filter(|segment| !segment.is_empty())
+            .collect_vec();
+        match segments.iter().position(|segment| segment.contains('*')) {
+            None => Self::Entity(EntityPath::from(path)),
+            Some(pos) if pos + 1 == segments.len() && segments[pos] == "**" => {
+                Self::Subtree(EntityPathPrefix::parse(path))
+            }
+            Some(_) => Self::Glob(EntityPathGlob::parse(path)),
+        }
+    }
+}
+
+impl From<&str> for EntitySelector {
+    #[inline]
+    fn from(path: &str) -> Self {
+        Self::parse(path)
+    }
+}
+
+impl From<EntityPath> for EntitySelector {
+    #[inline]
+    fn from(ent_path: EntityPath) -> Self {
+        Self::Entity(ent_path)
+    }
+}
+
+impl From<EntityPathPrefix> for EntitySelector {
+    #[inline]
+    fn from(prefix: EntityPathPrefix) -> Self {
+        Self::Subtree(prefix)
+    }
+}
+
+impl From<EntityPathGlob> for EntitySelector {
+    #[inline]
+    fn from(glob: EntityPathGlob) -> Self {
+        Self::Glob(glob)
+    }
+}
+
+/// A glob over entity paths, where wildcards stand for whole path segments: `*` matches exactly
+/// one segment, and `**` any number of them (none included).
+///
+/// E.g. `world/*/points` matches `world/camera/points` but neither `world/points` nor
+/// `world/camera/left/points`, all of which are matched by `world/**/points`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EntityPathGlob {
+    segments: Vec<GlobSegment>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum GlobSegment {
+    Part(EntityPathPart),
+
+    /// `*`
+    AnyPart,
+
+    /// `**`
+    AnyParts,
+}
+
+impl EntityPathGlob {
+    /// Parses a glob, e.g. `foo/*/bar/**`.
+    ///
+    /// Panics if a wildcard only makes up part of a segment (e.g. `foo/ba*`), or if the path
+    /// itself is malformed, just like `EntityPath::from`.
+    pub fn parse(glob: &str) -> Self {
+        let mut segments = Vec::new();
+        for segment in glob.trim().split('/').filter(|segment| !segment.is_empty()) {
+            match segment {
+                "*" => segments.push(GlobSegment::AnyPart),
+                "**" => segments.push(GlobSegment::AnyParts),
+                _ => {
+                    assert!(
+                        !segment.contains('*'),
+                        "Unsupported entity path glob {glob:?}: wildcards must make up whole \
+                            path segments"
+                    );
+                    segments.extend(
+                        EntityPath::from(segment)
+                            .iter()
+                            .cloned()
+                            .map(GlobSegment::Part),
+                    );
+                }
+            }
+        }
+        Self { segments }
+    }
+
+    /// Does `ent_path` match this glob?
+    pub fn matches(&self, ent_path: &EntityPath) -> bool {
+        fn matches(segments: &[GlobSegment], parts: &[EntityPathPart]) -> bool {
+            match segments.split_first() {
+                None => parts.is_empty(),
+                Some((GlobSegment::AnyParts, rest)) => {
+                    (0..=parts.len()).any(|skipped| matches(rest, &parts[skipped..]))
+                }
+                Some((GlobSegment::AnyPart, rest)) => {
+                    !parts.is_empty() && matches(rest, &parts[1..])
+                }
+                Some((GlobSegment::Part(part), rest)) => {
+                    parts.first() == Some(part) && matches(rest, &parts[1..])
+                }
+            }
+        }
+
+        matches(&self.segments, ent_path.as_slice())
+    }
+
+    /// The entity that all the matching entities are part of the subtree of, i.e. everything
+    /// up until the first wildcard.
+    pub fn root(&self) -> EntityPath {
+        EntityPath::new(
+            self.segments
+                .iter()
+                .map_while(|segment| match segment {
+                    GlobSegment::Part(part) => Some(part.clone()),
+                    GlobSegment::AnyPart | GlobSegment::AnyParts => None,
+                })
+                .collect(),
+        )
+    }
+}
+
+// --- Builder ---
+
+/// The semantics of a [`QueryBuilder`].
+#[derive(Clone, Debug)]
+pub enum QueryKind {
+    /// The latest state of every entity at a given time, see [`DataStore::latest_at`].
+    LatestAt(LatestAtQuery),
+
+    /// Every state of every entity within a given time range, see [`DataStore::range`].
+    Range(RangeQuery),
+}
+
+/// Builds queries whose entities and components are only known at runtime.
+///
+/// Components are either required or optional: results are only yielded for the entities (and,
+/// for range queries, the points in time) for which all the required components are known.
+/// Without any required components, results are yielded as soon as any one component is known.
+///
+/// ```
+/// # use re_arrow_store::LatestAtQuery;
+/// # use re_log_types::{Timeline, component_types::{ColorRGBA, Point2D}, Component};
+/// # let store = re_query::__populate_example_store();
+///
+/// let query = LatestAtQuery::new(Timeline::new_sequence("frame_nr"), 123.into());
+///
+/// let results = re_query::QueryBuilder::latest_at(query)
+///     .entities("**")
+///     .required(Point2D::name())
+///     .optional(ColorRGBA::name())
+///     .execute(&store)
+///     .collect::<Vec<_>>();
+///
+/// assert_eq!(1, results.len());
+/// assert_eq!(2, results[0].get(Point2D::name()).unwrap().len());
+/// ```
+#[derive(Clone, Debug)]
+pub struct QueryBuilder {
+    kind: QueryKind,
+    entities: Vec<EntitySelector>,
+    required: Vec<ComponentName>,
+    optional: Vec<ComponentName>,
+}
+
+impl QueryBuilder {
+    /// Starts building a query of the given `kind`.
+    #[inline]
+    pub fn new(kind: QueryKind) -> Self {
+        Self {
+            kind,
+            entities: Vec::new(),
+            required: Vec::new(),
+            optional: Vec::new(),
+        }
+    }
+
+    /// Starts building a latest-at query.
+    #[inline]
+    pub fn latest_at(query: LatestAtQuery) -> Self {
+        Self::new(QueryKind::LatestAt(query))
+    }
+
+    /// Starts building a range query.
+    ///
+    /// If `query` has a value filter (see [`RangeQuery::with_filter`]), only the rows that match
+    /// it are taken into account, and no latest-at state is yielded for the start of the time
+    /// range. Just like for [`DataStore::range`], the filtered component must have a value index,
+    /// but it doesn't have to be part of the queried components.
+    #[inline]
+    pub fn range(query: RangeQuery) -> Self {
+        Self::new(QueryKind::Range(query))
+    }
+
+    /// Also look at the entities selected by `selector`, e.g. `foo/bar`, `foo/**` or
+    /// `foo/*/bar`.
+    #[inline]
+    pub fn entities(mut self, selector: impl Into<EntitySelector>) -> Self {
+        self.entities.push(selector.into());
+        self
+    }
+
+    /// Only yield results for which `component` is known.
+    #[inline]
+    pub fn required(mut self, component: ComponentName) -> Self {
+        self.required.push(component);
+        self
+    }
+
+    /// Yield `component` along with the results, if it's known.
+    #[inline]
+    pub fn optional(mut self, component: ComponentName) -> Self {
+        self.optional.push(component);
+        self
+    }
+
+    /// Runs the query against `store`.
+    ///
+    /// Results are yielded one entity at a time, in entity path order.
+    /// For range queries, the results of each entity follow the same streaming-join semantics as
+    /// [`crate::range_entity_with_primary`], all components acting as primaries: a result is
+    /// yielded for every row in which any of the components changed, with the latest known state
+    /// of all the others.
+    pub fn execute<'a>(&self, store: &'a DataStore) -> impl Iterator<Item = DynEntityView> + 'a {
+        crate::profile_function!();
+
+        let required = self.required.clone();
+        let components = self
+            .required
+            .iter()
+            .chain(self.optional.iter())
+            .copied()
+            .unique()
+            .collect_vec();
+
+        let ent_paths = self.ent_paths(store);
+
+        match self.kind.clone() {
+            QueryKind::LatestAt(query) => {
+                itertools::Either::Left(ent_paths.into_iter().filter_map(move |ent_path| {
+                    let state = latest_at_components(store, &query, &ent_path, &components);
+                    DynEntityView::from_state(&ent_path, None, &state, &required)
+                }))
+            }
+            QueryKind::Range(query) => {
+                itertools::Either::Right(ent_paths.into_iter().flat_map(move |ent_path| {
+                    range_components(store, &query, ent_path, required.clone(), &components)
+                }))
+            }
+        }
+    }
+
+    /// All the entities selected by this query, in entity path order.
+    fn ent_paths(&self, store: &DataStore) -> Vec<EntityPath> {
+        let timeline = match &self.kind {
+            QueryKind::LatestAt(query) => query.timeline,
+            QueryKind::Range(query) => query.timeline,
+        };
+
+        let mut ent_paths = self
+            .entities
+            .iter()
+            .flat_map(|selector| match selector {
+                EntitySelector::Entity(ent_path) => vec![ent_path.clone()],
+                EntitySelector::Subtree(prefix) => store.entities_in_subtree(timeline, prefix),
+                EntitySelector::Glob(glob) => store
+                    .entities_in_subtree(timeline, &EntityPathPrefix::new(glob.root()))
+                    .into_iter()
+                    .filter(|ent_path| glob.matches(ent_path))
+                    .collect(),
+            })
+            .collect_vec();
+        ent_paths.sort();
+        ent_paths.dedup();
+
+        ent_paths
+    }
+}
+
+// --- Results ---
+
+/// The components of a single entity at a single point in time, as returned by a
+/// [`QueryBuilder`].
+///
+/// Every component comes with its own instance keys: it is up to the caller to join them.
+#[derive(Clone, Debug)]
+pub struct DynEntityView {
+    pub(crate) ent_path: EntityPath,
+    pub(crate) time: Option<TimeInt>,
+    pub(crate) components: BTreeMap<ComponentName, ComponentWithInstances>,
+}
+
+impl DynEntityView {
+    /// Only yields a view if all the `required` components are present in `state`.
+    fn from_state(
+        ent_path: &EntityPath,
+        time: Option<TimeInt>,
+        state: &BTreeMap<ComponentName, ComponentWithInstances>,
+        required: &[ComponentName],
+    ) -> Option<Self> {
+        let is_complete = !state.is_empty()
+            && required
+                .iter()
+                .all(|component| state.contains_key(component));
+        is_complete.then(|| Self {
+            ent_path: ent_path.clone(),
+            time,
+            components: state.clone(), // shallow
+        })
+    }
+
+    #[inline]
+    pub fn ent_path(&self) -> &EntityPath {
+        &self.ent_path
+    }
+
+    /// The time of the row this view was yielded for.
+    ///
+    /// Always `None` for latest-at queries, as well as for timeless data.
+    #[inline]
+    pub fn time(&self) -> Option<TimeInt> {
+        self.time
+    }
+
+    /// The data of `component`, if it's known.
+    #[inline]
+    pub fn get(&self, component: ComponentName) -> Option<&ComponentWithInstances> {
+        self.components.get(&component)
+    }
+
+    /// Iterates over all the known components, in name order.
+    #[inline]
+    pub fn iter_components(&self) -> impl ExactSizeIterator<Item = &ComponentWithInstances> {
+        self.components.values()
+    }
+}
+
+// --- Internals ---
+
+fn latest_at_components(
+    store: &DataStore,
+    query: &LatestAtQuery,
+    ent_path: &EntityPath,
+    components: &[ComponentName],
+) -> BTreeMap<ComponentName, ComponentWithInstances> {
+    components
+        .iter()
+        .filter_map(|component| {
+            let cwi = get_component_with_instances(store, query, ent_path, *component).ok()?;
+            Some((*component, cwi))
+        })
+        .collect()
+}
+
+fn range_components<'a>(
+    store: &'a DataStore,
+    query: &RangeQuery,
+    ent_path: EntityPath,
+    required: Vec<ComponentName>,
+    components: &[ComponentName],
+) -> impl Iterator<Item = DynEntityView> + 'a {
+    let cluster_key = store.cluster_key();
+
+    // NOTE: This will return none for `TimeInt::Min`, i.e. range queries that start infinitely far
+    // into the past don't have a latest-at state!
+    let latest_time = query.range.min.as_i64().checked_sub(1).map(Into::into);
+
+    // `None` if there's no filter, see `QueryBuilder::range`.
+    let matching_rows = query.filter.as_ref().map(|filter| {
+        store
+            .range(query, &ent_path, [filter.component])
+            .map(|(time, idx_row_nr, _)| (time, idx_row_nr))
+            .collect::<BTreeSet<_>>()
+    });
+
+    let mut state = BTreeMap::default();
+    let mut latest = None;
+    if let (Some(latest_time), None) = (latest_time, &matching_rows) {
+        let query = LatestAtQuery::new(query.timeline, latest_time);
+        state = latest_at_components(store, &query, &ent_path, components);
+        latest = DynEntityView::from_state(&ent_path, Some(latest_time), &state, &required);
+    }
+
+    // The store can only query a fixed number of components at once: query them one by one and
+    // merge the results back, in store order.
+    // The filter has already been applied above: the other components must not be filtered on.
+    let unfiltered = RangeQuery::new(query.timeline, query.range);
+    let mut rows = components
+        .iter()
+        .map(|&component| {
+            store
+                .range(&unfiltered, &ent_path, [component, cluster_key])
+                .filter_map(move |(time, idx_row_nr, row_indices)| {
+                    // Every row holds the cluster key, but not necessarily `component`.
+                    row_indices[0]?;
+                    Some((time, idx_row_nr, component, row_indices))
+                })
+        })
+        .kmerge_by(|(time1, row_nr1, ..), (time2, row_nr2, ..)| (time1, row_nr1) < (time2, row_nr2))
+        .filter(move |(time, idx_row_nr, ..)| {
+            matching_rows
+                .as_ref()
+                .map_or(true, |rows| rows.contains(&(*time, *idx_row_nr)))
+        })
+        .peekable();
+
+    let rest = std::iter::from_fn(move || loop {
+        let (time, idx_row_nr, component, row_indices) = rows.next()?;
+
+        let mut update = |component: ComponentName, row_indices: [Option<RowIndex>; 2]| {
+            let [values, instance_keys] = store.get(&[component, cluster_key], &row_indices);
+            if let Some(values) = values {
+                let cwi = ComponentWithInstances {
+                    name: component,
+                    instance_keys,
+                    values,
+                };
+                state.insert(component, cwi);
+            }
+        };
+
+        update(component, row_indices);
+        // The other components that are part of the very same row, if any.
+        while let Some((_, _, component, row_indices)) =
+            rows.next_if(|(t, nr, ..)| (*t, *nr) == (time, idx_row_nr))
+        {
+            update(component, row_indices);
+        }
+
+        if let Some(view) = DynEntityView::from_state(&ent_path, time, &state, &required) {
+            return Some(view);
+        }
+    });
+
+    latest.into_iter().chain(rest)
+}
@@ -0,0 +1,483 @@
+use std::{
+    any::Any,
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+};
+
+use itertools::Either;
+use parking_lot::Mutex;
+use re_arrow_store::{
+    DataStore, LatestAtQuery, StoreEvent, StoreEventKind, StoreSubscriber, StoreSubscriberHandle,
+    TimeInt, TimeRange, Timeline,
+};
+use re_log_types::{
+    component_types::InstanceKey, Component, ComponentName, DeserializableComponent, EntityPath,
+    SerializableComponent,
+};
+
+use crate::{query_entity_with_primary, EntityView, QueryError};
+
+// --- Cache ---
+
+/// Caches the results of latest-at queries, along with their deserialized data, across frames.
+///
+/// The cache is kept up to date by the store itself, which reports every insertion and garbage
+/// collection: see [`Self::register`].
+/// Only the results that could have been affected by a change get invalidated: scenes made of
+/// static entities never have to be queried twice.
+///
+/// Every query shape (entity, primary, components & timeline) only ever keeps a single result
+/// around, along with the range of query times it is valid for: moving the time cursor around
+/// doesn't trigger a new query until the data actually changes.
+///
+/// Cloning a `QueryCache` is cheap: all clones refer to the same underlying cache.
+#[derive(Clone, Default)]
+pub struct QueryCache {
+    inner: Arc<Mutex<QueryCacheInner>>,
+    stats: Arc<QueryCacheStatsInner>,
+}
+
+#[derive(Default)]
+struct QueryCacheInner {
+    /// `None` until [`QueryCache::register`] has been called.
+    registration: Option<Registration>,
+
+    entries: HashMap<EntityPath, HashMap<CacheKey, CacheEntry>>,
+}
+
+#[derive(Default)]
+struct QueryCacheStatsInner {
+    hits: AtomicU64,
+    misses: AtomicU64,
+    invalidations: AtomicU64,
+}
+
+/// Statistics about a [`QueryCache`], see [`QueryCache::stats`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct QueryCacheStats {
+    pub num_entries: u64,
+    pub hits: u64,
+    pub misses: u64,
+    pub invalidations: u64,
+}
+
+/// Identifies the [`QueryCacheInvalidator`] that is currently in charge of a [`QueryCache`].
+///
+/// Invalidators registered with stores that the cache has since moved on from are ignored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Registration {
+    handle: StoreSubscriberHandle,
+    id: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct CacheKey {
+    timeline: Timeline,
+    primary: ComponentName,
+    components: Vec<ComponentName>,
+}
+
+struct CacheEntry {
+    /// The query times that yield this very result, see [`DataStore::latest_at_validity`].
+    validity: TimeRange,
+
+    /// `None` if the primary component couldn't be found.
+    view: Option<Arc<dyn Any + Send + Sync>>,
+}
+
+impl QueryCache {
+    /// Starts keeping this cache up to date with the changes made to `store`.
+    ///
+    /// Does nothing if the cache is already registered with this very store. Otherwise, the cache
+    /// is cleared first: it can only ever be used with the last store it was registered with.
+    pub fn register(&self, store: &mut DataStore) {
+        if self.is_registered(store) {
+            return;
+        }
+
+        static REGISTRATION_ID: AtomicU64 = AtomicU64::new(0);
+        let id = REGISTRATION_ID.fetch_add(1, Ordering::Relaxed);
+
+        let handle = store.register_subscriber(Box::new(QueryCacheInvalidator {
+            inner: self.inner.clone(),
+            stats: self.stats.clone(),
+            id,
+        }));
+
+        let mut inner = self.inner.lock();
+        inner.entries.clear();
+        inner.registration = Some(Registration { handle, id });
+    }
+
+    /// Is this cache kept up to date with the changes made to `store`? See [`Self::register`].
+    pub fn is_registered(&self, store: &DataStore) -> bool {
+        let Some(Registration { handle, id }) = self.inner.lock().registration else { return false };
+        store
+            .with_subscriber(handle, |invalidator: &QueryCacheInvalidator| {
+                invalidator.id == id && Arc::ptr_eq(&invalidator.inner, &self.inner)
+            })
+            .unwrap_or(false)
+    }
+
+    /// Same as [`crate::query_entity_with_primary`], going through the cache.
+    ///
+    /// The cache must have been registered with `store` beforehand, see [`Self::register`].
+    pub fn query_entity_with_primary<Primary>(
+        &self,
+        store: &DataStore,
+        query: &LatestAtQuery,
+        ent_path: &EntityPath,
+        components: &[ComponentName],
+    ) -> crate::Result<Arc<CachedEntityView<Primary>>>
+    where
+        Primary: SerializableComponent + DeserializableComponent + Send + Sync + 'static,
+        for<'a> &'a Primary::ArrayType: IntoIterator,
+    {
+        crate::profile_function!();
+
+        debug_assert!(
+            self.is_registered(store),
+            "the query cache must be registered with the store it is used with"
+        );
+
+        let key = CacheKey {
+            timeline: query.timeline,
+            primary: Primary::name(),
+            components: components.to_vec(),
+        };
+
+        let cached = self
+            .inner
+            .lock()
+            .entries
+            .get(ent_path)
+            .and_then(|entries| entries.get(&key))
+            .filter(|entry| entry.validity.contains(query.at))
+            .map(|entry| entry.view.clone());
+
+        if let Some(view) = cached {
+            self.stats.hits.fetch_add(1, Ordering::Relaxed);
+            return view
+                .and_then(|view| view.downcast::<CachedEntityView<Primary>>().ok())
+                .ok_or(QueryError::PrimaryNotFound);
+        }
+
+        self.stats.misses.fetch_add(1, Ordering::Relaxed);
+
+        let validity = {
+            // The cluster key is part of every single row: it doesn't tell us anything.
+            let relevant = std::iter::once(Primary::name())
+                .chain(components.iter().copied())
+                .filter(|component| *component != InstanceKey::name())
+                .collect::<Vec<_>>();
+            store.latest_at_validity(query, ent_path, &relevant)
+        };
+
+        let view = match query_entity_with_primary::<Primary>(store, query, ent_path, components) {
+            Ok(view) => Some(Arc::new(CachedEntityView::new(view))),
+            // Entities that don't have the primary component at all are by far the most common
+            // case: make sure these get cached too.
+            Err(QueryError::PrimaryNotFound) => None,
+            Err(err) => return Err(err),
+        };
+
+        self.inner
+            .lock()
+            .entries
+            .entry(ent_path.clone())
+            .or_default()
+            .insert(
+                key,
+                CacheEntry {
+                    validity,
+                    view: view.clone().map(|view| view as Arc<dyn Any + Send + Sync>),
+                },
+            );
+
+        view.ok_or(QueryError::PrimaryNotFound)
+    }
+
+    /// Drops all cached results.
+    pub fn clear(&self) {
+        self.inner.lock().entries.clear();
+    }
+
+    pub fn stats(&self) -> QueryCacheStats {
+        QueryCacheStats {
+            num_entries: self
+                .inner
+                .lock()
+                .entries
+                .values()
+                .map(|entries| entries.len() as u64)
+                .sum(),
+            hits: self.stats.hits.load(Ordering::Relaxed),
+            misses: self.stats.misses.load(Ordering::Relaxed),
+            invalidations: self.stats.invalidations.load(Ordering::Relaxed),
+        }
+    }
+}
+
+// --- Invalidation ---
+
+/// The [`StoreSubscriber`] that keeps a [`QueryCache`] up to date.
+struct QueryCacheInvalidator {
+    inner: Arc<Mutex<QueryCacheInner>>,
+    stats: Arc<QueryCacheStatsInner>,
+
+    /// See [`Registration`].
+    id: u64,
+}
+
+impl StoreSubscriber for QueryCacheInvalidator {
+    fn on_events(&mut self, events: &[StoreEvent]) {
+        crate::profile_function!();
+
+        let mut inner = self.inner.lock();
+        if inner.registration.map(|registration| registration.id) != Some(self.id) {
+            return; // the cache has moved on to another store
+        }
+
+        let mut num_invalidated = 0;
+
+        for event in events {
+            let Some(entries) = inner.entries.get_mut(&event.entity_path) else { continue };
+
+            entries.retain(|key, entry| {
+                // The cluster key is part of every single row: it doesn't tell us anything.
+                let is_relevant = event
+                    .components
+                    .iter()
+                    .filter(|component| **component != InstanceKey::name())
+                    .any(|component| {
+                        *component == key.primary || key.components.contains(component)
+                    });
+                if !is_relevant {
+                    return true;
+                }
+
+                let is_affected = if event.timepoint.is_timeless() {
+                    // Timeless data affects the results at all query times.
+                    true
+                } else if let Some(time) = event.timepoint.get(&key.timeline).copied() {
+                    match event.kind {
+                        // A change that happened after the validity range cannot affect it.
+                        _ if time > entry.validity.max => false,
+                        // A new row within the validity range only affects the query times that
+                        // come after it: the result still holds for those that come before.
+                        StoreEventKind::Addition if time > entry.validity.min => {
+                            entry.validity.max = time - TimeInt::from(1);
+                            false
+                        }
+                        StoreEventKind::Addition | StoreEventKind::Deletion => true,
+                    }
+                } else {
+                    false
+                };
+
+                num_invalidated += is_affected as u64;
+                !is_affected
+            });
+
+            if entries.is_empty() {
+                inner.entries.remove(&event.entity_path);
+            }
+        }
+
+        self.stats
+            .invalidations
+            .fetch_add(num_invalidated, Ordering::Relaxed);
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+// --- Cached entity views ---
+
+/// An [`EntityView`] returned by a [`QueryCache`].
+///
+/// On top of the raw data, it also keeps around the deserialized values of every component that
+/// was ever iterated on, so that they don't have to be deserialized again on the next frame.
+///
+/// Derefs to the underlying [`EntityView`], whose iteration methods it shadows.
+pub struct CachedEntityView<Primary: SerializableComponent + DeserializableComponent>
+where
+    for<'a> &'a Primary::ArrayType: IntoIterator,
+{
+    view: EntityView<Primary>,
+
+    instance_keys: Mutex<Option<Arc<Vec<InstanceKey>>>>,
+
+    /// `Arc<Vec<Option<C>>>` for every component `C`, primary included, joined on the instance
+    /// keys of the primary.
+    components: Mutex<HashMap<ComponentName, Arc<dyn Any + Send + Sync>>>,
+}
+
+impl<Primary> CachedEntityView<Primary>
+where
+    Primary: SerializableComponent + DeserializableComponent,
+    for<'a> &'a Primary::ArrayType: IntoIterator,
+{
+    /// Wraps `view`, nothing is deserialized until it's needed.
+    pub fn new(view: EntityView<Primary>) -> Self {
+        Self {
+            view,
+            instance_keys: Default::default(),
+            components: Default::default(),
+        }
+    }
+
+    /// Iterate over the instance keys.
+    ///
+    /// Same as [`EntityView::iter_instance_keys`], only deserialized once.
+    pub fn iter_instance_keys(&self) -> crate::Result<impl Iterator<Item = InstanceKey>> {
+        let mut cached = self.instance_keys.lock();
+        if cached.is_none() {
+            *cached = Some(Arc::new(self.view.iter_instance_keys()?.collect()));
+        }
+        let instance_keys = cached.clone().unwrap(); // set just above
+
+        Ok(iter_shared(instance_keys))
+    }
+
+    /// Iterate over the primary component values.
+    ///
+    /// Same as [`EntityView::iter_primary`], only deserialized once.
+    pub fn iter_primary(&self) -> crate::Result<impl Iterator<Item = Option<Primary>>>
+    where
+        Primary: Clone + Send + Sync + 'static,
+    {
+        let values = self.cached(Primary::name(), || Ok(self.view.iter_primary()?.collect()))?;
+        Ok(iter_shared(values))
+    }
+
+    /// Iterate over the values of a `Component`.
+    ///
+    /// Same as [`EntityView::iter_component`], only deserialized once.
+    pub fn iter_component<C>(&self) -> crate::Result<impl Iterator<Item = Option<C>>>
+    where
+        C: DeserializableComponent + Clone + Send + Sync + 'static,
+        for<'b> &'b C::ArrayType: IntoIterator,
+    {
+        let values = self.cached(C::name(), || Ok(self.view.iter_component::<C>()?.collect()))?;
+        Ok(iter_shared(values))
+    }
+
+    fn cached<C: Clone + Send + Sync + 'static>(
+        &self,
+        component: ComponentName,
+        deserialize: impl FnOnce() -> crate::Result<Vec<Option<C>>>,
+    ) -> crate::Result<Arc<Vec<Option<C>>>> {
+        let mut components = self.components.lock();
+
+        let cached = components
+            .get(&component)
+            .and_then(|values| values.clone().downcast::<Vec<Option<C>>>().ok());
+        if let Some(values) = cached {
+            return Ok(values);
+        }
+
+        let values = Arc::new(deserialize()?);
+        components.insert(component, values.clone());
+        Ok(values)
+    }
+}
+
+/// Iterates over shared values without holding on to any borrow.
+fn iter_shared<T: Clone>(values: Arc<Vec<T>>) -> impl Iterator<Item = T> {
+    (0..values.len()).map(move |i| values[i].clone())
+}
+
+impl<Primary: SerializableComponent + DeserializableComponent> std::ops::Deref
+    for CachedEntityView<Primary>
+where
+    for<'a> &'a Primary::ArrayType: IntoIterator,
+{
+    type Target = EntityView<Primary>;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.view
+    }
+}
+
+/// An entity view returned by [`crate::query_primary_with_history_cached`]: either a cached
+/// latest-at result, or one of the results of a range query, which are never cached.
+///
+/// Derefs to the underlying [`EntityView`], and dispatches its iteration methods to the ones of
+/// [`CachedEntityView`] whenever possible.
+pub enum MaybeCachedEntityView<Primary: SerializableComponent + DeserializableComponent>
+where
+    for<'a> &'a Primary::ArrayType: IntoIterator,
+{
+    Cached(Arc<CachedEntityView<Primary>>),
+    Uncached(EntityView<Primary>),
+}
+
+impl<Primary> MaybeCachedEntityView<Primary>
+where
+    Primary: SerializableComponent + DeserializableComponent,
+    for<'a> &'a Primary::ArrayType: IntoIterator,
+{
+    /// Iterate over the instance keys.
+    ///
+    /// See [`CachedEntityView::iter_instance_keys`].
+    pub fn iter_instance_keys(&self) -> crate::Result<impl Iterator<Item = InstanceKey> + '_> {
+        Ok(match self {
+            Self::Cached(view) => Either::Left(view.iter_instance_keys()?),
+            Self::Uncached(view) => Either::Right(view.iter_instance_keys()?),
+        })
+    }
+
+    /// Iterate over the primary component values.
+    ///
+    /// See [`CachedEntityView::iter_primary`].
+    pub fn iter_primary(&self) -> crate::Result<impl Iterator<Item = Option<Primary>> + '_>
+    where
+        Primary: Clone + Send + Sync + 'static,
+    {
+        Ok(match self {
+            Self::Cached(view) => Either::Left(view.iter_primary()?),
+            Self::Uncached(view) => Either::Right(view.iter_primary()?),
+        })
+    }
+
+    /// Iterate over the values of a `Component`.
+    ///
+    /// See [`CachedEntityView::iter_component`].
+    pub fn iter_component<C>(&self) -> crate::Result<impl Iterator<Item = Option<C>> + '_>
+    where
+        C: DeserializableComponent + Clone + Send + Sync + 'static,
+        for<'b> &'b C::ArrayType: IntoIterator,
+    {
+        Ok(match self {
+            Self::Cached(view) => Either::Left(view.iter_component::<C>()?),
+            Self::Uncached(view) => Either::Right(view.iter_component::<C>()?),
+        })
+    }
+}
+
+impl<Primary: SerializableComponent + DeserializableComponent> std::ops::Deref
+    for MaybeCachedEntityView<Primary>
+where
+    for<'a> &'a Primary::ArrayType: IntoIterator,
+{
+    type Target = EntityView<Primary>;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        match self {
+            Self::Cached(view) => &view.view,
+            Self::Uncached(view) => view,
+        }
+    }
+}
@@ -1,19 +1,27 @@
+use std::collections::BTreeSet;
+
 use arrow2::{
-    array::{Array, StructArray},
+    array::{
+        new_empty_array, new_null_array, Array, Int64Array, StructArray, UInt64Array, Utf8Array,
+    },
+    compute::{
+        concatenate::concatenate,
+        take::{can_take, take},
+    },
     datatypes::PhysicalType,
 };
 use itertools::Itertools;
 use polars_core::prelude::*;
-use re_arrow_store::ArrayExt;
+use re_arrow_store::{ArrayExt, DataStore, RangeQuery};
 use re_log_types::{
     component_types::InstanceKey,
     external::arrow2_convert::deserialize::arrow_array_deserialize_iterator, Component,
-    DeserializableComponent, SerializableComponent,
+    ComponentName, DeserializableComponent, SerializableComponent,
 };
 
 use crate::{
     entity_view::{ComponentWithInstances, EntityView},
-    QueryError,
+    DynEntityView, EntitySelector, QueryBuilder, QueryError,
 };
 
 /// Make it so that our arrays can be deserialized again by arrow2-convert
@@ -182,6 +190,173 @@ where
     }
 }
 
+/// Runs a range query over any number of entities and components, and returns all of the results
+/// as a single long-format `DataFrame`.
+///
+/// The dataframe holds one row per instance, per entity, per point in time, with the following
+/// columns:
+/// - the time on the queried timeline, `null` for timeless data,
+/// - the entity path (`entity`),
+/// - the instance key (named after the store's cluster key),
+/// - one column per component, in the order they were asked for.
+///
+/// Rows are ordered by entity path, then time, then instance key.
+///
+/// This follows the streaming-join semantics of [`QueryBuilder`], with all components being
+/// optional: rows are yielded for every instance any time any one component changes, along with
+/// the latest known state of all the others.
+/// Components that the store has never seen any data for are left out.
+///
+/// ```
+/// # use re_arrow_store::{RangeQuery, TimeInt, TimeRange};
+/// # use re_log_types::{Timeline, component_types::{ColorRGBA, Point2D}, Component};
+/// # let store = re_query::__populate_example_store();
+///
+/// let query = RangeQuery::new(
+///     Timeline::new_sequence("frame_nr"),
+///     TimeRange::new(TimeInt::MIN, TimeInt::MAX),
+/// );
+///
+/// let df = re_query::dataframe_util::range_entities_as_df(
+///     &store,
+///     &query,
+///     ["**"],
+///     &[Point2D::name(), ColorRGBA::name()],
+/// )
+/// .unwrap();
+///
+/// // Two points, as of the first row, then again once the color was added.
+/// assert_eq!(4, df.height());
+/// ```
+pub fn range_entities_as_df<S: Into<EntitySelector>>(
+    store: &DataStore,
+    query: &RangeQuery,
+    entities: impl IntoIterator<Item = S>,
+    components: &[ComponentName],
+) -> crate::Result<DataFrame> {
+    crate::profile_function!();
+
+    let cluster_key = store.cluster_key();
+
+    // Every row holds the cluster key: it would match everything.
+    let components = components
+        .iter()
+        .copied()
+        .filter(|component| *component != cluster_key)
+        .unique()
+        .collect_vec();
+
+    let builder = entities
+        .into_iter()
+        .fold(QueryBuilder::range(query.clone()), |builder, selector| {
+            builder.entities(selector)
+        });
+    let builder = components
+        .iter()
+        .fold(builder, |builder, component| builder.optional(*component));
+    let views = builder.execute(store).collect_vec();
+
+    // We need to know the datatype of every column upfront in order to fill the holes.
+    let columns = components
+        .iter()
+        .filter_map(|component| {
+            let datatype = views
+                .iter()
+                .find_map(|view| view.get(*component))
+                .map(|cwi| cwi.values().data_type().clone())
+                .or_else(|| store.lookup_data_type(component).cloned())?;
+            Some((*component, datatype))
+        })
+        .collect_vec();
+
+    let mut times = Vec::new();
+    let mut ent_paths = Vec::new();
+    let mut instance_keys = Vec::new();
+    let mut values = columns.iter().map(|_| Vec::new()).collect_vec();
+
+    for view in &views {
+        let keys = instance_keys_of(view)?;
+
+        times.extend(std::iter::repeat(view.time().map(|time| time.as_i64())).take(keys.len()));
+        ent_paths.extend(std::iter::repeat(view.ent_path().to_string()).take(keys.len()));
+        instance_keys.extend(keys.iter().map(|key| key.0));
+
+        for ((component, datatype), values) in columns.iter().zip(&mut values) {
+            values.push(match view.get(*component) {
+                Some(cwi) => take_instances(cwi, &keys)?,
+                None => new_null_array(datatype.clone(), keys.len()),
+            });
+        }
+    }
+
+    let times = Int64Array::from(times).to(query.timeline.datatype());
+    let ent_paths = Utf8Array::<i32>::from_slice(ent_paths);
+    let instance_keys = UInt64Array::from_vec(instance_keys);
+
+    let mut series = vec![
+        Series::try_from((query.timeline.name().as_str(), times.boxed()))?,
+        Series::try_from(("entity", ent_paths.boxed()))?,
+        Series::try_from((cluster_key.as_str(), instance_keys.boxed()))?,
+    ];
+    for ((component, datatype), values) in columns.into_iter().zip(values) {
+        let values = if values.is_empty() {
+            new_empty_array(datatype)
+        } else {
+            concatenate(&values.iter().map(|values| values.as_ref()).collect_vec())?
+        };
+        series.push(Series::try_from((
+            component.as_str(),
+            values.as_ref().clean_for_polars(),
+        ))?);
+    }
+
+    Ok(DataFrame::new(series)?)
+}
+
+/// The values of `cwi` for each of the given `instance_keys`, null where there is none.
+fn take_instances(
+    cwi: &ComponentWithInstances,
+    instance_keys: &[InstanceKey],
+) -> crate::Result<Box<dyn Array>> {
+    if can_take(cwi.values().data_type()) {
+        let indices = instance_keys
+            .iter()
+            .map(|key| cwi.lookup_offset(key).map(|offset| offset as u64))
+            .collect_vec();
+        Ok(take(cwi.values(), &UInt64Array::from(indices))?)
+    } else if instance_keys.is_empty() {
+        Ok(new_empty_array(cwi.values().data_type().clone()))
+    } else {
+        // The take kernel doesn't support unions: gather these one instance at a time.
+        let values = instance_keys
+            .iter()
+            .map(|key| {
+                cwi.lookup_arrow(key)
+                    .unwrap_or_else(|| new_null_array(cwi.values().data_type().clone(), 1))
+            })
+            .collect_vec();
+        Ok(concatenate(
+            &values.iter().map(|values| values.as_ref()).collect_vec(),
+        )?)
+    }
+}
+
+/// All the instances of an entity: the union of the instance keys of all its components.
+///
+/// Splats don't count as instances of their own, unless there's nothing but splats.
+fn instance_keys_of(view: &DynEntityView) -> crate::Result<Vec<InstanceKey>> {
+    let mut instance_keys = BTreeSet::new();
+    for cwi in view.iter_components() {
+        instance_keys.extend(cwi.iter_instance_keys()?);
+    }
+
+    if instance_keys.len() > 1 {
+        instance_keys.remove(&InstanceKey::SPLAT);
+    }
+
+    Ok(instance_keys.into_iter().collect())
+}
+
 #[test]
 fn test_df_builder() {
     use re_log_types::component_types::{ColorRGBA, Point2D};
@@ -244,3 +419,140 @@ fn test_df_builder() {
 
     assert_eq!(df, expected);
 }
+
+#[test]
+fn test_range_entities_as_df() {
+    use re_arrow_store::{TimeInt, TimeRange};
+    use re_log_types::{
+        component_types::{ColorRGBA, Point2D, Radius},
+        datagen::build_frame_nr,
+        DataRow, MsgId,
+    };
+
+    let mut store = DataStore::new(InstanceKey::name(), Default::default());
+
+    let timepoint1 = [build_frame_nr(123.into())];
+    let points = vec![Point2D { x: 1.0, y: 2.0 }, Point2D { x: 3.0, y: 4.0 }];
+    let row = DataRow::from_cells1(MsgId::random(), "world/points", timepoint1, 2, points);
+    store.insert_row(&row).unwrap();
+    let colors = (vec![InstanceKey(1)], vec![ColorRGBA(0xff000000)]);
+    let row = DataRow::from_cells2(MsgId::random(), "world/points", timepoint1, 1, colors);
+    store.insert_row(&row).unwrap();
+    let colors = vec![ColorRGBA(0x00ff0000)];
+    let row = DataRow::from_cells1(MsgId::random(), "world/colors", timepoint1, 1, colors);
+    store.insert_row(&row).unwrap();
+
+    let timepoint2 = [build_frame_nr(223.into())];
+    let radii = vec![Radius(1.0), Radius(2.0)];
+    let row = DataRow::from_cells1(MsgId::random(), "world/points", timepoint2, 2, radii);
+    store.insert_row(&row).unwrap();
+
+    let timepoint3 = [build_frame_nr(323.into())];
+    let points = vec![Point2D { x: 10.0, y: 20.0 }, Point2D { x: 30.0, y: 40.0 }];
+    let radii = vec![Radius(3.0), Radius(4.0)];
+    let row = DataRow::from_cells2(
+        MsgId::random(),
+        "world/points",
+        timepoint3,
+        2,
+        (points, radii),
+    );
+    store.insert_row(&row).unwrap();
+
+    let timeline = build_frame_nr(0.into()).0;
+    let query = RangeQuery::new(timeline, TimeRange::new(TimeInt::MIN, TimeInt::MAX));
+    let df = range_entities_as_df(
+        &store,
+        &query,
+        ["**"],
+        &[Point2D::name(), ColorRGBA::name(), Radius::name()],
+    )
+    .unwrap();
+
+    assert_eq!(
+        vec![
+            timeline.name().as_str(),
+            "entity",
+            InstanceKey::name().as_str(),
+            Point2D::name().as_str(),
+            ColorRGBA::name().as_str(),
+            Radius::name().as_str(),
+        ],
+        df.get_column_names()
+    );
+
+    // `world/points` yields a result for each of its 4 rows, for both of its instances.
+    let ent_paths = df
+        .column("entity")
+        .unwrap()
+        .utf8()
+        .unwrap()
+        .into_no_null_iter()
+        .collect_vec();
+    let mut expected = vec!["world/colors"];
+    expected.extend(["world/points"; 8]);
+    assert_eq!(expected, ent_paths);
+
+    let times = df
+        .column(timeline.name().as_str())
+        .unwrap()
+        .i64()
+        .unwrap()
+        .into_no_null_iter()
+        .collect_vec();
+    assert_eq!(vec![123, 123, 123, 123, 123, 223, 223, 323, 323], times);
+
+    let instance_keys = df
+        .column(InstanceKey::name().as_str())
+        .unwrap()
+        .u64()
+        .unwrap()
+        .into_no_null_iter()
+        .collect_vec();
+    assert_eq!(vec![0, 0, 1, 0, 1, 0, 1, 0, 1], instance_keys);
+
+    // Only the second point has a color, which carries over to later results.
+    let colors = df
+        .column(ColorRGBA::name().as_str())
+        .unwrap()
+        .u32()
+        .unwrap()
+        .into_iter()
+        .collect_vec();
+    assert_eq!(
+        vec![
+            Some(0x00ff0000),
+            None,
+            None,
+            None,
+            Some(0xff000000),
+            None,
+            Some(0xff000000),
+            None,
+            Some(0xff000000),
+        ],
+        colors
+    );
+
+    let radii = df
+        .column(Radius::name().as_str())
+        .unwrap()
+        .f32()
+        .unwrap()
+        .into_iter()
+        .collect_vec();
+    assert_eq!(
+        vec![
+            None,
+            None,
+            None,
+            None,
+            None,
+            Some(1.0),
+            Some(2.0),
+            Some(3.0),
+            Some(4.0),
+        ],
+        radii
+    );
+}
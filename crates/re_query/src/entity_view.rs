@@ -40,6 +40,18 @@ impl ComponentWithInstances {
         self.values.len() == 0
     }
 
+    /// The raw instance keys, if present.
+    ///
+    /// See [`Self::iter_instance_keys`].
+    pub fn instance_keys(&self) -> Option<&dyn Array> {
+        self.instance_keys.as_deref()
+    }
+
+    /// The raw values.
+    pub fn values(&self) -> &dyn Array {
+        self.values.as_ref()
+    }
+
     /// Iterate over the instance keys
     ///
     /// If the instance keys don't exist, generate them based on array-index position of the values
@@ -96,7 +108,14 @@ impl ComponentWithInstances {
 
     /// Look up the value that corresponds to a given `InstanceKey` and return as an arrow `Array`
     pub fn lookup_arrow(&self, instance_key: &InstanceKey) -> Option<Box<dyn Array>> {
-        let offset = if let Some(instance_keys) = &self.instance_keys {
+        let offset = self.lookup_offset(instance_key)?;
+        Some(self.values.slice(offset, 1))
+    }
+
+    /// Look up the offset, within [`Self::values`], of the value that corresponds to a given
+    /// `InstanceKey`
+    pub fn lookup_offset(&self, instance_key: &InstanceKey) -> Option<usize> {
+        if let Some(instance_keys) = &self.instance_keys {
             // If `instance_keys` is set, extract the `PrimitiveArray`, and find
             // the index of the value by `binary_search`
 
@@ -107,19 +126,17 @@ impl ComponentWithInstances {
 
             // If the value is splatted, return the offset of the splat
             if keys.len() == 1 && keys[0] == InstanceKey::SPLAT.0 {
-                0
+                Some(0)
             } else {
                 // Otherwise binary search to find the offset of the instance
-                keys.binary_search(&instance_key.0).ok()?
+                keys.binary_search(&instance_key.0).ok()
             }
         } else {
             // If `instance_keys` is not set, then offset is the instance because the implicit
             // index is a sequential list
             let offset = instance_key.0 as usize;
-            (offset < self.values.len()).then_some(offset)?
-        };
-
-        Some(self.values.slice(offset, 1))
+            (offset < self.values.len()).then_some(offset)
+        }
     }
 
     /// Produce a `ComponentWithInstances` from native component types
@@ -0,0 +1,226 @@
+use std::collections::BTreeMap;
+
+use re_arrow_store::{DataStore, LatestAtQuery, RowIndex};
+use re_log_types::{
+    component_types::{InstanceKey, Point3D, Quaternion, Rigid3, Scalar, Transform, Vec3D},
+    Component, ComponentName, DeserializableComponent, EntityPath, SerializableComponent,
+};
+
+use crate::{ComponentWithInstances, QueryError};
+
+/// A [`Component`] whose values can be blended between two samples.
+///
+/// See [`get_component_with_instances_interpolated`].
+pub trait Interpolate: Sized {
+    /// Blends `self` (at `t = 0.0`) with `other` (at `t = 1.0`).
+    fn interpolate(&self, other: &Self, t: f32) -> Self;
+}
+
+#[inline]
+fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
+}
+
+impl Interpolate for Scalar {
+    #[inline]
+    fn interpolate(&self, other: &Self, t: f32) -> Self {
+        Self(self.0 + (other.0 - self.0) * t as f64)
+    }
+}
+
+impl Interpolate for Vec3D {
+    #[inline]
+    fn interpolate(&self, other: &Self, t: f32) -> Self {
+        Self::new(
+            lerp(self.x(), other.x(), t),
+            lerp(self.y(), other.y(), t),
+            lerp(self.z(), other.z(), t),
+        )
+    }
+}
+
+impl Interpolate for Point3D {
+    #[inline]
+    fn interpolate(&self, other: &Self, t: f32) -> Self {
+        Self::new(
+            lerp(self.x, other.x, t),
+            lerp(self.y, other.y, t),
+            lerp(self.z, other.z, t),
+        )
+    }
+}
+
+/// Spherical linear interpolation, along the shortest path.
+impl Interpolate for Quaternion {
+    fn interpolate(&self, other: &Self, t: f32) -> Self {
+        let a = [self.x, self.y, self.z, self.w];
+        let mut b = [other.x, other.y, other.z, other.w];
+
+        let mut dot: f32 = a.iter().zip(&b).map(|(a, b)| a * b).sum();
+        if dot < 0.0 {
+            // `q` and `-q` are the same rotation: go the short way around.
+            b = b.map(|v| -v);
+            dot = -dot;
+        }
+
+        let [x, y, z, w] = if dot > 0.9995 {
+            // Nearly identical rotations: `sin(theta)` gets too close to zero, fall back to a
+            // normalized linear interpolation.
+            let q = [0, 1, 2, 3].map(|i| lerp(a[i], b[i], t));
+            let norm = q.iter().map(|v| v * v).sum::<f32>().sqrt();
+            q.map(|v| v / norm)
+        } else {
+            let theta = dot.acos();
+            let sin_theta = theta.sin();
+            let wa = ((1.0 - t) * theta).sin() / sin_theta;
+            let wb = (t * theta).sin() / sin_theta;
+            [0, 1, 2, 3].map(|i| wa * a[i] + wb * b[i])
+        };
+
+        Self::new(x, y, z, w)
+    }
+}
+
+impl Interpolate for Rigid3 {
+    #[inline]
+    fn interpolate(&self, other: &Self, t: f32) -> Self {
+        Self {
+            rotation: self.rotation.interpolate(&other.rotation, t),
+            translation: self.translation.interpolate(&other.translation, t),
+        }
+    }
+}
+
+/// Only rigid transforms are interpolated: anything else steps from one sample to the next.
+impl Interpolate for Transform {
+    fn interpolate(&self, other: &Self, t: f32) -> Self {
+        match (self, other) {
+            (Self::Rigid3(a), Self::Rigid3(b)) => Self::Rigid3(a.interpolate(b, t)),
+            _ => self.clone(),
+        }
+    }
+}
+
+// ---
+
+/// Same as [`crate::get_component_with_instances`], but the value of the component is linearly
+/// interpolated between the latest sample at or before the queried time, and the earliest sample
+/// after it.
+///
+/// Instances are matched using their [`InstanceKey`]s: those that are missing from the later
+/// sample keep their earlier value.
+/// Timeless samples are never interpolated, and neither is the very last sample of a component,
+/// which holds until the end of time.
+///
+/// ```
+/// # use re_arrow_store::{DataStore, LatestAtQuery};
+/// # use re_log_types::{
+/// #     component_types::{InstanceKey, Scalar}, datagen::build_frame_nr, Component, DataRow,
+/// #     MsgId, Timeline,
+/// # };
+/// let mut store = DataStore::new(InstanceKey::name(), Default::default());
+/// for (frame_nr, value) in [(10, 1.0), (20, 2.0)] {
+///     let row = DataRow::from_cells1(
+///         MsgId::random(),
+///         "scalar",
+///         [build_frame_nr(frame_nr.into())],
+///         1,
+///         vec![Scalar(value)],
+///     );
+///     store.insert_row(&row).unwrap();
+/// }
+///
+/// let query = LatestAtQuery::new(Timeline::new_sequence("frame_nr"), 15.into());
+/// let component = re_query::get_component_with_instances_interpolated::<Scalar>(
+///     &store,
+///     &query,
+///     &"scalar".into(),
+/// )
+/// .unwrap();
+///
+/// let value = component.iter_values::<Scalar>().unwrap().next().flatten().unwrap();
+/// assert_eq!(1.5, value.0);
+/// ```
+pub fn get_component_with_instances_interpolated<C>(
+    store: &DataStore,
+    query: &LatestAtQuery,
+    ent_path: &EntityPath,
+) -> crate::Result<ComponentWithInstances>
+where
+    C: SerializableComponent + DeserializableComponent + Interpolate,
+    for<'a> &'a C::ArrayType: IntoIterator,
+{
+    crate::profile_function!();
+
+    let components = [InstanceKey::name(), C::name()];
+
+    let samples = store
+        .latest_at_and_next(query, ent_path, C::name(), &components)
+        .ok_or(QueryError::PrimaryNotFound)?;
+
+    let before = component_with_instances(store, C::name(), &components, &samples.before)?;
+
+    let (Some(before_time), Some((after_time, after_row_indices))) =
+        (samples.before_time, samples.after) else {
+        return Ok(before);
+    };
+
+    let after = component_with_instances(store, C::name(), &components, &after_row_indices)?;
+
+    let t = ((query.at - before_time).as_f64() / (after_time - before_time).as_f64()) as f32;
+
+    let after_values: BTreeMap<InstanceKey, C> = after
+        .iter_instance_keys()?
+        .zip(after.iter_values::<C>()?)
+        .filter_map(|(instance_key, value)| Some((instance_key, value?)))
+        .collect();
+
+    let (instance_keys, values): (Vec<_>, Vec<_>) = before
+        .iter_instance_keys()?
+        .zip(before.iter_values::<C>()?)
+        .filter_map(|(instance_key, value)| {
+            let value = value?;
+            let value = match after_values.get(&instance_key) {
+                Some(after) => value.interpolate(after, t),
+                None => value,
+            };
+            Some((instance_key, value))
+        })
+        .unzip();
+
+    ComponentWithInstances::from_native(Some(&instance_keys), &values)
+}
+
+fn component_with_instances(
+    store: &DataStore,
+    name: ComponentName,
+    components: &[ComponentName; 2],
+    row_indices: &[Option<RowIndex>; 2],
+) -> crate::Result<ComponentWithInstances> {
+    let mut results = store.get(components, row_indices);
+
+    Ok(ComponentWithInstances {
+        name,
+        instance_keys: results[0].take(),
+        values: results[1].take().ok_or(QueryError::PrimaryNotFound)?,
+    })
+}
+
+/// Same as [`re_data_store::query_latest_single`], but the value of the component is
+/// interpolated, see [`get_component_with_instances_interpolated`].
+///
+/// Only the first instance is returned.
+pub fn query_latest_single_interpolated<C>(
+    store: &DataStore,
+    query: &LatestAtQuery,
+    ent_path: &EntityPath,
+) -> Option<C>
+where
+    C: SerializableComponent + DeserializableComponent + Interpolate,
+    for<'a> &'a C::ArrayType: IntoIterator,
+{
+    let component = get_component_with_instances_interpolated::<C>(store, query, ent_path).ok()?;
+
+    let mut values = component.iter_values::<C>().ok()?;
+    values.next().flatten()
+}
@@ -6,7 +6,10 @@
 
 // TODO(jleibs) better crate documentation.
 
+mod builder;
+mod cache;
 mod entity_view;
+mod interpolate;
 mod query;
 mod range;
 mod util;
@@ -15,10 +18,15 @@ pub mod visit;
 #[cfg(feature = "polars")]
 pub mod dataframe_util;
 
+pub use self::builder::{DynEntityView, EntityPathGlob, EntitySelector, QueryBuilder, QueryKind};
+pub use self::cache::{CachedEntityView, MaybeCachedEntityView, QueryCache, QueryCacheStats};
 pub use self::entity_view::{ComponentWithInstances, EntityView};
+pub use self::interpolate::{
+    get_component_with_instances_interpolated, query_latest_single_interpolated, Interpolate,
+};
 pub use self::query::{get_component_with_instances, query_entity_with_primary};
-pub use self::range::range_entity_with_primary;
-pub use self::util::query_primary_with_history;
+pub use self::range::{range_entity_with_primary, range_instance_history};
+pub use self::util::{query_primary_with_history, query_primary_with_history_cached};
 
 // Used for doc-tests
 #[doc(hidden)]
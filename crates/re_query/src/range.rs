@@ -1,6 +1,8 @@
 use itertools::Itertools as _;
-use re_arrow_store::{DataStore, LatestAtQuery, RangeQuery, TimeInt};
-use re_log_types::{Component, ComponentName, EntityPath};
+use re_arrow_store::{DataStore, LatestAtQuery, RangeQuery, TimeInt, ValueFilter};
+use re_log_types::{
+    component_types::InstanceKey, Component, ComponentName, DeserializableComponent, EntityPath,
+};
 
 use crate::{get_component_with_instances, ComponentWithInstances, EntityView};
 
@@ -141,3 +143,54 @@ pub fn range_entity_with_primary<'a, Primary: Component + 'a, const N: usize>(
             })
         })
 }
+
+/// Iterates over the values of the `C` component of a single instance, e.g. the trajectory of one
+/// tracked object.
+///
+/// A value is yielded for every row in which `C` was logged for `instance_key` (splats
+/// included), in the same order as [`DataStore::range`].
+/// Rows that don't hold `C` for that instance are skipped, and only the value of `instance_key`
+/// itself ever gets deserialized.
+///
+/// Unlike [`range_entity_with_primary`], no latest-at state is yielded for the start of the time
+/// range.
+///
+/// If the cluster key has a value index (see [`DataStore::enable_value_index`]), the rows that
+/// don't hold `instance_key` are never even looked at.
+pub fn range_instance_history<'a, C: DeserializableComponent + 'a>(
+    store: &'a DataStore,
+    query: &RangeQuery,
+    ent_path: &'a EntityPath,
+    instance_key: InstanceKey,
+) -> impl Iterator<Item = (Option<TimeInt>, C)> + 'a
+where
+    for<'b> &'b C::ArrayType: IntoIterator,
+{
+    crate::profile_function!();
+
+    let cluster_key = store.cluster_key();
+    let components = [C::name(), cluster_key];
+
+    let mut query = query.clone();
+    if store.has_value_index(cluster_key) {
+        let keys = [instance_key.0, InstanceKey::SPLAT.0];
+        query = query.with_filter(ValueFilter::any_of(cluster_key, keys));
+    }
+
+    store
+        .range(&query, ent_path, components)
+        .filter_map(move |(time, _, row_indices)| {
+            // Every row holds the cluster key, but not necessarily `C`.
+            row_indices[0]?;
+
+            let [values, instance_keys] = store.get(&components, &row_indices);
+            let cwi = ComponentWithInstances {
+                name: C::name(),
+                instance_keys,
+                values: values?,
+            };
+
+            let value = cwi.lookup::<C>(&instance_key).ok()?;
+            Some((time, value))
+        })
+}
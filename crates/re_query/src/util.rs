@@ -1,8 +1,13 @@
 use re_arrow_store::{DataStore, LatestAtQuery, RangeQuery, TimeInt, TimeRange, Timeline};
 use re_data_store::ExtraQueryHistory;
-use re_log_types::{Component, ComponentName, EntityPath};
+use re_log_types::{
+    Component, ComponentName, DeserializableComponent, EntityPath, SerializableComponent,
+};
 
-use crate::{query_entity_with_primary, range_entity_with_primary, EntityView};
+use crate::{
+    query_entity_with_primary, range_entity_with_primary, EntityView, MaybeCachedEntityView,
+    QueryCache,
+};
 
 /// Either dispatch to `query_entity_with_primary` or `range_entity_with_primary`
 /// depending on whether `ExtraQueryHistory` is set.
@@ -35,3 +40,50 @@ pub fn query_primary_with_history<'a, Primary: Component + 'a, const N: usize>(
         Ok(itertools::Either::Right(range.map(|(_, entity)| entity)))
     }
 }
+
+/// Same as [`query_primary_with_history`], except that latest-at queries go through `cache`.
+///
+/// The results of range queries are not cached: they are yielded as
+/// [`MaybeCachedEntityView::Uncached`].
+pub fn query_primary_with_history_cached<'a, Primary, const N: usize>(
+    cache: &QueryCache,
+    store: &'a DataStore,
+    timeline: &'a Timeline,
+    time: &'a TimeInt,
+    history: &ExtraQueryHistory,
+    ent_path: &'a EntityPath,
+    components: [ComponentName; N],
+) -> crate::Result<impl Iterator<Item = MaybeCachedEntityView<Primary>> + 'a>
+where
+    Primary: SerializableComponent + DeserializableComponent + Send + Sync + 'static,
+    for<'b> &'b Primary::ArrayType: IntoIterator,
+{
+    let visible_history = match timeline.typ() {
+        re_log_types::TimeType::Time => history.nanos,
+        re_log_types::TimeType::Sequence => history.sequences,
+    };
+
+    if visible_history == 0 {
+        let latest_query = LatestAtQuery::new(*timeline, *time);
+        let latest = cache.query_entity_with_primary::<Primary>(
+            store,
+            &latest_query,
+            ent_path,
+            &components,
+        )?;
+
+        Ok(itertools::Either::Left(std::iter::once(
+            MaybeCachedEntityView::Cached(latest),
+        )))
+    } else {
+        let min_time = *time - TimeInt::from(visible_history);
+        let range_query = RangeQuery::new(*timeline, TimeRange::new(min_time, *time));
+
+        let range =
+            range_entity_with_primary::<Primary, N>(store, &range_query, ent_path, components);
+
+        Ok(itertools::Either::Right(range.map(|(_, entity)| {
+            MaybeCachedEntityView::Uncached(entity)
+        })))
+    }
+}
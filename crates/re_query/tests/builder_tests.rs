@@ -0,0 +1,265 @@
+use re_arrow_store::{DataStore, LatestAtQuery, RangeQuery, TimeInt, TimeRange, ValueFilter};
+use re_log_types::{
+    component_types::InstanceKey,
+    component_types::{ColorRGBA, Point2D, Radius},
+    datagen::build_frame_nr,
+    Component, DataRow, EntityPath, MsgId,
+};
+use re_query::{DynEntityView, EntityPathGlob, QueryBuilder};
+
+fn populate() -> DataStore {
+    let mut store = DataStore::new(InstanceKey::name(), Default::default());
+
+    let timepoint1 = [build_frame_nr(123.into())];
+    {
+        // Create some points with implicit instances
+        let points = vec![Point2D { x: 1.0, y: 2.0 }, Point2D { x: 3.0, y: 4.0 }];
+        let row = DataRow::from_cells1(MsgId::random(), "world/points", timepoint1, 2, points);
+        store.insert_row(&row).unwrap();
+
+        // Assign one of them a color with an explicit instance
+        let color_instances = vec![InstanceKey(1)];
+        let colors = vec![ColorRGBA(0xff000000)];
+        let row = DataRow::from_cells2(
+            MsgId::random(),
+            "world/points",
+            timepoint1,
+            1,
+            (color_instances, colors),
+        );
+        store.insert_row(&row).unwrap();
+
+        // Some colors without any points, elsewhere
+        let colors = vec![ColorRGBA(0x00ff0000)];
+        let row = DataRow::from_cells1(MsgId::random(), "world/colors", timepoint1, 1, colors);
+        store.insert_row(&row).unwrap();
+
+        // Some points outside of the `world` subtree
+        let points = vec![Point2D { x: 5.0, y: 6.0 }];
+        let row = DataRow::from_cells1(MsgId::random(), "other/points", timepoint1, 1, points);
+        store.insert_row(&row).unwrap();
+    }
+
+    let timepoint2 = [build_frame_nr(223.into())];
+    {
+        // Give all the points a radius
+        let radii = vec![Radius(1.0), Radius(2.0)];
+        let row = DataRow::from_cells1(MsgId::random(), "world/points", timepoint2, 2, radii);
+        store.insert_row(&row).unwrap();
+    }
+
+    let timepoint3 = [build_frame_nr(323.into())];
+    {
+        // Move the points and give them a radius, as part of the same row
+        let points = vec![Point2D { x: 10.0, y: 20.0 }, Point2D { x: 30.0, y: 40.0 }];
+        let radii = vec![Radius(3.0), Radius(4.0)];
+        let row = DataRow::from_cells2(
+            MsgId::random(),
+            "world/points",
+            timepoint3,
+            2,
+            (points, radii),
+        );
+        store.insert_row(&row).unwrap();
+    }
+
+    store
+}
+
+fn ent_paths(results: &[DynEntityView]) -> Vec<String> {
+    results
+        .iter()
+        .map(|view| view.ent_path().to_string())
+        .collect()
+}
+
+fn points(view: &DynEntityView) -> Vec<Point2D> {
+    view.get(Point2D::name())
+        .unwrap()
+        .iter_values::<Point2D>()
+        .unwrap()
+        .flatten()
+        .collect()
+}
+
+#[test]
+fn latest_at() {
+    let store = populate();
+    let timeline = build_frame_nr(0.into()).0;
+    let query = LatestAtQuery::new(timeline, 223.into());
+
+    // Required components only yield the entities that have them.
+    let results = QueryBuilder::latest_at(query.clone())
+        .entities("world/**")
+        .required(Point2D::name())
+        .optional(ColorRGBA::name())
+        .execute(&store)
+        .collect::<Vec<_>>();
+    assert_eq!(vec!["world/points".to_owned()], ent_paths(&results));
+
+    let view = &results[0];
+    assert_eq!(None, view.time());
+    assert_eq!(
+        vec![ColorRGBA::name(), Point2D::name()],
+        view.iter_components()
+            .map(|cwi| cwi.name())
+            .collect::<Vec<_>>()
+    );
+    assert_eq!(
+        vec![Point2D { x: 1.0, y: 2.0 }, Point2D { x: 3.0, y: 4.0 }],
+        points(view)
+    );
+
+    // Instance keys come along with the raw data.
+    let colors = view.get(ColorRGBA::name()).unwrap();
+    assert_eq!(1, colors.values().len());
+    assert_eq!(
+        vec![InstanceKey(1)],
+        colors.iter_instance_keys().unwrap().collect::<Vec<_>>()
+    );
+
+    // Without required components, any component will do.
+    let results = QueryBuilder::latest_at(query.clone())
+        .entities("**")
+        .optional(Point2D::name())
+        .optional(ColorRGBA::name())
+        .execute(&store)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        vec![
+            "other/points".to_owned(),
+            "world/colors".to_owned(),
+            "world/points".to_owned(),
+        ],
+        ent_paths(&results)
+    );
+
+    // Exact paths only select the entity itself.
+    let results = QueryBuilder::latest_at(query)
+        .entities("world")
+        .entities(EntityPath::from("world/colors"))
+        .optional(ColorRGBA::name())
+        .execute(&store)
+        .collect::<Vec<_>>();
+    assert_eq!(vec!["world/colors".to_owned()], ent_paths(&results));
+}
+
+#[test]
+fn globs() {
+    let store = populate();
+    let timeline = build_frame_nr(0.into()).0;
+    let query = LatestAtQuery::new(timeline, 223.into());
+
+    let select = |glob: &str| {
+        let results = QueryBuilder::latest_at(query.clone())
+            .entities(glob)
+            .optional(Point2D::name())
+            .optional(ColorRGBA::name())
+            .execute(&store)
+            .collect::<Vec<_>>();
+        ent_paths(&results)
+    };
+
+    assert_eq!(
+        vec!["other/points".to_owned(), "world/points".to_owned()],
+        select("*/points")
+    );
+    assert_eq!(
+        vec!["other/points".to_owned(), "world/points".to_owned()],
+        select("**/points")
+    );
+    assert_eq!(
+        vec!["world/colors".to_owned(), "world/points".to_owned()],
+        select("world/*")
+    );
+    assert!(select("*").is_empty());
+
+    let glob = EntityPathGlob::parse("world/**/points");
+    assert!(glob.matches(&"world/points".into()));
+    assert!(glob.matches(&"world/camera/left/points".into()));
+    assert!(!glob.matches(&"world/camera/left".into()));
+    assert!(!glob.matches(&"other/points".into()));
+    assert_eq!(EntityPath::from("world"), glob.root());
+}
+
+#[test]
+fn range() {
+    let store = populate();
+    let timeline = build_frame_nr(0.into()).0;
+
+    // --- `(123, 323]`: latest-at semantics kick in ---
+
+    let query = RangeQuery::new(timeline, TimeRange::new(124.into(), 323.into()));
+    let results = QueryBuilder::range(query)
+        .entities("world/**")
+        .required(Point2D::name())
+        .optional(Radius::name())
+        .execute(&store)
+        .collect::<Vec<_>>();
+
+    let times = results
+        .iter()
+        .map(|view| view.time().map(|time| time.as_i64()))
+        .collect::<Vec<_>>();
+    assert_eq!(vec![Some(123), Some(223), Some(323)], times);
+    assert!(results
+        .iter()
+        .all(|view| view.ent_path().to_string() == "world/points"));
+
+    // Initial state: no radius yet.
+    assert!(results[0].get(Radius::name()).is_none());
+    // Frame #223: only the radius changed.
+    assert!(results[1].get(Radius::name()).is_some());
+    assert_eq!(
+        vec![Point2D { x: 1.0, y: 2.0 }, Point2D { x: 3.0, y: 4.0 }],
+        points(&results[1])
+    );
+    // Frame #323: both changed as part of a single row, which yields a single result.
+    assert_eq!(
+        vec![Point2D { x: 10.0, y: 20.0 }, Point2D { x: 30.0, y: 40.0 }],
+        points(&results[2])
+    );
+
+    // --- Radii are required: nothing before frame #223 ---
+
+    let query = RangeQuery::new(timeline, TimeRange::new(TimeInt::MIN, TimeInt::MAX));
+    let results = QueryBuilder::range(query)
+        .entities("world/points")
+        .required(Point2D::name())
+        .required(Radius::name())
+        .execute(&store)
+        .collect::<Vec<_>>();
+
+    let times = results
+        .iter()
+        .map(|view| view.time().map(|time| time.as_i64()))
+        .collect::<Vec<_>>();
+    assert_eq!(vec![Some(223), Some(323)], times);
+}
+
+#[test]
+fn range_filtered() {
+    let mut store = populate();
+    store.enable_value_index(Radius::name()).unwrap();
+    let timeline = build_frame_nr(0.into()).0;
+
+    // Only the row of frame #323 has radii that large: the other ones are ignored altogether.
+    let query = RangeQuery::new(timeline, TimeRange::new(TimeInt::MIN, TimeInt::MAX))
+        .with_filter(ValueFilter::ge(Radius::name(), 3.0));
+    let results = QueryBuilder::range(query)
+        .entities("world/points")
+        .required(Point2D::name())
+        .optional(Radius::name())
+        .execute(&store)
+        .collect::<Vec<_>>();
+
+    let times = results
+        .iter()
+        .map(|view| view.time().map(|time| time.as_i64()))
+        .collect::<Vec<_>>();
+    assert_eq!(vec![Some(323)], times);
+    assert_eq!(
+        vec![Point2D { x: 10.0, y: 20.0 }, Point2D { x: 30.0, y: 40.0 }],
+        points(&results[0])
+    );
+}
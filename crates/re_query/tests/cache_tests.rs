@@ -0,0 +1,182 @@
+use re_arrow_store::{DataStore, GarbageCollectionTarget, LatestAtQuery};
+use re_log_types::{
+    component_types::InstanceKey,
+    component_types::{ColorRGBA, Point2D},
+    datagen::build_frame_nr,
+    Component, DataRow, EntityPath, MsgId,
+};
+use re_query::{QueryCache, QueryCacheStats, QueryError};
+
+fn insert_points(store: &mut DataStore, ent_path: &EntityPath, frame_nr: i64, x: f32) {
+    let points = vec![Point2D { x, y: 2.0 }, Point2D { x, y: 4.0 }];
+    let row = DataRow::from_cells1(
+        MsgId::random(),
+        ent_path.clone(),
+        [build_frame_nr(frame_nr.into())],
+        2,
+        points,
+    );
+    store.insert_row(&row).unwrap();
+}
+
+fn query_points(
+    cache: &QueryCache,
+    store: &DataStore,
+    ent_path: &EntityPath,
+    frame_nr: i64,
+) -> Option<Vec<Point2D>> {
+    let query = LatestAtQuery::new(build_frame_nr(0.into()).0, frame_nr.into());
+    match cache.query_entity_with_primary::<Point2D>(store, &query, ent_path, &[ColorRGBA::name()])
+    {
+        Ok(view) => Some(view.iter_primary().unwrap().flatten().collect()),
+        Err(QueryError::PrimaryNotFound) => None,
+        Err(err) => panic!("unexpected error: {err}"),
+    }
+}
+
+#[test]
+fn cached_latest_at() {
+    let mut store = DataStore::new(InstanceKey::name(), Default::default());
+    let cache = QueryCache::default();
+    cache.register(&mut store);
+    assert!(cache.is_registered(&store));
+
+    let ent_path: EntityPath = "point".into();
+
+    // Missing entities are cached too.
+    assert_eq!(None, query_points(&cache, &store, &ent_path, 200));
+    assert_eq!(None, query_points(&cache, &store, &ent_path, 200));
+    assert_eq!(
+        QueryCacheStats {
+            num_entries: 1,
+            hits: 1,
+            misses: 1,
+            invalidations: 0
+        },
+        cache.stats()
+    );
+
+    insert_points(&mut store, &ent_path, 100, 1.0);
+
+    // The insertion doesn't invalidate the missing result, but it is no longer valid at the time
+    // of the query.
+    let expected = vec![Point2D { x: 1.0, y: 2.0 }, Point2D { x: 1.0, y: 4.0 }];
+    assert_eq!(
+        Some(expected.clone()),
+        query_points(&cache, &store, &ent_path, 200)
+    );
+    assert_eq!(
+        Some(expected.clone()),
+        query_points(&cache, &store, &ent_path, 200)
+    );
+    assert_eq!(
+        QueryCacheStats {
+            num_entries: 1,
+            hits: 2,
+            misses: 2,
+            invalidations: 0
+        },
+        cache.stats()
+    );
+
+    // Data that comes after the query time doesn't affect its results.
+    insert_points(&mut store, &ent_path, 300, 3.0);
+    assert_eq!(
+        Some(expected.clone()),
+        query_points(&cache, &store, &ent_path, 200)
+    );
+    assert_eq!(0, cache.stats().invalidations);
+
+    // Neither does data about other entities.
+    insert_points(&mut store, &"other".into(), 150, 3.0);
+    assert_eq!(Some(expected), query_points(&cache, &store, &ent_path, 200));
+    assert_eq!(0, cache.stats().invalidations);
+    assert_eq!(4, cache.stats().hits);
+
+    // A different query time replaces the cached result.
+    let expected = vec![Point2D { x: 3.0, y: 2.0 }, Point2D { x: 3.0, y: 4.0 }];
+    assert_eq!(Some(expected), query_points(&cache, &store, &ent_path, 300));
+    assert_eq!(1, cache.stats().num_entries);
+    assert_eq!(3, cache.stats().misses);
+
+    // Garbage collection invalidates the results too.
+    store.gc(
+        GarbageCollectionTarget::DropEverythingBefore(250.into()),
+        build_frame_nr(0.into()).0,
+        Point2D::name(),
+    );
+    assert_eq!(1, cache.stats().invalidations);
+    query_points(&cache, &store, &ent_path, 300);
+    assert_eq!(4, cache.stats().misses);
+}
+
+#[test]
+fn registration() {
+    let mut store = DataStore::new(InstanceKey::name(), Default::default());
+    let cache = QueryCache::default();
+    assert!(!cache.is_registered(&store));
+
+    cache.register(&mut store);
+    cache.register(&mut store);
+    assert!(cache.is_registered(&store));
+
+    // Registering with another store clears the cache.
+    let ent_path: EntityPath = "point".into();
+    insert_points(&mut store, &ent_path, 100, 1.0);
+    assert!(query_points(&cache, &store, &ent_path, 200).is_some());
+    assert_eq!(1, cache.stats().num_entries);
+
+    let mut other_store = DataStore::new(InstanceKey::name(), Default::default());
+    cache.register(&mut other_store);
+    assert!(!cache.is_registered(&store));
+    assert!(cache.is_registered(&other_store));
+    assert_eq!(0, cache.stats().num_entries);
+    assert!(query_points(&cache, &other_store, &ent_path, 200).is_none());
+}
+
+#[test]
+fn validity_ranges() {
+    let mut store = DataStore::new(InstanceKey::name(), Default::default());
+    let cache = QueryCache::default();
+    cache.register(&mut store);
+
+    let ent_path: EntityPath = "point".into();
+    insert_points(&mut store, &ent_path, 100, 1.0);
+    insert_points(&mut store, &ent_path, 300, 3.0);
+
+    let points = |x| Some(vec![Point2D { x, y: 2.0 }, Point2D { x, y: 4.0 }]);
+
+    // A result holds from the data it was read from, up to the next change.
+    assert_eq!(points(1.0), query_points(&cache, &store, &ent_path, 150));
+    assert_eq!(points(1.0), query_points(&cache, &store, &ent_path, 100));
+    assert_eq!(points(1.0), query_points(&cache, &store, &ent_path, 299));
+    assert_eq!((2, 1), (cache.stats().hits, cache.stats().misses));
+
+    assert_eq!(points(3.0), query_points(&cache, &store, &ent_path, 300));
+    assert_eq!(points(3.0), query_points(&cache, &store, &ent_path, 1000));
+    assert_eq!((3, 2), (cache.stats().hits, cache.stats().misses));
+
+    // New data within the validity range only affects the query times that come after it.
+    assert_eq!(points(1.0), query_points(&cache, &store, &ent_path, 150));
+    insert_points(&mut store, &ent_path, 200, 2.0);
+    assert_eq!(0, cache.stats().invalidations);
+    assert_eq!(points(1.0), query_points(&cache, &store, &ent_path, 199));
+    assert_eq!((4, 3), (cache.stats().hits, cache.stats().misses));
+    assert_eq!(points(2.0), query_points(&cache, &store, &ent_path, 250));
+    assert_eq!((4, 4), (cache.stats().hits, cache.stats().misses));
+
+    // New data for any of the queried components that comes before the validity range might
+    // affect all of it.
+    let colors = vec![ColorRGBA(0xff0000ff), ColorRGBA(0x00ff00ff)];
+    let row = DataRow::from_cells1(
+        MsgId::random(),
+        ent_path.clone(),
+        [build_frame_nr(120.into())],
+        2,
+        colors,
+    );
+    store.insert_row(&row).unwrap();
+    assert_eq!(1, cache.stats().invalidations);
+    assert_eq!(points(2.0), query_points(&cache, &store, &ent_path, 250));
+    assert_eq!((4, 5), (cache.stats().hits, cache.stats().misses));
+}
@@ -0,0 +1,143 @@
+use re_arrow_store::{DataStore, LatestAtQuery, TimeInt};
+use re_log_types::{
+    component_types::{InstanceKey, Point3D, Quaternion, Rigid3, Transform, Vec3D},
+    datagen::build_frame_nr,
+    Component, DataRow, MsgId, Timeline,
+};
+use re_query::{get_component_with_instances_interpolated, query_latest_single_interpolated};
+
+#[test]
+fn interpolate_points() {
+    let mut store = DataStore::new(InstanceKey::name(), Default::default());
+
+    let ent_path = "points";
+
+    // Instance #2 disappears in the second sample, and instance #3 appears.
+    let samples = [
+        (
+            10,
+            vec![InstanceKey(1), InstanceKey(2)],
+            vec![Point3D::new(0.0, 0.0, 0.0), Point3D::new(1.0, 1.0, 1.0)],
+        ),
+        (
+            20,
+            vec![InstanceKey(1), InstanceKey(3)],
+            vec![Point3D::new(10.0, 20.0, 30.0), Point3D::new(5.0, 5.0, 5.0)],
+        ),
+    ];
+    for (frame_nr, instances, points) in samples {
+        let row = DataRow::from_cells2(
+            MsgId::random(),
+            ent_path,
+            [build_frame_nr(frame_nr.into())],
+            instances.len() as _,
+            (instances, points),
+        );
+        store.insert_row(&row).unwrap();
+    }
+
+    let query_at = |frame_nr: i64| {
+        let query = LatestAtQuery::new(Timeline::new_sequence("frame_nr"), frame_nr.into());
+        let component =
+            get_component_with_instances_interpolated::<Point3D>(&store, &query, &ent_path.into())
+                .unwrap();
+        component
+            .iter_instance_keys()
+            .unwrap()
+            .zip(component.iter_values::<Point3D>().unwrap())
+            .map(|(instance_key, point)| (instance_key, point.unwrap()))
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        vec![
+            (InstanceKey(1), Point3D::new(5.0, 10.0, 15.0)),
+            (InstanceKey(2), Point3D::new(1.0, 1.0, 1.0)),
+        ],
+        query_at(15),
+    );
+
+    // Right on a sample.
+    assert_eq!(
+        vec![
+            (InstanceKey(1), Point3D::new(0.0, 0.0, 0.0)),
+            (InstanceKey(2), Point3D::new(1.0, 1.0, 1.0)),
+        ],
+        query_at(10),
+    );
+
+    // Past the last sample, there's nothing to interpolate with.
+    assert_eq!(
+        vec![
+            (InstanceKey(1), Point3D::new(10.0, 20.0, 30.0)),
+            (InstanceKey(3), Point3D::new(5.0, 5.0, 5.0)),
+        ],
+        query_at(25),
+    );
+}
+
+#[test]
+fn interpolate_transforms() {
+    let mut store = DataStore::new(InstanceKey::name(), Default::default());
+
+    let ent_path = "camera";
+
+    let half_sqrt = std::f32::consts::FRAC_1_SQRT_2;
+    let samples = [
+        (
+            0,
+            Transform::Rigid3(Rigid3 {
+                rotation: Quaternion::IDENTITY,
+                translation: Vec3D::new(0.0, 0.0, 0.0),
+            }),
+        ),
+        (
+            // A quarter turn around Z.
+            10,
+            Transform::Rigid3(Rigid3 {
+                rotation: Quaternion::new(0.0, 0.0, half_sqrt, half_sqrt),
+                translation: Vec3D::new(10.0, 0.0, 0.0),
+            }),
+        ),
+        (20, Transform::Unknown),
+    ];
+    for (frame_nr, transform) in samples {
+        let row = DataRow::from_cells1(
+            MsgId::random(),
+            ent_path,
+            [build_frame_nr(frame_nr.into())],
+            1,
+            vec![transform],
+        );
+        store.insert_row(&row).unwrap();
+    }
+
+    let query_at = |frame_nr: i64| {
+        let query = LatestAtQuery::new(Timeline::new_sequence("frame_nr"), TimeInt::from(frame_nr));
+        query_latest_single_interpolated::<Transform>(&store, &query, &ent_path.into()).unwrap()
+    };
+
+    // Halfway through: an eighth of a turn.
+    let Transform::Rigid3(rigid) = query_at(5) else { panic!("expected a rigid transform") };
+    let expected = Quaternion::new(
+        0.0,
+        0.0,
+        (std::f32::consts::PI / 8.0).sin(),
+        (std::f32::consts::PI / 8.0).cos(),
+    );
+    for (actual, expected) in [
+        (rigid.rotation.x, expected.x),
+        (rigid.rotation.y, expected.y),
+        (rigid.rotation.z, expected.z),
+        (rigid.rotation.w, expected.w),
+    ] {
+        assert!((actual - expected).abs() < 1e-6, "{rigid:?}");
+    }
+    assert_eq!(Vec3D::new(5.0, 0.0, 0.0), rigid.translation);
+
+    // Non-rigid transforms cannot be interpolated.
+    assert!(
+        matches!(query_at(15), Transform::Rigid3(rigid) if rigid.translation == Vec3D::new(10.0, 0.0, 0.0))
+    );
+    assert_eq!(Transform::Unknown, query_at(20));
+}
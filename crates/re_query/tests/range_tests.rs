@@ -5,9 +5,9 @@ use re_log_types::{
     component_types::InstanceKey,
     component_types::{ColorRGBA, Point2D},
     datagen::build_frame_nr,
-    Component, DataRow, EntityPath, MsgId,
+    Component, DataRow, EntityPath, MsgId, Timeline,
 };
-use re_query::range_entity_with_primary;
+use re_query::{range_entity_with_primary, range_instance_history};
 
 #[test]
 fn simple_range() {
@@ -666,48 +666,7 @@ fn simple_splatted_range() {
 
     let ent_path: EntityPath = "point".into();
 
-    let timepoint1 = [build_frame_nr(123.into())];
-    {
-        // Create some points with implicit instances
-        let points = vec![Point2D { x: 1.0, y: 2.0 }, Point2D { x: 3.0, y: 4.0 }];
-        let row = DataRow::from_cells1(MsgId::random(), ent_path.clone(), timepoint1, 2, points);
-        store.insert_row(&row).unwrap();
-
-        // Assign one of them a color with an explicit instance
-        let color_instances = vec![InstanceKey(1)];
-        let colors = vec![ColorRGBA(0xff000000)];
-        let row = DataRow::from_cells2(
-            MsgId::random(),
-            ent_path.clone(),
-            timepoint1,
-            1,
-            (color_instances, colors),
-        );
-        store.insert_row(&row).unwrap();
-    }
-
-    let timepoint2 = [build_frame_nr(223.into())];
-    {
-        // Assign one of them a color with a splatted instance
-        let color_instances = vec![InstanceKey::SPLAT];
-        let colors = vec![ColorRGBA(0x00ff0000)];
-        let row = DataRow::from_cells2(
-            MsgId::random(),
-            ent_path.clone(),
-            timepoint2,
-            1,
-            (color_instances, colors),
-        );
-        store.insert_row(&row).unwrap();
-    }
-
-    let timepoint3 = [build_frame_nr(323.into())];
-    {
-        // Create some points with implicit instances
-        let points = vec![Point2D { x: 10.0, y: 20.0 }, Point2D { x: 30.0, y: 40.0 }];
-        let row = DataRow::from_cells1(MsgId::random(), ent_path.clone(), timepoint3, 2, points);
-        store.insert_row(&row).unwrap();
-    }
+    let [timepoint1, _, timepoint3] = insert_splatted_points(&mut store, &ent_path);
 
     // --- First test: `(timepoint1, timepoint3]` ---
 
@@ -877,3 +836,111 @@ fn simple_splatted_range() {
         _ = results;
     }
 }
+
+#[test]
+fn instance_history() {
+    fn test(store: &DataStore, ent_path: &EntityPath) {
+        let query = re_arrow_store::RangeQuery::new(
+            build_frame_nr(0.into()).0,
+            TimeRange::new(TimeInt::MIN, TimeInt::MAX),
+        );
+
+        let points = range_instance_history::<Point2D>(store, &query, ent_path, InstanceKey(1))
+            .map(|(time, point)| (time.unwrap().as_i64(), point))
+            .collect::<Vec<_>>();
+        let expected = vec![
+            (123, Point2D { x: 3.0, y: 4.0 }),
+            (323, Point2D { x: 30.0, y: 40.0 }),
+        ];
+        assert_eq!(expected, points);
+
+        let colors = range_instance_history::<ColorRGBA>(store, &query, ent_path, InstanceKey(1))
+            .map(|(time, color)| (time.unwrap().as_i64(), color))
+            .collect::<Vec<_>>();
+        let expected = vec![(123, ColorRGBA(0xff000000)), (223, ColorRGBA(0x00ff0000))];
+        assert_eq!(expected, colors);
+
+        // Instance #0 only ever gets colored through a splat.
+        let colors = range_instance_history::<ColorRGBA>(store, &query, ent_path, InstanceKey(0))
+            .map(|(time, color)| (time.unwrap().as_i64(), color))
+            .collect::<Vec<_>>();
+        let expected = vec![(223, ColorRGBA(0x00ff0000))];
+        assert_eq!(expected, colors);
+
+        // Instance #2 doesn't exist at all.
+        let points = range_instance_history::<Point2D>(store, &query, ent_path, InstanceKey(2));
+        assert_eq!(0, points.count());
+    }
+
+    let ent_path: EntityPath = "point".into();
+
+    // Scanning every row...
+    let mut store = DataStore::new(InstanceKey::name(), Default::default());
+    insert_splatted_points(&mut store, &ent_path);
+    test(&store, &ent_path);
+
+    // ...and going through the index of the cluster key must yield the exact same results,
+    // whether it was built from existing data...
+    store.enable_value_index(InstanceKey::name()).unwrap();
+    test(&store, &ent_path);
+
+    // ...or kept up to date during insertion.
+    let mut store = DataStore::new(InstanceKey::name(), Default::default());
+    store.enable_value_index(InstanceKey::name()).unwrap();
+    insert_splatted_points(&mut store, &ent_path);
+    test(&store, &ent_path);
+}
+
+// ---
+
+/// Points logged at frames #123 and #323, colored through both explicit and splatted instances
+/// in between. Returns the timepoints of the three frames involved.
+fn insert_splatted_points(
+    store: &mut DataStore,
+    ent_path: &EntityPath,
+) -> [[(Timeline, TimeInt); 1]; 3] {
+    let timepoint1 = [build_frame_nr(123.into())];
+    {
+        // Create some points with implicit instances
+        let points = vec![Point2D { x: 1.0, y: 2.0 }, Point2D { x: 3.0, y: 4.0 }];
+        let row = DataRow::from_cells1(MsgId::random(), ent_path.clone(), timepoint1, 2, points);
+        store.insert_row(&row).unwrap();
+
+        // Assign one of them a color with an explicit instance
+        let color_instances = vec![InstanceKey(1)];
+        let colors = vec![ColorRGBA(0xff000000)];
+        let row = DataRow::from_cells2(
+            MsgId::random(),
+            ent_path.clone(),
+            timepoint1,
+            1,
+            (color_instances, colors),
+        );
+        store.insert_row(&row).unwrap();
+    }
+
+    let timepoint2 = [build_frame_nr(223.into())];
+    {
+        // Assign one of them a color with a splatted instance
+        let color_instances = vec![InstanceKey::SPLAT];
+        let colors = vec![ColorRGBA(0x00ff0000)];
+        let row = DataRow::from_cells2(
+            MsgId::random(),
+            ent_path.clone(),
+            timepoint2,
+            1,
+            (color_instances, colors),
+        );
+        store.insert_row(&row).unwrap();
+    }
+
+    let timepoint3 = [build_frame_nr(323.into())];
+    {
+        // Create some points with implicit instances
+        let points = vec![Point2D { x: 10.0, y: 20.0 }, Point2D { x: 30.0, y: 40.0 }];
+        let row = DataRow::from_cells1(MsgId::random(), ent_path.clone(), timepoint3, 2, points);
+        store.insert_row(&row).unwrap();
+    }
+
+    [timepoint1, timepoint2, timepoint3]
+}
@@ -38,6 +38,13 @@ impl crate::sink::LogSink for re_log_encoding::FileSink {
     }
 }
 
+#[cfg(not(target_arch = "wasm32"))]
+impl crate::sink::LogSink for re_log_encoding::StdoutSink {
+    fn send(&self, msg: re_log_types::LogMsg) {
+        re_log_encoding::StdoutSink::send(self, msg);
+    }
+}
+
 // ---------------
 // Public modules:
 
@@ -50,9 +57,15 @@ pub mod demo_util;
 /// sent over TCP, written to file, etc.
 pub mod sink {
     pub use crate::log_sink::{disabled, BufferedSink, LogSink, TcpSink};
+    pub use re_sdk_comms::PacketEncoding;
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub use re_log_encoding::{FileRotation, FileSink, SinkError, StdoutSink};
 
     #[cfg(not(target_arch = "wasm32"))]
-    pub use re_log_encoding::{FileSink, FileSinkError};
+    /// The error type of [`FileSink`], now shared with [`StdoutSink`].
+    #[deprecated = "renamed to `SinkError`, which is shared with `StdoutSink`"]
+    pub type FileSinkError = SinkError;
 }
 
 /// Things directly related to logging.
@@ -91,6 +91,17 @@ impl TcpSink {
             client: re_sdk_comms::Client::new(addr),
         }
     }
+
+    /// Like [`Self::new`], but encodes messages with the given
+    /// [`PacketEncoding`](re_sdk_comms::PacketEncoding).
+    pub fn with_packet_encoding(
+        addr: std::net::SocketAddr,
+        encoding: re_sdk_comms::PacketEncoding,
+    ) -> Self {
+        Self {
+            client: re_sdk_comms::Client::with_packet_encoding(addr, encoding),
+        }
+    }
 }
 
 impl LogSink for TcpSink {
@@ -133,12 +133,36 @@ impl SessionBuilder {
     pub fn save(
         self,
         path: impl Into<std::path::PathBuf>,
-    ) -> Result<Session, crate::sink::FileSinkError> {
+    ) -> Result<Session, crate::sink::SinkError> {
+        self.save_with_rotation(path, Default::default())
+    }
+
+    /// Stream all log messages to a series of `.rrd` files, moving on to a new file once the
+    /// current one gets too large or too old.
+    ///
+    /// Every file can be opened on its own. See [`crate::sink::FileRotation`] for details.
+    ///
+    /// ``` no_run
+    /// # use re_sdk::{SessionBuilder, sink::FileRotation};
+    /// let rotation = FileRotation {
+    ///     max_bytes: Some(1024 * 1024 * 1024),
+    ///     max_files: Some(10),
+    ///     ..Default::default()
+    /// };
+    /// let session = SessionBuilder::new("my_app").save_with_rotation("my_recording.rrd", rotation)?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn save_with_rotation(
+        self,
+        path: impl Into<std::path::PathBuf>,
+        rotation: crate::sink::FileRotation,
+    ) -> Result<Session, crate::sink::SinkError> {
         let (rerun_enabled, recording_info) = self.finalize();
         if rerun_enabled {
             Ok(Session::new(
                 recording_info,
-                Box::new(crate::sink::FileSink::new(path)?),
+                Box::new(crate::sink::FileSink::with_rotation(path, rotation)?),
             ))
         } else {
             re_log::debug!("Rerun disabled - call to save() ignored");
@@ -146,6 +170,27 @@ impl SessionBuilder {
         }
     }
 
+    /// Stream all log messages to standard output, e.g. to pipe them into `rerun -`.
+    ///
+    /// ``` no_run
+    /// # use re_sdk::SessionBuilder;
+    /// let session = SessionBuilder::new("my_app").stdout()?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn stdout(self) -> Result<Session, crate::sink::SinkError> {
+        let (rerun_enabled, recording_info) = self.finalize();
+        if rerun_enabled {
+            Ok(Session::new(
+                recording_info,
+                Box::new(crate::sink::StdoutSink::new()?),
+            ))
+        } else {
+            re_log::debug!("Rerun disabled - call to stdout() ignored");
+            Ok(Session::disabled())
+        }
+    }
+
     /// Returns whether or not logging is enabled, plus a [`RecordingInfo`].
     ///
     /// This can be used to then construct a [`Session`] manually using [`Session::new`].
@@ -4,6 +4,8 @@ use crossbeam::channel::{select, Receiver, Sender};
 
 use re_log_types::{LogMsg, MsgId};
 
+use crate::PacketEncoding;
+
 #[derive(Debug, PartialEq, Eq)]
 struct FlushedMsg;
 
@@ -58,7 +60,12 @@ impl Default for Client {
 impl Client {
     /// Connect via TCP to this log server.
     pub fn new(addr: SocketAddr) -> Self {
-        re_log::debug!("Connecting to remote {addr}…");
+        Self::with_packet_encoding(addr, PacketEncoding::default())
+    }
+
+    /// Connect via TCP to this log server, encoding messages with the given [`PacketEncoding`].
+    pub fn with_packet_encoding(addr: SocketAddr, encoding: PacketEncoding) -> Self {
+        re_log::debug!("Connecting to remote {addr} using {encoding:?}…");
 
         // TODO(emilk): keep track of how much memory is in each pipe
         // and apply back-pressure to not use too much RAM.
@@ -73,7 +80,7 @@ impl Client {
         let encode_join = std::thread::Builder::new()
             .name("msg_encoder".into())
             .spawn(move || {
-                msg_encode(&msg_rx, &msg_drop_tx, &encode_quit_rx, &packet_tx);
+                msg_encode(encoding, &msg_rx, &msg_drop_tx, &encode_quit_rx, &packet_tx);
                 re_log::debug!("Shutting down msg encoder thread");
             })
             .expect("Failed to spawn thread");
@@ -178,6 +185,7 @@ fn msg_drop(msg_drop_rx: &Receiver<MsgMsg>, quit_rx: &Receiver<QuitMsg>) {
 }
 
 fn msg_encode(
+    encoding: PacketEncoding,
     msg_rx: &Receiver<MsgMsg>,
     msg_drop_tx: &Sender<MsgMsg>,
     quit_rx: &Receiver<QuitMsg>,
@@ -189,16 +197,25 @@ fn msg_encode(
                 if let Ok(msg_msg) = msg_msg {
                     let packet_msg = match &msg_msg {
                         MsgMsg::LogMsg(log_msg) => {
-                            let packet = crate::encode_log_msg(log_msg);
-                            re_log::trace!("Encoded message of size {}", packet.len());
-                            PacketMsg::Packet(packet)
+                            match crate::encode_log_msg_with(log_msg, encoding) {
+                                Ok(packet) => {
+                                    re_log::trace!("Encoded message of size {}", packet.len());
+                                    Some(PacketMsg::Packet(packet))
+                                }
+                                Err(err) => {
+                                    re_log::error!("Failed to encode message: {err:#}");
+                                    None
+                                }
+                            }
                         }
-                        MsgMsg::Flush => PacketMsg::Flush,
+                        MsgMsg::Flush => Some(PacketMsg::Flush),
                     };
 
-                    packet_tx
-                        .send(packet_msg)
-                        .expect("tcp_sender thread should live longer");
+                    if let Some(packet_msg) = packet_msg {
+                        packet_tx
+                            .send(packet_msg)
+                            .expect("tcp_sender thread should live longer");
+                    }
 
                     msg_drop_tx.send(msg_msg).expect("Main thread should still be alive");
                 } else {
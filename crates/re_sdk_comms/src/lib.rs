@@ -32,8 +32,28 @@ pub fn default_server_addr() -> std::net::SocketAddr {
     std::net::SocketAddr::from(([127, 0, 0, 1], DEFAULT_SERVER_PORT))
 }
 
+/// Prefix of packets holding a bincode-encoded [`re_log_types::LogMsg`].
 const PREFIX: [u8; 4] = *b"RR00";
 
+/// Prefix of packets holding a [`re_log_types::LogMsg`] serialized with
+/// [`re_log_encoding::Serializer::ArrowIpc`].
+const PREFIX_ARROW_IPC: [u8; 4] = *b"RR01";
+
+/// How [`re_log_types::LogMsg`]es are encoded into packets.
+///
+/// The server understands all of them, so this only needs to be picked on the client side.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PacketEncoding {
+    /// Everything is encoded with bincode.
+    #[default]
+    Bincode,
+
+    /// The payloads of [`re_log_types::ArrowMsg`]s are sent as native Arrow IPC streams.
+    ///
+    /// Servers older than this option will refuse these packets.
+    ArrowIpc,
+}
+
 pub fn encode_log_msg(log_msg: &LogMsg) -> Vec<u8> {
     use bincode::Options as _;
     let mut bytes = PREFIX.to_vec();
@@ -43,14 +63,39 @@ pub fn encode_log_msg(log_msg: &LogMsg) -> Vec<u8> {
     bytes
 }
 
-pub fn decode_log_msg(data: &[u8]) -> Result<LogMsg> {
-    let payload = data
-        .strip_prefix(&PREFIX)
-        .ok_or_else(|| anyhow::format_err!("Message didn't start with the correct prefix"))?;
+/// Same as [`encode_log_msg`], with the given [`PacketEncoding`].
+///
+/// Unlike bincode, Arrow IPC can fail to serialize some messages.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn encode_log_msg_with(log_msg: &LogMsg, encoding: PacketEncoding) -> Result<Vec<u8>> {
+    match encoding {
+        PacketEncoding::Bincode => Ok(encode_log_msg(log_msg)),
+        PacketEncoding::ArrowIpc => {
+            use anyhow::Context as _;
+            let mut bytes = PREFIX_ARROW_IPC.to_vec();
+            re_log_encoding::serializer::encode_log_msg(
+                re_log_encoding::Serializer::ArrowIpc,
+                log_msg,
+                &mut bytes,
+            )
+            .context("Arrow IPC")?;
+            Ok(bytes)
+        }
+    }
+}
 
+pub fn decode_log_msg(data: &[u8]) -> Result<LogMsg> {
     use anyhow::Context as _;
-    use bincode::Options as _;
-    bincode::DefaultOptions::new()
-        .deserialize(payload)
-        .context("bincode")
+
+    if let Some(payload) = data.strip_prefix(&PREFIX) {
+        use bincode::Options as _;
+        bincode::DefaultOptions::new()
+            .deserialize(payload)
+            .context("bincode")
+    } else if let Some(payload) = data.strip_prefix(&PREFIX_ARROW_IPC) {
+        re_log_encoding::serializer::decode_log_msg(re_log_encoding::Serializer::ArrowIpc, payload)
+            .context("Arrow IPC")
+    } else {
+        anyhow::bail!("Message didn't start with the correct prefix")
+    }
 }
@@ -18,6 +18,9 @@ pub enum Source {
     /// Streaming an `.rrd` file over http.
     RrdHttpStream { url: String },
 
+    /// Streaming an `.rrd` file from standard input, e.g. `rerun -`.
+    Stdin,
+
     /// The source is the logging sdk directly, same process.
     Sdk,
 
@@ -36,7 +39,7 @@ pub enum Source {
 impl Source {
     pub fn is_network(&self) -> bool {
         match self {
-            Self::File { .. } | Self::Sdk => false,
+            Self::File { .. } | Self::Stdin | Self::Sdk => false,
             Self::RrdHttpStream { .. } | Self::WsClient { .. } | Self::TcpServer { .. } => true,
         }
     }
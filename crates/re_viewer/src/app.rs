@@ -41,9 +41,15 @@ enum TimeControlCommand {
 // ----------------------------------------------------------------------------
 
 /// Settings set once at startup (e.g. via command-line options) and not serialized.
-#[derive(Clone, Copy, Default)]
+#[derive(Clone, Default)]
 pub struct StartupOptions {
     pub memory_limit: re_memory::MemoryLimit,
+
+    /// Where to spill old data to, instead of keeping it all in RAM.
+    ///
+    /// Each recording gets its own sub-directory.
+    /// See [`re_arrow_store::DataStore::enable_spilling`].
+    pub spill_directory: Option<std::path::PathBuf>,
 }
 
 // ----------------------------------------------------------------------------
@@ -70,6 +76,9 @@ pub struct App {
     /// Where the logs are stored.
     log_dbs: IntMap<RecordingId, LogDb>,
 
+    /// Cached query results for each recording, kept up to date by their stores.
+    query_caches: IntMap<RecordingId, re_query::QueryCache>,
+
     /// What is serialized
     state: AppState,
 
@@ -134,6 +143,7 @@ impl App {
             component_ui_registry: Default::default(),
             rx,
             log_dbs: Default::default(),
+            query_caches: Default::default(),
             state,
             shutdown,
             pending_promises: Default::default(),
@@ -464,7 +474,7 @@ impl eframe::App for App {
             render_ctx.gpu_resources.statistics()
         };
 
-        let store_stats = DataStoreStats::from_store(&self.log_db().entity_db.data_store);
+        let store_stats = DataStoreStats::from_store(&self.log_db().entity_db.data_store.read());
 
         self.memory_panel.update(&gpu_resource_stats, &store_stats); // do first, before doing too many allocations
 
@@ -498,6 +508,14 @@ impl eframe::App for App {
                 self.memory_panel_ui(ui, &gpu_resource_stats, &store_stats);
 
                 let log_db = self.log_dbs.entry(self.state.selected_rec_id).or_default();
+                let query_cache = self
+                    .query_caches
+                    .entry(self.state.selected_rec_id)
+                    .or_default();
+                // Only lock the store for writing when there's actually something to do.
+                if !query_cache.is_registered(&log_db.entity_db.data_store.read()) {
+                    query_cache.register(&mut log_db.entity_db.data_store.write());
+                }
                 let selected_app_id = log_db
                     .recording_info()
                     .map_or_else(ApplicationId::unknown, |rec_info| {
@@ -537,6 +555,7 @@ impl eframe::App for App {
                             ui,
                             render_ctx,
                             log_db,
+                            query_cache,
                             &self.re_ui,
                             &self.component_ui_registry,
                             self.rx.source(),
@@ -629,6 +648,9 @@ fn wait_screen_ui(ui: &mut egui::Ui, rx: &Receiver<LogMsg>) {
             re_smart_channel::Source::RrdHttpStream { url } => {
                 ui.strong(format!("Loading {url}…"));
             }
+            re_smart_channel::Source::Stdin => {
+                ready_and_waiting(ui, "Waiting for data on stdin");
+            }
             re_smart_channel::Source::Sdk => {
                 ready_and_waiting(ui, "Waiting for logging data from SDK");
             }
@@ -687,7 +709,10 @@ impl App {
                     false
                 };
 
-                let log_db = self.log_dbs.entry(*recording_id).or_default();
+                let log_db = self
+                    .log_dbs
+                    .entry(*recording_id)
+                    .or_insert_with(|| new_log_db(&self.startup_options, *recording_id));
 
                 if log_db.data_source.is_none() {
                     log_db.data_source = Some(self.rx.source().clone());
@@ -710,12 +735,24 @@ impl App {
                 }
             }
         }
+
+        // Rows are inserted into the store in the background: keep repainting until all the
+        // data received so far shows up.
+        if self
+            .log_dbs
+            .values()
+            .any(|log_db| log_db.entity_db.has_pending_rows())
+        {
+            egui_ctx.request_repaint();
+        }
     }
 
     fn cleanup(&mut self) {
         crate::profile_function!();
 
         self.log_dbs.retain(|_, log_db| !log_db.is_empty());
+        self.query_caches
+            .retain(|recording_id, _| self.log_dbs.contains_key(recording_id));
 
         if !self.log_dbs.contains_key(&self.state.selected_rec_id) {
             self.state.selected_rec_id = self.log_dbs.keys().next().cloned().unwrap_or_default();
@@ -946,6 +983,7 @@ impl AppState {
         ui: &mut egui::Ui,
         render_ctx: &mut re_renderer::RenderContext,
         log_db: &LogDb,
+        query_cache: &re_query::QueryCache,
         re_ui: &re_ui::ReUi,
         component_ui_registry: &ComponentUiRegistry,
         data_source: &re_smart_channel::Source,
@@ -979,6 +1017,7 @@ impl AppState {
             cache,
             component_ui_registry,
             log_db,
+            query_cache,
             rec_cfg,
             re_ui,
             render_ctx,
@@ -1802,16 +1841,64 @@ fn save_database_to_file(
     }
 }
 
+/// Creates an empty [`LogDb`] for the given recording, spilling to disk if so configured.
+fn new_log_db(startup_options: &StartupOptions, recording_id: RecordingId) -> LogDb {
+    let log_db = LogDb::default();
+
+    if let Some(spill_directory) = &startup_options.spill_directory {
+        let directory = spill_directory.join(recording_id.to_string());
+        if let Err(err) = log_db
+            .entity_db
+            .data_store
+            .write()
+            .enable_spilling(&directory)
+        {
+            re_log::warn!(
+                "Failed to enable spilling to {}, keeping all data in RAM: {}",
+                directory.display(),
+                re_error::format(anyhow::Error::from(err))
+            );
+        }
+    }
+
+    log_db
+}
+
+/// Loads as much as possible from the given `.rrd` file, warning about any data that had to be
+/// skipped, e.g. because the process writing the file crashed.
 #[allow(unused_mut)]
-fn load_rrd_to_log_db(mut read: impl std::io::Read) -> anyhow::Result<LogDb> {
+fn load_rrd_to_log_db(name: &str, mut read: impl std::io::Read) -> anyhow::Result<LogDb> {
     crate::profile_function!();
 
-    let decoder = re_log_encoding::decoder::Decoder::new(read)?;
+    let mut decoder = re_log_encoding::decoder::Decoder::new_recovering(read)?;
 
     let mut log_db = LogDb::default();
-    for msg in decoder {
+    for msg in decoder.by_ref() {
         log_db.add(msg?)?;
     }
+
+    if let Some(report) = decoder.recovery_report() {
+        if !report.is_clean() {
+            let mut msg = format!(
+                "{name:?} is damaged. Loaded {} messages, but {} of data could not be decoded:",
+                re_format::format_number(report.num_messages as _),
+                re_format::format_bytes(report.num_bytes_skipped() as _),
+            );
+            for skipped in &report.skipped {
+                msg += &format!(
+                    "\n• {}: {}",
+                    re_format::format_bytes(skipped.num_bytes as _),
+                    skipped.error
+                );
+            }
+            re_log::warn!("{msg}");
+            rfd::MessageDialog::new()
+                .set_level(rfd::MessageLevel::Warning)
+                .set_description(&msg)
+                .show();
+        }
+    }
+
     Ok(log_db)
 }
 
@@ -1822,7 +1909,7 @@ fn load_file_path(path: &std::path::Path) -> Option<LogDb> {
         crate::profile_function!();
         use anyhow::Context as _;
         let file = std::fs::File::open(path).context("Failed to open file")?;
-        load_rrd_to_log_db(file)
+        load_rrd_to_log_db(&path.to_string_lossy(), file)
     }
 
     re_log::info!("Loading {path:?}…");
@@ -1847,7 +1934,7 @@ fn load_file_path(path: &std::path::Path) -> Option<LogDb> {
 
 #[must_use]
 fn load_file_contents(name: &str, read: impl std::io::Read) -> Option<LogDb> {
-    match load_rrd_to_log_db(read) {
+    match load_rrd_to_log_db(name, read) {
         Ok(mut log_db) => {
             re_log::info!("Loaded {name:?}");
             log_db.data_source = Some(re_smart_channel::Source::File { path: name.into() });
@@ -1890,7 +1977,8 @@ fn new_recording_confg(
         }
 
         // Live data - follow it!
-        re_smart_channel::Source::Sdk
+        re_smart_channel::Source::Stdin
+        | re_smart_channel::Source::Sdk
         | re_smart_channel::Source::WsClient { .. }
         | re_smart_channel::Source::TcpServer { .. } => PlayState::Following,
     };
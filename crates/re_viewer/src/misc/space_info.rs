@@ -274,7 +274,7 @@ pub fn query_view_coordinates(
     ent_path: &EntityPath,
     query: &LatestAtQuery,
 ) -> Option<re_log_types::ViewCoordinates> {
-    let data_store = &entity_db.data_store;
+    let data_store = &entity_db.data_store.read();
 
     let entity_view =
         query_entity_with_primary::<ViewCoordinates>(data_store, query, ent_path, &[]).ok()?;
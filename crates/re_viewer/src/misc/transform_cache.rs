@@ -3,6 +3,7 @@ use re_arrow_store::LatestAtQuery;
 use re_data_store::{
     log_db::EntityDb, query_latest_single, EntityPath, EntityPropertyMap, EntityTree,
 };
+use re_log_types::Transform;
 
 use crate::misc::TimeControl;
 
@@ -120,6 +121,7 @@ impl TransformCache {
             match inverse_transform_at(
                 &current_tree.path,
                 entity_db,
+                entity_prop_map,
                 &query,
                 &mut encountered_pinhole,
             ) {
@@ -217,6 +219,24 @@ impl TransformCache {
     // }
 }
 
+/// Queries the transform of an entity, interpolating it if the entity opted into it.
+fn query_transform(
+    entity_path: &EntityPath,
+    entity_db: &EntityDb,
+    entity_properties: &EntityPropertyMap,
+    query: &LatestAtQuery,
+) -> Option<Transform> {
+    if *entity_properties
+        .get(entity_path)
+        .interpolate_transforms
+        .get()
+    {
+        re_query::query_latest_single_interpolated(&entity_db.data_store.read(), query, entity_path)
+    } else {
+        query_latest_single(entity_db, entity_path, query)
+    }
+}
+
 fn transform_at(
     entity_path: &EntityPath,
     entity_db: &EntityDb,
@@ -224,7 +244,7 @@ fn transform_at(
     query: &LatestAtQuery,
     encountered_pinhole: &mut bool,
 ) -> Result<Option<macaw::Mat4>, UnreachableTransform> {
-    if let Some(transform) = query_latest_single(entity_db, entity_path, query) {
+    if let Some(transform) = query_transform(entity_path, entity_db, entity_properties, query) {
         match transform {
             re_log_types::Transform::Rigid3(rigid) => Ok(Some(rigid.parent_from_child().to_mat4())),
             // If we're connected via 'unknown' it's not reachable
@@ -266,10 +286,13 @@ fn transform_at(
 fn inverse_transform_at(
     entity_path: &EntityPath,
     entity_db: &EntityDb,
+    entity_properties: &EntityPropertyMap,
     query: &LatestAtQuery,
     encountered_pinhole: &mut bool,
 ) -> Result<Option<macaw::Mat4>, UnreachableTransform> {
-    if let Some(parent_transform) = query_latest_single(entity_db, entity_path, query) {
+    if let Some(parent_transform) =
+        query_transform(entity_path, entity_db, entity_properties, query)
+    {
         match parent_transform {
             re_log_types::Transform::Rigid3(rigid) => Ok(Some(rigid.child_from_parent().to_mat4())),
             // If we're connected via 'unknown', everything except whats under `parent_tree` is unreachable
@@ -25,6 +25,9 @@ pub struct ViewerContext<'a> {
     /// The current recording
     pub log_db: &'a LogDb,
 
+    /// Cached query results for the current recording.
+    pub query_cache: &'a re_query::QueryCache,
+
     /// UI config for the current recording (found in [`LogDb`]).
     pub rec_cfg: &'a mut RecordingConfig,
 
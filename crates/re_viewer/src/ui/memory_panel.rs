@@ -237,6 +237,9 @@ impl MemoryPanel {
                     total_index_size_bytes,
                     total_component_rows,
                     total_component_size_bytes,
+                    total_deduplicated_size_bytes,
+                    total_reclaimed_index_rows,
+                    total_reclaimed_index_size_bytes,
                     config: _,
                 } = *store_stats;
 
@@ -272,6 +275,12 @@ impl MemoryPanel {
                 label_size(ui, total_index_size_bytes);
                 ui.end_row();
 
+                ui.label("Indices (reclaimed by GC):");
+                ui.label("");
+                label_rows(ui, total_reclaimed_index_rows);
+                label_size(ui, total_reclaimed_index_size_bytes);
+                ui.end_row();
+
                 ui.label("Components (timeless):");
                 ui.label("");
                 label_rows(ui, total_timeless_component_rows);
@@ -289,6 +298,12 @@ impl MemoryPanel {
                 label_rows(ui, total_component_rows);
                 label_size(ui, total_component_size_bytes);
                 ui.end_row();
+
+                ui.label("Components (deduplicated):");
+                ui.label("");
+                ui.label("");
+                label_size(ui, total_deduplicated_size_bytes);
+                ui.end_row();
             });
     }
 
@@ -414,6 +414,10 @@ fn entity_props_ui(
             }
             ui.end_row();
 
+            if let Some(entity_path) = entity_path {
+                transform_props_ui(ctx, ui, entity_path, entity_props);
+            }
+
             if *view_state.state_spatial.nav_mode.get() == SpatialNavigationMode::ThreeD {
                 if let Some(entity_path) = entity_path {
                     pinhole_props_ui(ctx, ui, entity_path, entity_props);
@@ -454,6 +458,34 @@ fn colormap_props_ui(ui: &mut egui::Ui, entity_props: &mut EntityProperties) {
     ui.end_row();
 }
 
+fn transform_props_ui(
+    ctx: &mut ViewerContext<'_>,
+    ui: &mut egui::Ui,
+    entity_path: &EntityPath,
+    entity_props: &mut EntityProperties,
+) {
+    let query = ctx.current_query();
+    if let Some(re_log_types::Transform::Rigid3(_)) =
+        query_latest_single::<Transform>(&ctx.log_db.entity_db, entity_path, &query)
+    {
+        let mut interpolate_transforms = *entity_props.interpolate_transforms.get();
+
+        ui.label("Interpolate transform");
+        if ui
+            .checkbox(&mut interpolate_transforms, "")
+            .on_hover_text(
+                "Blend between the transforms logged before and after the current time, \
+                rather than jumping from one to the next.",
+            )
+            .changed()
+        {
+            entity_props.interpolate_transforms =
+                EditableAutoValue::UserEdited(interpolate_transforms);
+        }
+        ui.end_row();
+    }
+}
+
 fn pinhole_props_ui(
     ctx: &mut ViewerContext<'_>,
     ui: &mut egui::Ui,
@@ -51,12 +51,17 @@ fn contains_any_image(
     entity_db: &EntityDb,
     query: &LatestAtQuery,
 ) -> bool {
-    re_query::query_entity_with_primary::<Tensor>(&entity_db.data_store, query, entity_path, &[])
-        .map_or(false, |entity_view| {
-            entity_view
-                .iter_primary_flattened()
-                .any(|tensor| tensor.is_shaped_like_an_image())
-        })
+    re_query::query_entity_with_primary::<Tensor>(
+        &entity_db.data_store.read(),
+        query,
+        entity_path,
+        &[],
+    )
+    .map_or(false, |entity_view| {
+        entity_view
+            .iter_primary_flattened()
+            .any(|tensor| tensor.is_shaped_like_an_image())
+    })
 }
 
 fn is_interesting_space_view_at_root(
@@ -178,7 +183,7 @@ fn default_created_space_views_from_candidates(
             // For this we're only interested in the direct children.
             for entity_path in &candidate.data_blueprint.root_group().entities {
                 if let Ok(entity_view) = re_query::query_entity_with_primary::<Tensor>(
-                    &entity_db.data_store,
+                    &entity_db.data_store.read(),
                     &query,
                     entity_path,
                     &[],
@@ -280,7 +285,7 @@ pub fn default_queried_entities(
 
     let timeline = Timeline::log_time();
     let log_db = &ctx.log_db;
-    let data_store = &log_db.entity_db.data_store;
+    let data_store = &log_db.entity_db.data_store.read();
 
     let mut entities = Vec::new();
     let space_info = spaces_info.get_first_parent_with_info(space_path);
@@ -311,7 +316,7 @@ fn default_queried_entities_by_category(
 
     let timeline = Timeline::log_time();
     let log_db = &ctx.log_db;
-    let data_store = &log_db.entity_db.data_store;
+    let data_store = &log_db.entity_db.data_store.read();
 
     let mut groups: BTreeMap<ViewCategory, Vec<EntityPath>> = BTreeMap::default();
     let space_info = space_info_collection.get_first_parent_with_info(space_path);
@@ -70,9 +70,9 @@ pub fn categorize_entity_path(
 
     let mut set = ViewCategorySet::default();
 
-    for component in log_db
-        .entity_db
-        .data_store
+    let store = log_db.entity_db.data_store.read();
+
+    for component in store
         .all_components(&timeline, entity_path)
         .unwrap_or_default()
     {
@@ -94,12 +94,9 @@ pub fn categorize_entity_path(
         } else if component == Tensor::name() {
             let timeline_query = LatestAtQuery::new(timeline, TimeInt::MAX);
 
-            if let Ok(entity_view) = query_entity_with_primary::<Tensor>(
-                &log_db.entity_db.data_store,
-                &timeline_query,
-                entity_path,
-                &[],
-            ) {
+            if let Ok(entity_view) =
+                query_entity_with_primary::<Tensor>(&store, &timeline_query, entity_path, &[])
+            {
                 if let Ok(iter) = entity_view.iter_primary() {
                     for tensor in iter.flatten() {
                         if tensor.is_vector() {
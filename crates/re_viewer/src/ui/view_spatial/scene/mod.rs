@@ -161,6 +161,7 @@ impl SceneSpatial {
 
         let parts: Vec<&dyn ScenePart> = vec![
             &scene_part::Points3DPart { max_labels: 10 },
+            &scene_part::InstanceTrailsPart,
             // --
             &scene_part::Boxes3DPart,
             &scene_part::Lines3DPart,
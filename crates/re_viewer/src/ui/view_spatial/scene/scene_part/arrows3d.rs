@@ -4,7 +4,7 @@ use re_log_types::{
     component_types::{ColorRGBA, InstanceKey, Label, Radius},
     Arrow3D, Component,
 };
-use re_query::{query_primary_with_history, EntityView, QueryError};
+use re_query::{query_primary_with_history_cached, EntityView, QueryError};
 use re_renderer::Size;
 
 use crate::{
@@ -107,8 +107,9 @@ impl ScenePart for Arrows3DPart {
                 continue;
             };
 
-            match query_primary_with_history::<Arrow3D, 5>(
-                &ctx.log_db.entity_db.data_store,
+            match query_primary_with_history_cached::<Arrow3D, 5>(
+                ctx.query_cache,
+                &ctx.log_db.entity_db.data_store.read(),
                 &query.timeline,
                 &query.latest_at,
                 &props.visible_history,
@@ -4,7 +4,7 @@ use re_log_types::{
     component_types::{ClassId, ColorRGBA, InstanceKey, Label, Radius, Rect2D},
     Component,
 };
-use re_query::{query_primary_with_history, QueryError};
+use re_query::{query_primary_with_history_cached, QueryError};
 use re_renderer::Size;
 
 use crate::{
@@ -104,8 +104,9 @@ impl ScenePart for Boxes2DPart {
 
             let entity_highlight = highlights.entity_outline_mask(ent_path.hash());
 
-            match query_primary_with_history::<Rect2D, 6>(
-                &ctx.log_db.entity_db.data_store,
+            match query_primary_with_history_cached::<Rect2D, 6>(
+                ctx.query_cache,
+                &ctx.log_db.entity_db.data_store.read(),
                 &query.timeline,
                 &query.latest_at,
                 &props.visible_history,
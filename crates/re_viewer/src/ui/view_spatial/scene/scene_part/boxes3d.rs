@@ -5,7 +5,7 @@ use re_log_types::{
     component_types::{Box3D, ClassId, ColorRGBA, InstanceKey, Label, Quaternion, Radius, Vec3D},
     Component,
 };
-use re_query::{query_primary_with_history, EntityView, QueryError};
+use re_query::{query_primary_with_history_cached, EntityView, QueryError};
 use re_renderer::Size;
 
 use crate::{
@@ -109,8 +109,9 @@ impl ScenePart for Boxes3DPart {
             };
             let entity_highlight = highlights.entity_outline_mask(ent_path.hash());
 
-            match query_primary_with_history::<Box3D, 8>(
-                &ctx.log_db.entity_db.data_store,
+            match query_primary_with_history_cached::<Box3D, 8>(
+                ctx.query_cache,
+                &ctx.log_db.entity_db.data_store.read(),
                 &query.timeline,
                 &query.latest_at,
                 &props.visible_history,
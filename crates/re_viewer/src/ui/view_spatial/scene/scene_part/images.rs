@@ -9,7 +9,7 @@ use re_log_types::{
     component_types::{ColorRGBA, InstanceKey, Tensor, TensorData, TensorDataMeaning, TensorTrait},
     Component, Transform,
 };
-use re_query::{query_primary_with_history, EntityView, QueryError};
+use re_query::{query_primary_with_history_cached, EntityView, QueryError};
 use re_renderer::{
     renderer::{DepthCloud, DepthCloudDepthData},
     ColorMap, OutlineMaskPreference,
@@ -373,8 +373,9 @@ impl ScenePart for ImagesPart {
                 continue;
             };
 
-            match query_primary_with_history::<Tensor, 3>(
-                &ctx.log_db.entity_db.data_store,
+            match query_primary_with_history_cached::<Tensor, 3>(
+                ctx.query_cache,
+                &ctx.log_db.entity_db.data_store.read(),
                 &query.timeline,
                 &query.latest_at,
                 &props.visible_history,
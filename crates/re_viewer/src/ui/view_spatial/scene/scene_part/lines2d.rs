@@ -5,7 +5,7 @@ use re_log_types::{
     component_types::{ColorRGBA, InstanceKey, LineStrip2D, Radius},
     Component,
 };
-use re_query::{query_primary_with_history, EntityView, QueryError};
+use re_query::{query_primary_with_history_cached, EntityView, QueryError};
 use re_renderer::{renderer::LineStripFlags, Size};
 
 use crate::{
@@ -92,8 +92,9 @@ impl ScenePart for Lines2DPart {
             };
             let entity_highlight = highlights.entity_outline_mask(ent_path.hash());
 
-            match query_primary_with_history::<LineStrip2D, 4>(
-                &ctx.log_db.entity_db.data_store,
+            match query_primary_with_history_cached::<LineStrip2D, 4>(
+                ctx.query_cache,
+                &ctx.log_db.entity_db.data_store.read(),
                 &query.timeline,
                 &query.latest_at,
                 &props.visible_history,
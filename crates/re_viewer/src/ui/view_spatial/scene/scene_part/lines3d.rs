@@ -5,7 +5,7 @@ use re_log_types::{
     component_types::{ColorRGBA, InstanceKey, LineStrip3D, Radius},
     Component,
 };
-use re_query::{query_primary_with_history, EntityView, QueryError};
+use re_query::{query_primary_with_history_cached, EntityView, QueryError};
 use re_renderer::Size;
 
 use crate::{
@@ -91,8 +91,9 @@ impl ScenePart for Lines3DPart {
             };
             let entity_highlight = highlights.entity_outline_mask(ent_path.hash());
 
-            match query_primary_with_history::<LineStrip3D, 4>(
-                &ctx.log_db.entity_db.data_store,
+            match query_primary_with_history_cached::<LineStrip3D, 4>(
+                ctx.query_cache,
+                &ctx.log_db.entity_db.data_store.read(),
                 &query.timeline,
                 &query.latest_at,
                 &props.visible_history,
@@ -5,7 +5,7 @@ use re_log_types::{
     component_types::{ColorRGBA, InstanceKey},
     Component, Mesh3D,
 };
-use re_query::{query_primary_with_history, EntityView, QueryError};
+use re_query::{query_primary_with_history_cached, EntityView, QueryError};
 
 use crate::{
     misc::{SpaceViewHighlights, TransformCache, ViewerContext},
@@ -91,8 +91,9 @@ impl ScenePart for MeshPart {
                 continue;
             };
 
-            match query_primary_with_history::<Mesh3D, 3>(
-                &ctx.log_db.entity_db.data_store,
+            match query_primary_with_history_cached::<Mesh3D, 3>(
+                ctx.query_cache,
+                &ctx.log_db.entity_db.data_store.read(),
                 &query.timeline,
                 &query.latest_at,
                 &props.visible_history,
@@ -10,6 +10,7 @@ mod lines3d;
 mod meshes;
 mod points2d;
 mod points3d;
+mod trails;
 
 pub(crate) use arrows3d::Arrows3DPart;
 pub(crate) use boxes2d::Boxes2DPart;
@@ -21,6 +22,7 @@ pub(crate) use lines3d::Lines3DPart;
 pub(crate) use meshes::MeshPart;
 pub(crate) use points2d::Points2DPart;
 pub(crate) use points3d::Points3DPart;
+pub(crate) use trails::InstanceTrailsPart;
 
 use super::SceneSpatial;
 use crate::{
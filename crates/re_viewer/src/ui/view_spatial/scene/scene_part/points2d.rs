@@ -5,7 +5,7 @@ use re_log_types::{
     component_types::{ClassId, ColorRGBA, InstanceKey, KeypointId, Label, Point2D, Radius},
     Component,
 };
-use re_query::{query_primary_with_history, EntityView, QueryError};
+use re_query::{query_primary_with_history_cached, EntityView, QueryError};
 use re_renderer::Size;
 
 use crate::{
@@ -142,8 +142,9 @@ impl ScenePart for Points2DPart {
             };
             let entity_highlight = highlights.entity_outline_mask(ent_path.hash());
 
-            match query_primary_with_history::<Point2D, 7>(
-                &ctx.log_db.entity_db.data_store,
+            match query_primary_with_history_cached::<Point2D, 7>(
+                ctx.query_cache,
+                &ctx.log_db.entity_db.data_store.read(),
                 &query.timeline,
                 &query.latest_at,
                 &props.visible_history,
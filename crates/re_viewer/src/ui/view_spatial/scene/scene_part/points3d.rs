@@ -8,7 +8,7 @@ use re_log_types::{
     component_types::{ClassId, ColorRGBA, InstanceKey, KeypointId, Label, Point3D, Radius},
     Component,
 };
-use re_query::{query_primary_with_history, EntityView, QueryError};
+use re_query::{query_primary_with_history_cached, MaybeCachedEntityView, QueryError};
 use re_renderer::Size;
 
 use crate::{
@@ -34,7 +34,7 @@ pub struct Points3DPart {
 impl Points3DPart {
     fn process_annotations(
         query: &SceneQuery<'_>,
-        entity_view: &EntityView<Point3D>,
+        entity_view: &MaybeCachedEntityView<Point3D>,
         annotations: &Arc<Annotations>,
     ) -> Result<(Vec<ResolvedAnnotationInfo>, Keypoints), QueryError> {
         crate::profile_function!();
@@ -76,7 +76,7 @@ impl Points3DPart {
     }
 
     fn process_colors<'a>(
-        entity_view: &'a EntityView<Point3D>,
+        entity_view: &'a MaybeCachedEntityView<Point3D>,
         ent_path: &'a EntityPath,
         annotation_infos: &'a [ResolvedAnnotationInfo],
     ) -> Result<impl Iterator<Item = egui::Color32> + 'a, QueryError> {
@@ -95,7 +95,7 @@ impl Points3DPart {
 
     fn process_radii<'view>(
         ent_path: &EntityPath,
-        entity_view: &'view EntityView<Point3D>,
+        entity_view: &'view MaybeCachedEntityView<Point3D>,
     ) -> Result<impl Iterator<Item = Size> + 'view, QueryError> {
         let ent_path = ent_path.clone();
         Ok(entity_view.iter_component::<Radius>()?.map(move |radius| {
@@ -117,7 +117,7 @@ impl Points3DPart {
     }
 
     fn process_labels<'a>(
-        entity_view: &'a EntityView<Point3D>,
+        entity_view: &'a MaybeCachedEntityView<Point3D>,
         instance_path_hashes: &'a [InstancePathHash],
         colors: &'a [egui::Color32],
         annotation_infos: &'a [ResolvedAnnotationInfo],
@@ -155,7 +155,7 @@ impl Points3DPart {
         scene: &mut SceneSpatial,
         query: &SceneQuery<'_>,
         properties: &EntityProperties,
-        entity_view: &EntityView<Point3D>,
+        entity_view: &MaybeCachedEntityView<Point3D>,
         ent_path: &EntityPath,
         world_from_obj: Mat4,
         entity_highlight: &SpaceViewOutlineMasks,
@@ -264,8 +264,9 @@ impl ScenePart for Points3DPart {
             };
             let entity_highlight = highlights.entity_outline_mask(ent_path.hash());
 
-            match query_primary_with_history::<Point3D, 7>(
-                &ctx.log_db.entity_db.data_store,
+            match query_primary_with_history_cached::<Point3D, 7>(
+                ctx.query_cache,
+                &ctx.log_db.entity_db.data_store.read(),
                 &query.timeline,
                 &query.latest_at,
                 &props.visible_history,
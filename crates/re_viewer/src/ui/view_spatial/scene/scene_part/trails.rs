@@ -0,0 +1,114 @@
+use re_arrow_store::{RangeQuery, TimeInt, TimeRange};
+use re_data_store::{ExtraQueryHistory, InstancePath};
+use re_log_types::{component_types::Point3D, TimeType};
+use re_query::range_instance_history;
+use re_renderer::Size;
+
+use crate::{
+    misc::{Item, SpaceViewHighlights, TransformCache, ViewerContext},
+    ui::{scene::SceneQuery, view_spatial::SceneSpatial, DefaultColor},
+};
+
+use super::ScenePart;
+
+/// Draws the trail of the currently selected 3D point instances, i.e. the positions they've been
+/// through over the visible history of their entity (or [`DEFAULT_TRAIL_HISTORY`] if it has
+/// none), up until the current time.
+///
+/// Trails are drawn in the current space of their entity: past positions are transformed with
+/// the transform of the entity at the current time, not at the time they were logged.
+pub struct InstanceTrailsPart;
+
+/// How far back trails go for entities that don't have any visible history of their own.
+const DEFAULT_TRAIL_HISTORY: ExtraQueryHistory = ExtraQueryHistory {
+    nanos: 10_000_000_000, // 10s
+    sequences: 100,
+};
+
+impl ScenePart for InstanceTrailsPart {
+    fn load(
+        &self,
+        scene: &mut SceneSpatial,
+        ctx: &mut ViewerContext<'_>,
+        query: &SceneQuery<'_>,
+        transforms: &TransformCache,
+        highlights: &SpaceViewHighlights,
+    ) {
+        crate::profile_scope!("InstanceTrailsPart");
+
+        // Only specific instances have a trail: a whole entity would just be a mess of lines.
+        let selected = ctx
+            .selection()
+            .iter()
+            .filter_map(|item| match item {
+                Item::InstancePath(_, instance_path)
+                    if instance_path.instance_key.is_specific() =>
+                {
+                    Some(instance_path.clone())
+                }
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        for InstancePath {
+            entity_path: ent_path,
+            instance_key,
+        } in selected
+        {
+            let Some((_, props)) = query.iter_entities().find(|(path, _)| **path == ent_path) else {
+                continue;
+            };
+            // Trails are drawn in the current space of the entity, see `InstanceTrailsPart`.
+            let Some(world_from_obj) = transforms.reference_from_entity(&ent_path) else {
+                continue;
+            };
+
+            // The viewer doesn't maintain a value index on the cluster key (it would be about as
+            // large as the data itself), so every single row of the entity in the time range has
+            // to be looked at: keep that range bounded.
+            let history_on_timeline = |history: &ExtraQueryHistory| match query.timeline.typ() {
+                TimeType::Time => history.nanos,
+                TimeType::Sequence => history.sequences,
+            };
+            let history = Some(history_on_timeline(&props.visible_history))
+                .filter(|history| *history > 0)
+                .unwrap_or_else(|| history_on_timeline(&DEFAULT_TRAIL_HISTORY));
+            let range_query = RangeQuery::new(
+                query.timeline,
+                TimeRange::new(query.latest_at - TimeInt::from(history), query.latest_at),
+            );
+            let positions = range_instance_history::<Point3D>(
+                &ctx.log_db.entity_db.data_store.read(),
+                &range_query,
+                &ent_path,
+                instance_key,
+            )
+            .map(|(_, point)| glam::Vec3::from(point))
+            .collect::<Vec<_>>();
+
+            if positions.len() < 2 {
+                continue;
+            }
+
+            let annotations = scene.annotation_map.find(&ent_path);
+            let color = annotations
+                .class_description(None)
+                .annotation_info()
+                .color(None, DefaultColor::EntityPath(&ent_path));
+
+            let entity_highlight = highlights.entity_outline_mask(ent_path.hash());
+            let mut line_batch = scene
+                .primitives
+                .line_strips
+                .batch("instance trail")
+                .world_from_obj(world_from_obj);
+            let trail = line_batch
+                .add_strip(positions.into_iter())
+                .radius(Size::AUTO)
+                .color(color);
+            if let Some(outline_mask_ids) = entity_highlight.instances.get(&instance_key) {
+                trail.outline_mask_ids(*outline_mask_ids);
+            }
+        }
+    }
+}
@@ -45,7 +45,7 @@ impl SceneText {
     ) {
         crate::profile_function!();
 
-        let store = &ctx.log_db.entity_db.data_store;
+        let store = &ctx.log_db.entity_db.data_store.read();
 
         for entity_path in query.entity_paths {
             let ent_path = entity_path;
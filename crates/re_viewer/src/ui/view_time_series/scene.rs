@@ -1,13 +1,16 @@
 use crate::{
-    ui::{annotations::AnnotationMap, DefaultColor, SceneQuery},
+    ui::{
+        annotations::{AnnotationMap, ResolvedAnnotationInfo},
+        DefaultColor, SceneQuery,
+    },
     ViewerContext,
 };
 use re_arrow_store::TimeRange;
 use re_log_types::{
     component_types::{self, InstanceKey},
-    Component,
+    Component, ComponentName, EntityPath, Timeline,
 };
-use re_query::{range_entity_with_primary, QueryError};
+use re_query::{query_entity_with_primary, range_entity_with_primary, QueryError};
 
 // ---
 
@@ -78,66 +81,33 @@ impl SceneTimeSeries {
     fn load_scalars(&mut self, ctx: &mut ViewerContext<'_>, query: &SceneQuery<'_>) {
         crate::profile_function!();
 
-        let store = &ctx.log_db.entity_db.data_store;
-
         for entity_path in query.entity_paths {
             let ent_path = entity_path;
 
-            let mut points = Vec::new();
             let annotations = self.annotation_map.find(ent_path);
-            let annotation_info = annotations.class_description(None).annotation_info();
-            let default_color = DefaultColor::EntityPath(ent_path);
-
-            let query = re_arrow_store::RangeQuery::new(
-                query.timeline,
-                TimeRange::new(i64::MIN.into(), i64::MAX.into()),
-            );
-
-            let components = [
-                InstanceKey::name(),
-                component_types::Scalar::name(),
-                component_types::ScalarPlotProps::name(),
-                component_types::ColorRGBA::name(),
-                component_types::Radius::name(),
-                component_types::Label::name(),
-            ];
-            let ent_views = range_entity_with_primary::<component_types::Scalar, 6>(
-                store, &query, ent_path, components,
-            );
-
-            for (time, ent_view) in ent_views {
-                match ent_view.visit5(
-                    |_instance,
-                     scalar: component_types::Scalar,
-                     props: Option<component_types::ScalarPlotProps>,
-                     color: Option<component_types::ColorRGBA>,
-                     radius: Option<component_types::Radius>,
-                     label: Option<component_types::Label>| {
-                        // TODO(andreas): Support entity path
-                        let color = annotation_info
-                            .color(color.map(|c| c.to_array()).as_ref(), default_color);
-                        let label = annotation_info.label(label.map(|l| l.into()).as_ref());
-
-                        const DEFAULT_RADIUS: f32 = 0.75;
-
-                        points.push(PlotPoint {
-                            time: time.unwrap().as_i64(), // scalars cannot be timeless
-                            value: scalar.into(),
-                            attrs: PlotPointAttrs {
-                                label,
-                                color,
-                                radius: radius.map_or(DEFAULT_RADIUS, |r| r.0),
-                                scattered: props.map_or(false, |props| props.scattered),
-                            },
-                        });
-                    },
-                ) {
-                    Ok(_) | Err(QueryError::PrimaryNotFound) => {}
-                    Err(err) => {
-                        re_log::error_once!("Unexpected error querying {ent_path:?}: {err}");
-                    }
+            let attrs = AttrsResolver {
+                annotation_info: annotations.class_description(None).annotation_info(),
+                default_color: DefaultColor::EntityPath(ent_path),
+            };
+
+            let num_scalars = ctx
+                .log_db
+                .entity_db
+                .tree
+                .subtree(ent_path)
+                .and_then(|tree| tree.components.get(&component_types::Scalar::name()))
+                .and_then(|stats| stats.times.get(&query.timeline))
+                .map(|times| (times.total_count(), times.min_key(), times.max_key()));
+
+            let mut points = match num_scalars {
+                Some((num_scalars, Some(min_time), Some(max_time)))
+                    if num_scalars > MAX_POINTS_PER_SERIES =>
+                {
+                    let time_range = TimeRange::new(min_time.into(), max_time.into());
+                    aggregated_points(ctx, query.timeline, ent_path, time_range, &attrs)
                 }
-            }
+                _ => all_points(ctx, query.timeline, ent_path, &attrs),
+            };
 
             points.sort_by_key(|s| s.time);
 
@@ -228,3 +198,165 @@ impl SceneTimeSeries {
         }
     }
 }
+
+/// The components queried alongside each scalar, see [`AttrsResolver`].
+fn plot_components() -> [ComponentName; 6] {
+    [
+        InstanceKey::name(),
+        component_types::Scalar::name(),
+        component_types::ScalarPlotProps::name(),
+        component_types::ColorRGBA::name(),
+        component_types::Radius::name(),
+        component_types::Label::name(),
+    ]
+}
+
+/// Series with more scalars than this are downsampled, see [`aggregated_points`].
+const MAX_POINTS_PER_SERIES: u64 = 10_000;
+
+/// Turns the attribute components of a scalar into [`PlotPointAttrs`].
+struct AttrsResolver<'a> {
+    annotation_info: ResolvedAnnotationInfo,
+    default_color: DefaultColor<'a>,
+}
+
+impl AttrsResolver<'_> {
+    fn resolve(
+        &self,
+        props: Option<component_types::ScalarPlotProps>,
+        color: Option<component_types::ColorRGBA>,
+        radius: Option<component_types::Radius>,
+        label: Option<component_types::Label>,
+    ) -> PlotPointAttrs {
+        // TODO(andreas): Support entity path
+        let color = self
+            .annotation_info
+            .color(color.map(|c| c.to_array()).as_ref(), self.default_color);
+        let label = self.annotation_info.label(label.map(|l| l.into()).as_ref());
+
+        const DEFAULT_RADIUS: f32 = 0.75;
+
+        PlotPointAttrs {
+            label,
+            color,
+            radius: radius.map_or(DEFAULT_RADIUS, |r| r.0),
+            scattered: props.map_or(false, |props| props.scattered),
+        }
+    }
+}
+
+/// Every single scalar logged to `ent_path`, each with its own attributes.
+fn all_points(
+    ctx: &ViewerContext<'_>,
+    timeline: Timeline,
+    ent_path: &EntityPath,
+    attrs: &AttrsResolver<'_>,
+) -> Vec<PlotPoint> {
+    crate::profile_function!();
+
+    let store = &ctx.log_db.entity_db.data_store.read();
+
+    let query =
+        re_arrow_store::RangeQuery::new(timeline, TimeRange::new(i64::MIN.into(), i64::MAX.into()));
+
+    let ent_views = range_entity_with_primary::<component_types::Scalar, 6>(
+        store,
+        &query,
+        ent_path,
+        plot_components(),
+    );
+
+    let mut points = Vec::new();
+    for (time, ent_view) in ent_views {
+        match ent_view.visit5(
+            |_instance,
+             scalar: component_types::Scalar,
+             props: Option<component_types::ScalarPlotProps>,
+             color: Option<component_types::ColorRGBA>,
+             radius: Option<component_types::Radius>,
+             label: Option<component_types::Label>| {
+                points.push(PlotPoint {
+                    time: time.unwrap().as_i64(), // scalars cannot be timeless
+                    value: scalar.into(),
+                    attrs: attrs.resolve(props, color, radius, label),
+                });
+            },
+        ) {
+            Ok(_) | Err(QueryError::PrimaryNotFound) => {}
+            Err(err) => {
+                re_log::error_once!("Unexpected error querying {ent_path:?}: {err}");
+            }
+        }
+    }
+
+    points
+}
+
+/// The scalars logged to `ent_path`, downsampled to about [`MAX_POINTS_PER_SERIES`] points.
+///
+/// Each time bucket contributes its minimum and maximum, so that spikes remain visible.
+/// All points share the attributes that are current at the end of the series.
+fn aggregated_points(
+    ctx: &ViewerContext<'_>,
+    timeline: Timeline,
+    ent_path: &EntityPath,
+    time_range: TimeRange,
+    attrs: &AttrsResolver<'_>,
+) -> Vec<PlotPoint> {
+    crate::profile_function!();
+
+    let store = &ctx.log_db.entity_db.data_store.read();
+
+    let span = time_range
+        .max
+        .as_i64()
+        .saturating_sub(time_range.min.as_i64());
+    let resolution = (span / (MAX_POINTS_PER_SERIES as i64 / 2)).max(1);
+    let query = re_arrow_store::RangeAggregationQuery::new(timeline, time_range, resolution.into());
+
+    let buckets = match store.range_aggregate(&query, ent_path, component_types::Scalar::name()) {
+        Ok(buckets) => buckets,
+        Err(err) => {
+            re_log::error_once!("Unexpected error querying {ent_path:?}: {err}");
+            return Vec::new();
+        }
+    };
+
+    let latest = re_arrow_store::LatestAtQuery::new(timeline, time_range.max);
+    let mut latest_attrs = None;
+    if let Ok(ent_view) = query_entity_with_primary::<component_types::Scalar>(
+        store,
+        &latest,
+        ent_path,
+        &plot_components(),
+    ) {
+        ent_view
+            .visit5(
+                |_instance,
+                 _scalar: component_types::Scalar,
+                 props: Option<component_types::ScalarPlotProps>,
+                 color: Option<component_types::ColorRGBA>,
+                 radius: Option<component_types::Radius>,
+                 label: Option<component_types::Label>| {
+                    latest_attrs.get_or_insert_with(|| attrs.resolve(props, color, radius, label));
+                },
+            )
+            .ok();
+    }
+    let attrs = latest_attrs.unwrap_or_else(|| attrs.resolve(None, None, None, None));
+
+    buckets
+        .iter()
+        .flat_map(|bucket| {
+            [
+                (bucket.time_range.min.as_i64(), bucket.min),
+                (bucket.time_range.max.as_i64(), bucket.max),
+            ]
+        })
+        .map(|(time, value)| PlotPoint {
+            time,
+            value,
+            attrs: attrs.clone(),
+        })
+        .collect()
+}
@@ -45,6 +45,8 @@ pub struct RerunArgs {
     spawn: bool,
 
     /// Saves the data to an rrd file rather than visualizing it immediately.
+    ///
+    /// Use `-` to write the rrd stream to stdout instead, e.g. to pipe it into `rerun -`.
     #[clap(long)]
     save: Option<PathBuf>,
 
@@ -98,6 +100,10 @@ impl RerunArgs {
         let sink: Box<dyn re_sdk::sink::LogSink> = match self.to_behavior()? {
             RerunBehavior::Connect(addr) => Box::new(crate::sink::TcpSink::new(addr)),
 
+            RerunBehavior::Save(path) if path.as_os_str() == "-" => {
+                Box::new(crate::sink::StdoutSink::new()?)
+            }
+
             RerunBehavior::Save(path) => Box::new(crate::sink::FileSink::new(path)?),
 
             #[cfg(feature = "web_viewer")]
@@ -90,6 +90,7 @@
 #![warn(missing_docs)] // Let's keep the this crate well-documented!
 
 mod crash_handler;
+mod rrd;
 mod run;
 
 /// Module for integrating with the [`clap`](https://crates.io/crates/clap) command line argument parser.
@@ -0,0 +1,1533 @@
+//! The `rerun rrd` family of subcommands, for working with `.rrd` files straight from the
+//! terminal, without ever having to open the viewer.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+use anyhow::Context as _;
+
+use re_arrow_store::{DataStore, DataStoreStats};
+use re_log_encoding::{
+    decoder::Decoder, encoder::Encoder, Compression, EncodingOptions, Layout, Serializer,
+};
+use re_log_types::{
+    component_types::InstanceKey, parse_entity_path, Component as _, ComponentName, DataRow,
+    DataTable, EntityPath, LogMsg, MsgId, RecordingId, RecordingInfo, TimeInt, TimePoint,
+    TimeRange, TimeType, Timeline,
+};
+
+// ---
+
+#[derive(Debug, Clone, clap::Subcommand)]
+pub enum RrdCommands {
+    /// Print a summary of the contents of an `.rrd` file.
+    ///
+    /// This includes the recording metadata, the timelines and their time ranges, all entity
+    /// paths along with their components, as well as row counts and memory usage once loaded.
+    Info {
+        /// Path to the `.rrd` file to summarize.
+        path: PathBuf,
+    },
+
+    /// Write a copy of an `.rrd` file that only contains the given entities and time range.
+    ///
+    /// Recording metadata is always kept. Timeless data is kept for all matching entities, and so
+    /// is the latest state of every component right before the start of the time range, so that
+    /// the filtered recording looks the same as the original one within that range.
+    ///
+    /// Example: `rerun rrd filter big.rrd -o small.rrd --include 'camera/**' --timeline log_time --from 10s --to 40s`
+    Filter {
+        /// Path to the `.rrd` file to read from.
+        path: PathBuf,
+
+        #[command(flatten)]
+        output: OutputArgs,
+
+        /// Only keep this entity and all of its descendants, e.g. `camera/**`.
+        ///
+        /// Can be specified several times. If none are given, all entities are included.
+        #[clap(long)]
+        include: Vec<String>,
+
+        /// Drop this entity and all of its descendants, e.g. `camera/depth/**`.
+        ///
+        /// Can be specified several times. Takes precedence over `--include`.
+        #[clap(long)]
+        exclude: Vec<String>,
+
+        /// The name of the timeline that `--from` and `--to` refer to, e.g. `log_time` or `frame_nr`.
+        ///
+        /// If given, all non-timeless data that isn't on this timeline is dropped.
+        #[clap(long)]
+        timeline: Option<String>,
+
+        /// Start of the time range to keep (inclusive).
+        ///
+        /// Either a sequence number for sequence timelines (e.g. `42`), or a duration since the
+        /// earliest time on the timeline for temporal timelines (e.g. `10s`, `1.5m`).
+        #[clap(long, requires = "timeline")]
+        from: Option<TimeBound>,
+
+        /// End of the time range to keep (inclusive).
+        ///
+        /// Same format as `--from`.
+        #[clap(long, requires = "timeline")]
+        to: Option<TimeBound>,
+    },
+
+    /// Combine several `.rrd` files into a single one.
+    ///
+    /// By default, every recording keeps its own recording id, so the viewer will list them as
+    /// separate recordings. With `--single-recording`, all recordings are remapped onto a new one,
+    /// and the entity paths of each input file are prefixed so that they don't collide.
+    ///
+    /// Example: `rerun rrd merge arm.rrd base.rrd -o run.rrd --single-recording`
+    Merge {
+        /// Paths to the `.rrd` files to merge.
+        #[clap(required = true)]
+        paths: Vec<PathBuf>,
+
+        #[command(flatten)]
+        output: OutputArgs,
+
+        /// Remap all recordings onto a single recording id, prefixing the entity paths of each
+        /// input file.
+        #[clap(long)]
+        single_recording: bool,
+
+        /// The entity path prefix for each input file, in the same order as the input files.
+        ///
+        /// Defaults to the file names of the inputs without extension, e.g. `arm` for `arm.rrd`.
+        #[clap(long, requires = "single_recording")]
+        prefix: Vec<String>,
+    },
+
+    /// Salvage what can be salvaged from a truncated or partially corrupt `.rrd` file.
+    ///
+    /// Every message that can still be decoded is written to a new, well-formed file, and a
+    /// report of what had to be skipped and why is printed. This is typically needed for
+    /// recordings of processes that crashed or were killed while logging.
+    ///
+    /// Example: `rerun rrd repair crash.rrd -o crash_repaired.rrd`
+    Repair {
+        /// Path to the damaged `.rrd` file.
+        path: PathBuf,
+
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+
+    /// Write a copy of an `.rrd` file with a different compression.
+    ///
+    /// Recordings are typically written with cheap compression so that the logging process can
+    /// keep up, and can then be recompressed much more tightly for archival.
+    ///
+    /// Example: `rerun rrd recompress big.rrd -o archive.rrd --compression zstd:19`
+    Recompress {
+        /// Path to the `.rrd` file to read from.
+        path: PathBuf,
+
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+}
+
+/// Where and how to write the resulting `.rrd` file.
+#[derive(Debug, Clone, clap::Args)]
+pub struct OutputArgs {
+    /// Where to write the resulting `.rrd` file.
+    #[clap(long, short)]
+    output: PathBuf,
+
+    /// Write the file using the chunked layout, which lets readers jump straight to any time
+    /// range instead of having to decode the whole file from the start.
+    ///
+    /// `rrd recompress` keeps the layout of its input unless given `--seekable=true|false`.
+    #[clap(long, num_args = 0..=1, default_missing_value = "true")]
+    seekable: Option<bool>,
+
+    /// Write the payloads of data messages as native Arrow IPC streams, which are faster to
+    /// encode and decode. Older versions of Rerun cannot read such files.
+    ///
+    /// `rrd recompress` keeps the serializer of its input unless given `--arrow-ipc=true|false`.
+    #[clap(long, num_args = 0..=1, default_missing_value = "true")]
+    arrow_ipc: Option<bool>,
+
+    /// How to compress the resulting file: `off`, `lz4`, `zstd` or `zstd:<level>`, e.g. `zstd:19`.
+    ///
+    /// Higher zstd levels yield smaller files, but are slower to write. Reading is fast either way.
+    /// Defaults to `zstd:3`, except for `rrd recompress`, which keeps the compression of its input.
+    #[clap(long)]
+    compression: Option<Compression>,
+}
+
+impl OutputArgs {
+    fn create_encoder(&self) -> anyhow::Result<Encoder<std::fs::File>> {
+        self.create_encoder_with(self.options_like(EncodingOptions::DEFAULT))
+    }
+
+    fn create_encoder_with(
+        &self,
+        options: EncodingOptions,
+    ) -> anyhow::Result<Encoder<std::fs::File>> {
+        let output = &self.output;
+        if output.exists() {
+            re_log::warn!("Overwriting existing file at {output:?}");
+        }
+        let file = std::fs::File::create(output)
+            .with_context(|| format!("Failed to create {output:?}"))?;
+
+        Ok(Encoder::with_options(file, options)?)
+    }
+
+    /// The options to write with: anything not set on the command line is taken from `base`.
+    fn options_like(&self, base: EncodingOptions) -> EncodingOptions {
+        let Self {
+            output: _,
+            seekable,
+            arrow_ipc,
+            compression,
+        } = self;
+
+        EncodingOptions {
+            layout: match seekable {
+                Some(true) if base.layout == Layout::Stream => Layout::CHUNKED,
+                Some(false) => Layout::Stream,
+                _ => base.layout,
+            },
+            serializer: match arrow_ipc {
+                Some(true) => Serializer::ArrowIpc,
+                Some(false) => Serializer::MsgPack,
+                None => base.serializer,
+            },
+            compression: compression.unwrap_or(base.compression),
+        }
+    }
+}
+
+pub fn run_rrd(cmd: &RrdCommands) -> anyhow::Result<()> {
+    match cmd {
+        RrdCommands::Info { path } => {
+            let info = RrdInfo::from_path(path).with_context(|| format!("{path:?}"))?;
+            print!("{info}");
+            Ok(())
+        }
+
+        RrdCommands::Filter {
+            path,
+            output,
+            include,
+            exclude,
+            timeline,
+            from,
+            to,
+        } => {
+            let entity_filter = EntityFilter::new(include, exclude)?;
+            let time_filter = timeline
+                .as_ref()
+                .map(|timeline| TimeFilter::new(path, timeline, *from, *to))
+                .transpose()
+                .with_context(|| format!("{path:?}"))?;
+            filter_rrd(path, output, &entity_filter, time_filter.as_ref())
+                .with_context(|| format!("{path:?}"))
+        }
+
+        RrdCommands::Merge {
+            paths,
+            output,
+            single_recording,
+            prefix,
+        } => {
+            let remap = if *single_recording {
+                Some(RecordingRemap::new(paths, prefix)?)
+            } else {
+                None
+            };
+            merge_rrds(paths, output, remap)
+        }
+
+        RrdCommands::Repair { path, output } => {
+            repair_rrd(path, output).with_context(|| format!("{path:?}"))
+        }
+
+        RrdCommands::Recompress { path, output } => {
+            recompress_rrd(path, output).with_context(|| format!("{path:?}"))
+        }
+    }
+}
+
+// --- Info ---
+
+/// What we know about a single entity, as seen from an `.rrd` file.
+#[derive(Default)]
+struct EntityInfo {
+    components: BTreeSet<ComponentName>,
+    num_rows: u64,
+    num_timeless_rows: u64,
+}
+
+/// A summary of the contents of an `.rrd` file.
+struct RrdInfo {
+    path: PathBuf,
+    file_size_bytes: Option<u64>,
+
+    /// Number of frames, for files using the chunked layout.
+    num_frames: Option<usize>,
+
+    num_messages: u64,
+    num_path_ops: u64,
+
+    /// Messages that could not be decoded, and are left out of everything else.
+    num_skipped_messages: u64,
+
+    recordings: Vec<RecordingInfo>,
+    timelines: BTreeMap<Timeline, TimeRange>,
+    entities: BTreeMap<EntityPath, EntityInfo>,
+
+    store_stats: DataStoreStats,
+}
+
+impl RrdInfo {
+    /// Decodes the entire file and indexes it into a [`DataStore`] in order to gather stats.
+    fn from_path(path: &Path) -> anyhow::Result<Self> {
+        use std::io::Seek as _;
+
+        let mut file = std::fs::File::open(path).context("Failed to open file")?;
+        let file_size_bytes = file.metadata().ok().map(|metadata| metadata.len());
+        let num_frames = Decoder::read_index(&mut file)?.map(|index| index.frames.len());
+        file.rewind()?;
+        let decoder = Decoder::new(file)?;
+
+        let mut store = DataStore::new(InstanceKey::name(), Default::default());
+
+        let mut num_messages = 0;
+        let mut num_path_ops = 0;
+        let mut num_skipped_messages = 0;
+        let mut recordings = Vec::new();
+        let mut timelines: BTreeMap<Timeline, TimeRange> = Default::default();
+        let mut entities: BTreeMap<EntityPath, EntityInfo> = Default::default();
+
+        for msg in decoder {
+            let msg = match msg {
+                Ok(msg) => msg,
+                Err(err) => {
+                    re_log::warn_once!("Failed to decode message in {path:?}: {err}");
+                    num_skipped_messages += 1;
+                    continue;
+                }
+            };
+
+            match &msg {
+                LogMsg::BeginRecordingMsg(msg) => recordings.push(msg.info.clone()),
+                LogMsg::EntityPathOpMsg(_, _) => num_path_ops += 1,
+                LogMsg::ArrowMsg(_, msg) => {
+                    let table = match DataTable::try_from(msg) {
+                        Ok(table) => table,
+                        Err(err) => {
+                            re_log::warn_once!("Failed to decode data table in {path:?}: {err}");
+                            num_skipped_messages += 1;
+                            continue;
+                        }
+                    };
+                    for row in table.as_rows() {
+                        for (timeline, time) in row.timepoint().iter() {
+                            timelines
+                                .entry(*timeline)
+                                .and_modify(|range| *range = range.union(TimeRange::point(*time)))
+                                .or_insert_with(|| TimeRange::point(*time));
+                        }
+
+                        let entity = entities.entry(row.entity_path().clone()).or_default();
+                        entity.components.extend(row.components());
+                        entity.num_rows += 1;
+                        if row.timepoint().is_timeless() {
+                            entity.num_timeless_rows += 1;
+                        }
+
+                        store.insert_row(&row)?;
+                    }
+                }
+                LogMsg::Goodbye(_) => {}
+            }
+
+            num_messages += 1;
+        }
+
+        Ok(Self {
+            path: path.to_owned(),
+            file_size_bytes,
+            num_frames,
+            num_messages,
+            num_path_ops,
+            num_skipped_messages,
+            recordings,
+            timelines,
+            entities,
+            store_stats: DataStoreStats::from_store(&store),
+        })
+    }
+}
+
+impl std::fmt::Display for RrdInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        use re_format::{format_bytes, format_number};
+
+        let Self {
+            path,
+            file_size_bytes,
+            num_frames,
+            num_messages,
+            num_path_ops,
+            num_skipped_messages,
+            recordings,
+            timelines,
+            entities,
+            store_stats,
+        } = self;
+
+        writeln!(f, "File: {path:?}")?;
+        if let Some(file_size_bytes) = file_size_bytes {
+            writeln!(f, "Size on disk: {}", format_bytes(*file_size_bytes as _))?;
+        }
+        if let Some(num_frames) = num_frames {
+            writeln!(f, "Seekable: yes ({} frames)", format_number(*num_frames))?;
+        } else {
+            writeln!(f, "Seekable: no")?;
+        }
+        writeln!(
+            f,
+            "Messages: {} ({} path operations)",
+            format_number(*num_messages as _),
+            format_number(*num_path_ops as _),
+        )?;
+        if *num_skipped_messages > 0 {
+            writeln!(
+                f,
+                "Skipped: {} messages could not be decoded and are not part of this summary",
+                format_number(*num_skipped_messages as _),
+            )?;
+        } else {
+            writeln!(f, "Skipped: none")?;
+        }
+
+        writeln!(f, "\nRecordings ({}):", recordings.len())?;
+        for info in recordings {
+            let RecordingInfo {
+                application_id,
+                recording_id,
+                is_official_example,
+                started,
+                recording_source,
+            } = info;
+            writeln!(f, "  - {recording_id}")?;
+            writeln!(f, "      application id: {application_id}")?;
+            writeln!(f, "      started: {}", started.format())?;
+            writeln!(f, "      source: {recording_source}")?;
+            writeln!(f, "      official example: {is_official_example}")?;
+        }
+
+        writeln!(f, "\nTimelines ({}):", timelines.len())?;
+        for (timeline, time_range) in timelines {
+            writeln!(
+                f,
+                "  - {} ({:?}): {}",
+                timeline.name(),
+                timeline.typ(),
+                timeline.typ().format_range(*time_range),
+            )?;
+        }
+
+        writeln!(f, "\nEntities ({}):", entities.len())?;
+        for (ent_path, info) in entities {
+            let EntityInfo {
+                components,
+                num_rows,
+                num_timeless_rows,
+            } = info;
+            writeln!(
+                f,
+                "  - {ent_path}: {} rows ({} timeless)",
+                format_number(*num_rows as _),
+                format_number(*num_timeless_rows as _),
+            )?;
+            for component in components {
+                writeln!(f, "      {component}")?;
+            }
+        }
+
+        let DataStoreStats {
+            total_timeless_index_rows,
+            total_timeless_index_size_bytes,
+            total_timeless_component_rows,
+            total_timeless_component_size_bytes,
+            total_temporal_index_rows,
+            total_temporal_index_size_bytes,
+            total_temporal_index_buckets,
+            total_temporal_component_rows,
+            total_temporal_component_size_bytes,
+            total_temporal_component_buckets,
+            total_index_rows,
+            total_index_size_bytes,
+            total_component_rows,
+            total_component_size_bytes,
+            total_deduplicated_size_bytes: _,
+            total_reclaimed_index_rows: _,
+            total_reclaimed_index_size_bytes: _,
+            config: _,
+        } = store_stats;
+
+        writeln!(f, "\nIn-memory store:")?;
+        writeln!(f, "  {:<24}{:>12}{:>14}{:>12}", "", "Buckets", "Rows", "Size")?;
+
+        let mut stats_line = |name: &str, buckets: Option<u64>, rows: u64, size_bytes: u64| {
+            writeln!(
+                f,
+                "  {name:<24}{:>12}{:>14}{:>12}",
+                buckets.map_or_else(String::new, |buckets| format_number(buckets as _)),
+                format_number(rows as _),
+                format_bytes(size_bytes as _),
+            )
+        };
+        stats_line(
+            "Indices (timeless):",
+            None,
+            *total_timeless_index_rows,
+            *total_timeless_index_size_bytes,
+        )?;
+        stats_line(
+            "Indices (temporal):",
+            Some(*total_temporal_index_buckets),
+            *total_temporal_index_rows,
+            *total_temporal_index_size_bytes,
+        )?;
+        stats_line(
+            "Indices (total):",
+            None,
+            *total_index_rows,
+            *total_index_size_bytes,
+        )?;
+        stats_line(
+            "Components (timeless):",
+            None,
+            *total_timeless_component_rows,
+            *total_timeless_component_size_bytes,
+        )?;
+        stats_line(
+            "Components (temporal):",
+            Some(*total_temporal_component_buckets),
+            *total_temporal_component_rows,
+            *total_temporal_component_size_bytes,
+        )?;
+        stats_line(
+            "Components (total):",
+            None,
+            *total_component_rows,
+            *total_component_size_bytes,
+        )?;
+
+        Ok(())
+    }
+}
+
+/// Parses an entity path given on the command line, ignoring leading and trailing slashes.
+fn parse_entity_path_arg(arg: &str) -> anyhow::Result<EntityPath> {
+    let path = arg.trim_matches('/');
+    if path.is_empty() {
+        return Ok(EntityPath::root());
+    }
+    let parts = parse_entity_path(path).with_context(|| format!("Invalid entity path: {arg:?}"))?;
+    Ok(EntityPath::from(parts))
+}
+
+// --- Filter ---
+
+/// Which entities to keep, as specified by `--include` and `--exclude`.
+struct EntityFilter {
+    include: Vec<EntityPath>,
+    exclude: Vec<EntityPath>,
+}
+
+impl EntityFilter {
+    fn new(include: &[String], exclude: &[String]) -> anyhow::Result<Self> {
+        fn parse(pattern: &str) -> anyhow::Result<EntityPath> {
+            // `camera/**` and `camera` mean the same thing: `camera` and everything below it.
+            parse_entity_path_arg(pattern.strip_suffix("**").unwrap_or(pattern))
+        }
+
+        Ok(Self {
+            include: include
+                .iter()
+                .map(|p| parse(p))
+                .collect::<anyhow::Result<_>>()?,
+            exclude: exclude
+                .iter()
+                .map(|p| parse(p))
+                .collect::<anyhow::Result<_>>()?,
+        })
+    }
+
+    fn is_at_or_below(ent_path: &EntityPath, prefix: &EntityPath) -> bool {
+        ent_path == prefix || ent_path.is_descendant_of(prefix)
+    }
+
+    fn is_excluded(&self, ent_path: &EntityPath) -> bool {
+        self.exclude
+            .iter()
+            .any(|prefix| Self::is_at_or_below(ent_path, prefix))
+    }
+
+    /// Should the data logged at `ent_path` be kept?
+    fn keeps(&self, ent_path: &EntityPath) -> bool {
+        let is_included = self.include.is_empty()
+            || self
+                .include
+                .iter()
+                .any(|prefix| Self::is_at_or_below(ent_path, prefix));
+        is_included && !self.is_excluded(ent_path)
+    }
+
+    /// Should an operation that affects `ent_path` (and its descendants if `recursive`) be kept?
+    fn keeps_op(&self, ent_path: &EntityPath, recursive: bool) -> bool {
+        self.keeps(ent_path)
+            || (recursive
+                && self
+                    .include
+                    .iter()
+                    .any(|prefix| prefix.is_descendant_of(ent_path) && !self.is_excluded(prefix)))
+    }
+}
+
+/// A bound given to `--from` or `--to`.
+///
+/// We only find out the type of the timeline it applies to once we've looked at the file.
+#[derive(Debug, Clone, Copy)]
+pub enum TimeBound {
+    /// A sequence number, e.g. `42`.
+    Sequence(i64),
+
+    /// A duration since the earliest time on a temporal timeline, in seconds.
+    Duration(f32),
+}
+
+impl std::str::FromStr for TimeBound {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Ok(seq) = s.parse() {
+            Ok(Self::Sequence(seq))
+        } else {
+            re_format::parse_duration(s)
+                .map(Self::Duration)
+                .map_err(|_err| {
+                    format!("Expected either a sequence number (e.g. `42`) or a duration (e.g. `10s`), got {s:?}")
+                })
+        }
+    }
+}
+
+impl TimeBound {
+    /// The time range delimited by `from` and `to` on `timeline`, where `time_range` is the range
+    /// of all the times found on that timeline.
+    ///
+    /// Missing bounds leave the time range open on that side.
+    pub(crate) fn resolve_range(
+        timeline: &Timeline,
+        time_range: TimeRange,
+        from: Option<Self>,
+        to: Option<Self>,
+    ) -> anyhow::Result<TimeRange> {
+        let min = from.map_or(Ok(TimeInt::MIN), |from| {
+            from.resolve(timeline, time_range.min)
+        })?;
+        let max = to.map_or(Ok(TimeInt::MAX), |to| to.resolve(timeline, time_range.min))?;
+        Ok(TimeRange::new(min, max))
+    }
+
+    fn resolve(self, timeline: &Timeline, start: TimeInt) -> anyhow::Result<TimeInt> {
+        match (timeline.typ(), self) {
+            (TimeType::Sequence, Self::Sequence(seq)) => Ok(TimeInt::from_sequence(seq)),
+            (TimeType::Time, Self::Duration(secs)) => {
+                Ok(start + TimeInt::from_nanos((f64::from(secs) * 1e9).round() as i64))
+            }
+            (TimeType::Sequence, Self::Duration(_)) => anyhow::bail!(
+                "Timeline {:?} is a sequence timeline: expected a sequence number, not a duration",
+                timeline.name()
+            ),
+            (TimeType::Time, Self::Sequence(_)) => anyhow::bail!(
+                "Timeline {:?} is a temporal timeline: expected a duration (e.g. `10s`), not a sequence number",
+                timeline.name()
+            ),
+        }
+    }
+}
+
+/// Where a [`TimePoint`] lies relative to the time range being kept.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum TimeVerdict {
+    Timeless,
+    Before,
+    Inside,
+    After,
+
+    /// Not on the filtered timeline at all.
+    Missing,
+}
+
+/// The time range to keep, as specified by `--timeline`, `--from` and `--to`.
+struct TimeFilter {
+    timeline_name: String,
+
+    /// `None` if the timeline doesn't exist in the file.
+    resolved: Option<(Timeline, TimeRange)>,
+}
+
+impl TimeFilter {
+    /// Resolves `from` and `to` against the times found on the timeline in the file at `path`.
+    ///
+    /// Durations are relative to the earliest time on the timeline, which requires a first pass
+    /// over the whole file.
+    fn new(
+        path: &Path,
+        timeline_name: &str,
+        from: Option<TimeBound>,
+        to: Option<TimeBound>,
+    ) -> anyhow::Result<Self> {
+        let resolved = match time_range_in_file(path, timeline_name)? {
+            Some((timeline, time_range)) => {
+                let range = TimeBound::resolve_range(&timeline, time_range, from, to)?;
+                re_log::info!(
+                    "Keeping {} out of {} on timeline {:?}",
+                    timeline.format_time_range(&range),
+                    timeline.format_time_range(&time_range),
+                    timeline.name(),
+                );
+                Some((timeline, range))
+            }
+            None => None,
+        };
+
+        Ok(Self {
+            timeline_name: timeline_name.to_owned(),
+            resolved,
+        })
+    }
+
+    fn classify(&self, timepoint: &TimePoint) -> TimeVerdict {
+        if timepoint.is_timeless() {
+            return TimeVerdict::Timeless;
+        }
+
+        let Some((timeline, range)) = &self.resolved else {
+            return TimeVerdict::Missing;
+        };
+        let Some(time) = timepoint.get(timeline) else {
+            return TimeVerdict::Missing;
+        };
+
+        if *time < range.min {
+            TimeVerdict::Before
+        } else if *time > range.max {
+            TimeVerdict::After
+        } else {
+            TimeVerdict::Inside
+        }
+    }
+}
+
+/// The range of all the times found on the timeline called `timeline_name` in the file at `path`.
+fn time_range_in_file(
+    path: &Path,
+    timeline_name: &str,
+) -> anyhow::Result<Option<(Timeline, TimeRange)>> {
+    let file = std::fs::File::open(path).context("Failed to open file")?;
+    let decoder = Decoder::new(file)?;
+
+    let mut found: Option<(Timeline, TimeRange)> = None;
+    let mut add = |timepoint: &TimePoint| {
+        for (timeline, time) in timepoint.iter() {
+            if timeline.name().as_str() != timeline_name {
+                continue;
+            }
+            found = Some(match found {
+                Some((timeline, range)) => (timeline, range.union(TimeRange::point(*time))),
+                None => (*timeline, TimeRange::point(*time)),
+            });
+        }
+    };
+
+    // Decoding errors get reported by the second pass.
+    for msg in decoder.flatten() {
+        match &msg {
+            LogMsg::EntityPathOpMsg(_, msg) => add(&msg.time_point),
+            LogMsg::ArrowMsg(_, msg) => {
+                let table = DataTable::try_from(msg)?;
+                for row in table.as_rows() {
+                    add(row.timepoint());
+                }
+            }
+            LogMsg::BeginRecordingMsg(_) | LogMsg::Goodbye(_) => {}
+        }
+    }
+
+    Ok(found)
+}
+
+/// Writes out the rows gathered in `latest_before` as a single table, returning the number of rows.
+fn write_latest_before<W: std::io::Write>(
+    encoder: &mut Encoder<W>,
+    recording_id: RecordingId,
+    latest_before: BTreeMap<(EntityPath, ComponentName), DataRow>,
+) -> anyhow::Result<u64> {
+    // A single row can be the latest one for several components: only write it once.
+    let mut row_ids = std::collections::HashSet::new();
+    let rows = latest_before
+        .into_values()
+        .filter(|row| row_ids.insert(row.row_id()))
+        .collect::<Vec<_>>();
+
+    let num_rows = rows.len() as u64;
+    if num_rows > 0 {
+        let table = DataTable::from_rows(MsgId::random(), rows);
+        encoder.append(&LogMsg::ArrowMsg(recording_id, (&table).try_into()?))?;
+    }
+    Ok(num_rows)
+}
+
+fn filter_rrd(
+    in_path: &Path,
+    output: &OutputArgs,
+    entity_filter: &EntityFilter,
+    time_filter: Option<&TimeFilter>,
+) -> anyhow::Result<()> {
+    let out_path = &output.output;
+    anyhow::ensure!(
+        in_path != out_path.as_path(),
+        "The filtered file must be written to a different path"
+    );
+
+    let file = std::fs::File::open(in_path).context("Failed to open file")?;
+    let decoder = Decoder::new(file)?;
+    let mut encoder = output.create_encoder()?;
+
+    // The latest row of each component logged before the start of the time range, per recording.
+    //
+    // These are written out right before the first row that falls inside the time range, so that
+    // the state at the start of the range is the same as in the original recording.
+    let mut latest_before: BTreeMap<RecordingId, BTreeMap<(EntityPath, ComponentName), DataRow>> =
+        Default::default();
+    let mut has_started: BTreeSet<RecordingId> = Default::default();
+
+    let mut num_messages_read = 0u64;
+    let mut num_messages_written = 0u64;
+    let mut num_rows_read = 0u64;
+    let mut num_rows_written = 0u64;
+
+    for msg in decoder {
+        let msg = match msg {
+            Ok(msg) => msg,
+            Err(err) => {
+                re_log::warn_once!("Failed to decode message in {in_path:?}: {err}");
+                continue;
+            }
+        };
+        num_messages_read += 1;
+
+        let keep = match &msg {
+            LogMsg::BeginRecordingMsg(_) | LogMsg::Goodbye(_) => true,
+
+            LogMsg::EntityPathOpMsg(_, msg) => {
+                let path_op = &msg.path_op;
+                let recursive = matches!(path_op, re_log_types::PathOp::ClearRecursive(_));
+                let verdict = time_filter.map_or(TimeVerdict::Inside, |time_filter| {
+                    time_filter.classify(&msg.time_point)
+                });
+                // Clears that happened before the time range still affect the state within it.
+                entity_filter.keeps_op(path_op.entity_path(), recursive)
+                    && matches!(
+                        verdict,
+                        TimeVerdict::Timeless | TimeVerdict::Before | TimeVerdict::Inside
+                    )
+            }
+
+            LogMsg::ArrowMsg(recording_id, arrow_msg) => {
+                let table = DataTable::try_from(arrow_msg)?;
+                let num_rows = table.num_rows() as u64;
+                num_rows_read += num_rows;
+
+                let mut rows = Vec::new();
+                for row in table.as_rows() {
+                    if !entity_filter.keeps(row.entity_path()) {
+                        continue;
+                    }
+
+                    let verdict = time_filter.map_or(TimeVerdict::Inside, |time_filter| {
+                        time_filter.classify(row.timepoint())
+                    });
+
+                    match verdict {
+                        TimeVerdict::Timeless => rows.push(row),
+                        TimeVerdict::Inside => {
+                            if has_started.insert(*recording_id) {
+                                if let Some(latest) = latest_before.remove(recording_id) {
+                                    let num_latest =
+                                        write_latest_before(&mut encoder, *recording_id, latest)?;
+                                    if num_latest > 0 {
+                                        num_rows_written += num_latest;
+                                        num_messages_written += 1;
+                                    }
+                                }
+                            }
+                            rows.push(row);
+                        }
+                        TimeVerdict::Before => {
+                            if !has_started.contains(recording_id) {
+                                let timeline = time_filter
+                                    .and_then(|time_filter| time_filter.resolved)
+                                    .map(|(timeline, _)| timeline);
+                                let latest = latest_before.entry(*recording_id).or_default();
+                                for component in row.components() {
+                                    let key = (row.entity_path().clone(), component);
+                                    let is_more_recent = latest.get(&key).map_or(true, |prev| {
+                                        timeline.map_or(true, |timeline| {
+                                            prev.timepoint().get(&timeline)
+                                                <= row.timepoint().get(&timeline)
+                                        })
+                                    });
+                                    if is_more_recent {
+                                        latest.insert(key, row.clone());
+                                    }
+                                }
+                            }
+                        }
+                        TimeVerdict::After | TimeVerdict::Missing => {}
+                    }
+                }
+
+                num_rows_written += rows.len() as u64;
+
+                if rows.len() as u64 == num_rows {
+                    true // nothing was filtered out: no need to re-encode anything
+                } else {
+                    if !rows.is_empty() {
+                        let table = DataTable::from_rows(table.table_id, rows);
+                        encoder.append(&LogMsg::ArrowMsg(*recording_id, (&table).try_into()?))?;
+                        num_messages_written += 1;
+                    }
+                    false
+                }
+            }
+        };
+
+        if keep {
+            encoder.append(&msg)?;
+            num_messages_written += 1;
+        }
+    }
+
+    // Recordings that never made it into the time range still get their initial state.
+    for (recording_id, latest) in latest_before {
+        let num_rows = write_latest_before(&mut encoder, recording_id, latest)?;
+        if num_rows > 0 {
+            num_rows_written += num_rows;
+            num_messages_written += 1;
+        }
+    }
+
+    encoder.finish()?;
+
+    if let Some(time_filter) = time_filter {
+        if time_filter.resolved.is_none() {
+            re_log::warn!(
+                "Timeline {:?} was not found in {in_path:?}: only timeless data was kept",
+                time_filter.timeline_name
+            );
+        }
+    }
+
+    re_log::info!(
+        "Wrote {} of {} messages ({} of {} rows) to {out_path:?}",
+        re_format::format_number(num_messages_written as _),
+        re_format::format_number(num_messages_read as _),
+        re_format::format_number(num_rows_written as _),
+        re_format::format_number(num_rows_read as _),
+    );
+
+    Ok(())
+}
+
+// --- Merge ---
+
+/// How to remap all input files onto a single recording, as requested by `--single-recording`.
+struct RecordingRemap {
+    recording_id: RecordingId,
+
+    /// The entity path prefix of each input file.
+    prefixes: Vec<EntityPath>,
+}
+
+impl RecordingRemap {
+    fn new(paths: &[PathBuf], prefixes: &[String]) -> anyhow::Result<Self> {
+        let prefixes = if prefixes.is_empty() {
+            paths
+                .iter()
+                .map(|path| {
+                    let stem = path
+                        .file_stem()
+                        .with_context(|| format!("{path:?} has no file name"))?;
+                    Ok(EntityPath::new(vec![stem
+                        .to_string_lossy()
+                        .as_ref()
+                        .into()]))
+                })
+                .collect::<anyhow::Result<Vec<_>>>()?
+        } else {
+            anyhow::ensure!(
+                prefixes.len() == paths.len(),
+                "Got {} input files but {} prefixes: expected one prefix per input file",
+                paths.len(),
+                prefixes.len()
+            );
+            prefixes
+                .iter()
+                .map(|prefix| parse_entity_path_arg(prefix))
+                .collect::<anyhow::Result<Vec<_>>>()?
+        };
+
+        let mut unique_prefixes = BTreeSet::new();
+        for prefix in &prefixes {
+            anyhow::ensure!(
+                unique_prefixes.insert(prefix),
+                "Several input files would be merged under the entity path {prefix}: use --prefix to disambiguate"
+            );
+        }
+
+        Ok(Self {
+            recording_id: RecordingId::random(),
+            prefixes,
+        })
+    }
+}
+
+fn merge_rrds(
+    in_paths: &[PathBuf],
+    output: &OutputArgs,
+    remap: Option<RecordingRemap>,
+) -> anyhow::Result<()> {
+    let out_path = &output.output;
+    anyhow::ensure!(
+        !in_paths.contains(out_path),
+        "The merged file must be written to a different path than any of its inputs"
+    );
+
+    let mut encoder = output.create_encoder()?;
+
+    let mut recording_ids = BTreeSet::new();
+    let mut has_begun = false;
+    let mut goodbye = None;
+    let mut num_messages = 0u64;
+
+    for (i, in_path) in in_paths.iter().enumerate() {
+        let file =
+            std::fs::File::open(in_path).with_context(|| format!("Failed to open {in_path:?}"))?;
+        let decoder = Decoder::new(file).with_context(|| format!("{in_path:?}"))?;
+
+        for msg in decoder {
+            let msg = match msg {
+                Ok(msg) => msg,
+                Err(err) => {
+                    re_log::warn_once!("Failed to decode message in {in_path:?}: {err}");
+                    continue;
+                }
+            };
+
+            if let Some(recording_id) = msg.recording_id() {
+                recording_ids.insert(*recording_id);
+            }
+
+            let Some(remap) = &remap else {
+                encoder.append(&msg)?;
+                num_messages += 1;
+                continue;
+            };
+            let prefix = &remap.prefixes[i];
+
+            let msg = match msg {
+                LogMsg::BeginRecordingMsg(mut msg) => {
+                    // The first recording we come across gives its metadata to the merged one.
+                    if std::mem::replace(&mut has_begun, true) {
+                        continue;
+                    }
+                    msg.info.recording_id = remap.recording_id;
+                    LogMsg::BeginRecordingMsg(msg)
+                }
+
+                LogMsg::EntityPathOpMsg(_, mut msg) => {
+                    let recursive = matches!(msg.path_op, re_log_types::PathOp::ClearRecursive(_));
+                    msg.path_op = re_log_types::PathOp::clear(
+                        recursive,
+                        prefix.join(msg.path_op.entity_path()),
+                    );
+                    LogMsg::EntityPathOpMsg(remap.recording_id, msg)
+                }
+
+                LogMsg::ArrowMsg(_, msg) => {
+                    let table = DataTable::try_from(&msg)?;
+                    let rows = table.as_rows().map(|mut row| {
+                        row.entity_path = prefix.join(&row.entity_path);
+                        row
+                    });
+                    let table = DataTable::from_rows(table.table_id, rows);
+                    LogMsg::ArrowMsg(remap.recording_id, (&table).try_into()?)
+                }
+
+                // Only say goodbye once everything has been merged.
+                LogMsg::Goodbye(msg_id) => {
+                    goodbye = Some(msg_id);
+                    continue;
+                }
+            };
+
+            encoder.append(&msg)?;
+            num_messages += 1;
+        }
+    }
+
+    if let Some(msg_id) = goodbye {
+        encoder.append(&LogMsg::Goodbye(msg_id))?;
+        num_messages += 1;
+    }
+
+    encoder.finish()?;
+
+    if let Some(remap) = &remap {
+        re_log::info!(
+            "Merged {} recordings from {} files into recording {} ({} messages) in {out_path:?}",
+            recording_ids.len(),
+            in_paths.len(),
+            remap.recording_id,
+            re_format::format_number(num_messages as _),
+        );
+    } else {
+        re_log::info!(
+            "Merged {} recordings from {} files ({} messages) into {out_path:?}",
+            recording_ids.len(),
+            in_paths.len(),
+            re_format::format_number(num_messages as _),
+        );
+    }
+
+    Ok(())
+}
+
+// --- Repair ---
+
+fn repair_rrd(in_path: &Path, output: &OutputArgs) -> anyhow::Result<()> {
+    let out_path = &output.output;
+    anyhow::ensure!(
+        in_path != out_path.as_path(),
+        "The repaired file must be written to a different path"
+    );
+
+    let file = std::fs::File::open(in_path).context("Failed to open file")?;
+    let mut decoder = Decoder::new_recovering(file)?;
+    let mut encoder = output.create_encoder()?;
+
+    for msg in decoder.by_ref() {
+        encoder.append(&msg?)?;
+    }
+    encoder.finish()?;
+
+    let report = decoder
+        .recovery_report()
+        .expect("decoder was created in recovery mode");
+
+    for skipped in &report.skipped {
+        re_log::warn!(
+            "Skipped {}: {}",
+            re_format::format_bytes(skipped.num_bytes as _),
+            skipped.error
+        );
+    }
+
+    if report.is_clean() {
+        re_log::info!(
+            "{in_path:?} was intact: copied all {} messages to {out_path:?}",
+            re_format::format_number(report.num_messages as _),
+        );
+    } else {
+        re_log::info!(
+            "Recovered {} messages to {out_path:?}. {} of data in {} places could not be decoded",
+            re_format::format_number(report.num_messages as _),
+            re_format::format_bytes(report.num_bytes_skipped() as _),
+            report.skipped.len(),
+        );
+    }
+
+    Ok(())
+}
+
+// --- Recompress ---
+
+fn recompress_rrd(in_path: &Path, output: &OutputArgs) -> anyhow::Result<()> {
+    let out_path = &output.output;
+    anyhow::ensure!(
+        in_path != out_path.as_path(),
+        "The recompressed file must be written to a different path"
+    );
+
+    let file = std::fs::File::open(in_path).context("Failed to open file")?;
+    let decoder = Decoder::new(file)?;
+    let options = output.options_like(decoder.options());
+    let mut encoder = output.create_encoder_with(options)?;
+
+    let mut num_messages = 0;
+    for msg in decoder {
+        encoder.append(&msg?)?;
+        num_messages += 1;
+    }
+    encoder.finish()?;
+    drop(encoder);
+
+    let file_size = |path: &Path| -> anyhow::Result<u64> {
+        Ok(std::fs::metadata(path)
+            .with_context(|| format!("{path:?}"))?
+            .len())
+    };
+    let in_size = file_size(in_path)?;
+    let out_size = file_size(out_path)?;
+
+    re_log::info!(
+        "Recompressed {} messages to {out_path:?} with {}: {} -> {} ({:.1}%)",
+        re_format::format_number(num_messages),
+        options.compression,
+        re_format::format_bytes(in_size as _),
+        re_format::format_bytes(out_size as _),
+        100.0 * out_size as f64 / in_size.max(1) as f64,
+    );
+
+    Ok(())
+}
+
+// --- Tests ---
+
+#[cfg(test)]
+fn test_timepoint(frame_nr: Option<i64>) -> TimePoint {
+    use re_log_types::datagen::build_frame_nr;
+
+    frame_nr.map_or_else(TimePoint::timeless, |frame_nr| {
+        TimePoint::from([build_frame_nr(frame_nr.into())])
+    })
+}
+
+/// A row with a single point, timeless if `frame_nr` is `None`.
+#[cfg(test)]
+fn test_row(ent_path: &str, frame_nr: Option<i64>) -> DataRow {
+    use re_log_types::datagen::build_some_point2d;
+
+    DataRow::from_cells1(
+        MsgId::random(),
+        ent_path,
+        test_timepoint(frame_nr),
+        1,
+        build_some_point2d(1),
+    )
+}
+
+#[cfg(test)]
+fn test_clear(recording_id: RecordingId, ent_path: &str, frame_nr: Option<i64>) -> LogMsg {
+    LogMsg::EntityPathOpMsg(
+        recording_id,
+        re_log_types::EntityPathOpMsg {
+            msg_id: MsgId::random(),
+            time_point: test_timepoint(frame_nr),
+            path_op: re_log_types::PathOp::clear(true, ent_path.into()),
+        },
+    )
+}
+
+#[cfg(test)]
+fn test_output(path: &Path) -> OutputArgs {
+    OutputArgs {
+        output: path.to_owned(),
+        seekable: None,
+        arrow_ipc: None,
+        compression: None,
+    }
+}
+
+/// A new recording holding each of `rows` in a message of its own.
+#[cfg(test)]
+fn test_recording(rows: impl IntoIterator<Item = DataRow>) -> (RecordingId, Vec<LogMsg>) {
+    use re_log_types::{ApplicationId, BeginRecordingMsg, RecordingSource, Time};
+
+    let recording_id = RecordingId::random();
+    let begin = LogMsg::BeginRecordingMsg(BeginRecordingMsg {
+        msg_id: MsgId::random(),
+        info: RecordingInfo {
+            application_id: ApplicationId("test".to_owned()),
+            recording_id,
+            is_official_example: false,
+            started: Time::now(),
+            recording_source: RecordingSource::RustSdk {
+                rustc_version: String::new(),
+                llvm_version: String::new(),
+            },
+        },
+    });
+    let messages = std::iter::once(begin)
+        .chain(
+            rows.into_iter()
+                .map(|row| LogMsg::ArrowMsg(recording_id, (&row.into_table()).try_into().unwrap())),
+        )
+        .collect();
+    (recording_id, messages)
+}
+
+#[cfg(test)]
+fn write_rrd(path: &Path, messages: &[LogMsg]) {
+    let file = std::fs::File::create(path).unwrap();
+    re_log_encoding::encoder::encode(messages.iter(), file).unwrap();
+}
+
+#[cfg(test)]
+fn read_rrd(path: &Path) -> Vec<LogMsg> {
+    let file = std::fs::File::open(path).unwrap();
+    Decoder::new(file)
+        .unwrap()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap()
+}
+
+/// All the rows of all the data messages, in order.
+#[cfg(test)]
+fn rows_in(messages: &[LogMsg]) -> Vec<DataRow> {
+    messages
+        .iter()
+        .filter_map(|msg| match msg {
+            LogMsg::ArrowMsg(_, msg) => Some(DataTable::try_from(msg).unwrap()),
+            _ => None,
+        })
+        .flat_map(|table| table.as_rows().collect::<Vec<_>>())
+        .collect()
+}
+
+#[test]
+fn test_rrd_info() {
+    use re_log_types::component_types::Point2D;
+
+    let (recording_id, mut messages) = test_recording([
+        test_row("points", None),
+        test_row("points", Some(1)),
+        test_row("points", Some(3)),
+    ]);
+    messages.push(test_clear(recording_id, "points", Some(2)));
+    messages.push(test_clear(recording_id, "points", Some(4)));
+
+    let mut file = vec![];
+    re_log_encoding::encoder::encode_with_options(
+        messages.iter(),
+        &mut file,
+        EncodingOptions {
+            layout: Layout::Stream,
+            serializer: Serializer::MsgPack,
+            compression: Compression::Off,
+        },
+    )
+    .unwrap();
+    // Without compression, this corrupts the last message, which fails its checksum.
+    *file.last_mut().unwrap() ^= 0xff;
+
+    let directory = tempfile::tempdir().unwrap();
+    let path = directory.path().join("info.rrd");
+    std::fs::write(&path, file).unwrap();
+
+    let info = RrdInfo::from_path(&path).unwrap();
+    assert_eq!(5, info.num_messages);
+    assert_eq!(1, info.num_path_ops);
+    assert_eq!(1, info.num_skipped_messages);
+    assert_eq!(None, info.num_frames);
+    assert_eq!(
+        vec![recording_id],
+        info.recordings
+            .iter()
+            .map(|info| info.recording_id)
+            .collect::<Vec<_>>()
+    );
+    assert_eq!(
+        Some(&TimeRange::new(1.into(), 3.into())),
+        info.timelines.get(&Timeline::new_sequence("frame_nr"))
+    );
+
+    let entity = &info.entities[&EntityPath::from("points")];
+    assert_eq!(3, entity.num_rows);
+    assert_eq!(1, entity.num_timeless_rows);
+    assert!(entity.components.contains(&Point2D::name()));
+
+    let summary = info.to_string();
+    assert!(
+        summary.contains("Messages: 5 (1 path operations)"),
+        "{summary}"
+    );
+    assert!(summary.contains("Skipped: 1 messages"), "{summary}");
+}
+
+#[test]
+fn test_entity_filter() {
+    let filter =
+        EntityFilter::new(&["world/**".to_owned()], &["/world/hidden".to_owned()]).unwrap();
+
+    assert!(filter.keeps(&"world".into()));
+    assert!(filter.keeps(&"world/points".into()));
+    assert!(!filter.keeps(&"world/hidden".into()));
+    assert!(!filter.keeps(&"world/hidden/points".into()));
+    assert!(!filter.keeps(&"elsewhere".into()));
+
+    // Recursively clearing an ancestor of an included entity clears that entity too.
+    assert!(filter.keeps_op(&EntityPath::root(), true));
+    assert!(!filter.keeps_op(&EntityPath::root(), false));
+    assert!(filter.keeps_op(&"world/points".into(), false));
+    assert!(!filter.keeps_op(&"world/hidden".into(), true));
+    assert!(!filter.keeps_op(&"elsewhere".into(), true));
+}
+
+#[test]
+fn test_filter_rrd() {
+    let rows = [
+        test_row("world/timeless", None),
+        test_row("world/points", Some(2)),
+        // Logged after frame #2, but older: must not make it into the state before the range.
+        test_row("world/points", Some(1)),
+        test_row("world/points", Some(5)),
+        test_row("world/points", Some(8)),
+        test_row("world/hidden", Some(5)),
+        test_row("elsewhere", Some(5)),
+    ];
+    let row_ids = rows.iter().map(DataRow::row_id).collect::<Vec<_>>();
+    let (recording_id, mut messages) = test_recording(rows);
+    messages.extend([
+        test_clear(recording_id, "world", Some(3)),
+        test_clear(recording_id, "world", Some(9)),
+        test_clear(recording_id, "world/hidden", Some(5)),
+        test_clear(recording_id, "elsewhere", Some(5)),
+    ]);
+
+    let directory = tempfile::tempdir().unwrap();
+    let in_path = directory.path().join("in.rrd");
+    let out_path = directory.path().join("out.rrd");
+    write_rrd(&in_path, &messages);
+
+    let entity_filter =
+        EntityFilter::new(&["world".to_owned()], &["world/hidden/**".to_owned()]).unwrap();
+    let time_filter = TimeFilter::new(
+        &in_path,
+        "frame_nr",
+        Some(TimeBound::Sequence(4)),
+        Some(TimeBound::Sequence(6)),
+    )
+    .unwrap();
+
+    // Writing to the input would truncate it before it is read.
+    assert!(filter_rrd(
+        &in_path,
+        &test_output(&in_path),
+        &entity_filter,
+        Some(&time_filter)
+    )
+    .is_err());
+    assert_eq!(messages, read_rrd(&in_path));
+
+    filter_rrd(
+        &in_path,
+        &test_output(&out_path),
+        &entity_filter,
+        Some(&time_filter),
+    )
+    .unwrap();
+    let filtered = read_rrd(&out_path);
+    assert_eq!(messages[0], filtered[0]);
+
+    // The timeless data, then the state right before frame #4, then the data within the range.
+    assert_eq!(
+        vec![row_ids[0], row_ids[1], row_ids[3]],
+        rows_in(&filtered)
+            .iter()
+            .map(DataRow::row_id)
+            .collect::<Vec<_>>()
+    );
+
+    // Only the clear that precedes the range affects what's kept.
+    let clears = filtered
+        .iter()
+        .filter_map(|msg| match msg {
+            LogMsg::EntityPathOpMsg(_, msg) => Some(msg),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(1, clears.len());
+    assert_eq!(&EntityPath::from("world"), clears[0].path_op.entity_path());
+    assert_eq!(test_timepoint(Some(3)), clears[0].time_point);
+}
+
+#[test]
+fn test_merge_rrds() {
+    let (_, arm) = test_recording([test_row("points", Some(1))]);
+    let (_, base) = test_recording([test_row("points", Some(2)), test_row("pose", None)]);
+
+    let directory = tempfile::tempdir().unwrap();
+    let arm_path = directory.path().join("arm.rrd");
+    let base_path = directory.path().join("base.rrd");
+    let out_path = directory.path().join("merged.rrd");
+    write_rrd(&arm_path, &arm);
+    write_rrd(&base_path, &base);
+    let in_paths = vec![arm_path, base_path];
+
+    let remap = RecordingRemap::new(&in_paths, &[]).unwrap();
+    let recording_id = remap.recording_id;
+    merge_rrds(&in_paths, &test_output(&out_path), Some(remap)).unwrap();
+    let merged = read_rrd(&out_path);
+
+    let recording_ids = merged
+        .iter()
+        .filter_map(LogMsg::recording_id)
+        .collect::<BTreeSet<_>>();
+    assert_eq!(BTreeSet::from([&recording_id]), recording_ids);
+    assert_eq!(
+        1,
+        merged
+            .iter()
+            .filter(|msg| matches!(msg, LogMsg::BeginRecordingMsg(_)))
+            .count()
+    );
+
+    // Each input file ends up under its own file name.
+    assert_eq!(
+        vec![
+            EntityPath::from("arm/points"),
+            EntityPath::from("base/points"),
+            EntityPath::from("base/pose"),
+        ],
+        rows_in(&merged)
+            .iter()
+            .map(|row| row.entity_path().clone())
+            .collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn test_merge_rrds_rejects_ambiguous_paths() {
+    let paths = vec![
+        PathBuf::from("left/run.rrd"),
+        PathBuf::from("right/run.rrd"),
+    ];
+    assert!(RecordingRemap::new(&paths, &[]).is_err());
+    assert!(RecordingRemap::new(&paths, &["run".to_owned(), "run/".to_owned()]).is_err());
+    assert!(RecordingRemap::new(&paths, &["left".to_owned(), "right".to_owned()]).is_ok());
+
+    // Writing to one of the inputs would truncate it before it is read.
+    let (_, messages) = test_recording([test_row("points", Some(1))]);
+    let directory = tempfile::tempdir().unwrap();
+    let path = directory.path().join("run.rrd");
+    write_rrd(&path, &messages);
+    assert!(merge_rrds(&[path.clone()], &test_output(&path), None).is_err());
+    assert_eq!(messages, read_rrd(&path));
+}
@@ -6,6 +6,8 @@ use re_smart_channel::Receiver;
 use anyhow::Context as _;
 use clap::Subcommand;
 
+use crate::rrd::{run_rrd, RrdCommands, TimeBound};
+
 #[cfg(feature = "web_viewer")]
 use crate::web_viewer::host_web_viewer;
 
@@ -41,11 +43,36 @@ struct Args {
     version: bool,
 
     /// Either a path to a `.rrd` file to load, an http url to an `.rrd` file,
-    /// or a websocket url to a Rerun Server from which to read data
+    /// a websocket url to a Rerun Server from which to read data,
+    /// or `-` to read an `.rrd` stream from stdin.
     ///
     /// If none is given, a server will be hosted which the Rerun SDK can connect to.
     url_or_path: Option<String>,
 
+    /// When loading an `.rrd` file: the name of the timeline that `--from` and `--to` refer to,
+    /// e.g. `log_time` or `frame_nr`.
+    ///
+    /// Files written with `--seekable` (see `rerun rrd recompress`) then only have the parts
+    /// needed for that time range read from disk, which makes it possible to jump straight into
+    /// the middle of huge recordings.
+    ///
+    /// Example: `rerun big.rrd --timeline log_time --from 45m --to 50m`
+    #[clap(long, requires = "url_or_path")]
+    timeline: Option<String>,
+
+    /// With `--timeline`: start of the time range to load (inclusive).
+    ///
+    /// Either a sequence number for sequence timelines (e.g. `42`), or a duration since the
+    /// earliest time on the timeline for temporal timelines (e.g. `45m`).
+    #[clap(long, requires = "timeline")]
+    from: Option<TimeBound>,
+
+    /// With `--timeline`: end of the time range to load (inclusive).
+    ///
+    /// Same format as `--from`.
+    #[clap(long, requires = "timeline")]
+    to: Option<TimeBound>,
+
     /// What TCP port do we listen to (for SDK:s to connect to)?
     #[cfg(feature = "server")]
     #[clap(long, default_value_t = re_sdk_comms::DEFAULT_SERVER_PORT)]
@@ -57,9 +84,27 @@ struct Args {
     web_viewer: bool,
 
     /// Stream incoming log events to an .rrd file at the given path.
+    ///
+    /// Use `-` to write the .rrd stream to stdout instead.
     #[clap(long)]
     save: Option<String>,
 
+    /// With `--save`: move on to a new file once the current one reaches this size, e.g. `1GB`.
+    ///
+    /// The files are then named after the `--save` path with a running index, e.g. `recording_0000.rrd`.
+    #[clap(long, requires = "save")]
+    save_max_size: Option<String>,
+
+    /// With `--save`: move on to a new file once the current one has been written to for this long, e.g. `1h`.
+    ///
+    /// The files are then named after the `--save` path with a running index, e.g. `recording_0000.rrd`.
+    #[clap(long, requires = "save")]
+    save_max_duration: Option<String>,
+
+    /// With `--save`: only keep the newest this many files, deleting the oldest ones.
+    #[clap(long, requires = "save")]
+    save_max_files: Option<usize>,
+
     /// Start with the puffin profiler running.
     #[clap(long)]
     profile: bool,
@@ -72,6 +117,13 @@ struct Args {
     #[clap(long)]
     memory_limit: Option<String>,
 
+    /// A directory where the Rerun Viewer can spill old data to, instead of keeping it all in RAM.
+    ///
+    /// Old data is then memory-mapped back from disk whenever it is needed.
+    /// Spilled data is deleted when the viewer is closed.
+    #[clap(long)]
+    spill_dir: Option<std::path::PathBuf>,
+
     /// Set a maximum input latency, e.g. "200ms" or "10s".
     ///
     /// If we go over this, we start dropping packets.
@@ -92,6 +144,10 @@ enum Commands {
     #[cfg(all(feature = "analytics"))]
     #[command(subcommand)]
     Analytics(AnalyticsCommands),
+
+    /// Manipulate the contents of .rrd files.
+    #[command(subcommand)]
+    Rrd(RrdCommands),
 }
 
 #[derive(Debug, Clone, Subcommand)]
@@ -191,8 +247,7 @@ where
         match commands {
             #[cfg(all(feature = "analytics"))]
             Commands::Analytics(analytics) => run_analytics(analytics).map_err(Into::into),
-            #[cfg(not(all(feature = "analytics")))]
-            _ => Ok(()),
+            Commands::Rrd(rrd) => run_rrd(rrd),
         }
     } else {
         run_impl(build_info, call_source, args).await
@@ -255,6 +310,7 @@ async fn run_impl(
             re_memory::MemoryLimit::parse(l)
                 .unwrap_or_else(|err| panic!("Bad --memory-limit: {err}"))
         }),
+        spill_directory: args.spill_dir.clone(),
     };
 
     let (shutdown_rx, shutdown_bool) = setup_ctrl_c_handler();
@@ -267,7 +323,15 @@ async fn run_impl(
             }
             ArgumentCategory::RrdFilePath(path) => {
                 re_log::info!("Loading {path:?}…");
-                load_file_to_channel(&path).with_context(|| format!("{path:?}"))?
+                let time_window = args
+                    .timeline
+                    .as_ref()
+                    .map(|timeline| (timeline.as_str(), args.from, args.to));
+                load_file_to_channel(&path, time_window).with_context(|| format!("{path:?}"))?
+            }
+            ArgumentCategory::Stdin => {
+                re_log::info!("Reading from stdin…");
+                load_stdin_to_channel()
             }
             ArgumentCategory::WebSocketAddr(rerun_server_ws_url) => {
                 // We are connecting to a server at a websocket address:
@@ -320,8 +384,19 @@ async fn run_impl(
 
     // Now what do we do with the data?
 
-    if let Some(rrd_path) = args.save {
-        Ok(stream_to_rrd(&rx, &rrd_path.into(), &shutdown_bool)?)
+    if args.save.as_deref() == Some("-") {
+        if parse_file_rotation(&args)?.is_enabled() {
+            anyhow::bail!("Can't rotate files when writing to stdout");
+        }
+        Ok(stream_to_stdout(&rx, &shutdown_bool)?)
+    } else if let Some(rrd_path) = args.save.clone() {
+        let rotation = parse_file_rotation(&args)?;
+        Ok(stream_to_rrd(
+            &rx,
+            &rrd_path.into(),
+            rotation,
+            &shutdown_bool,
+        )?)
     } else if args.web_viewer {
         #[cfg(feature = "web_viewer")]
         {
@@ -404,12 +479,17 @@ enum ArgumentCategory {
 
     /// A remote Rerun server.
     WebSocketAddr(String),
+
+    /// An RRD stream on stdin, given as `-`.
+    Stdin,
 }
 
 fn categorize_argument(mut uri: String) -> ArgumentCategory {
     let path = std::path::Path::new(&uri).to_path_buf();
 
-    if uri.starts_with("http") {
+    if uri == "-" {
+        ArgumentCategory::Stdin
+    } else if uri.starts_with("http") {
         ArgumentCategory::RrdHttpUrl(uri)
     } else if uri.starts_with("ws") {
         ArgumentCategory::WebSocketAddr(uri)
@@ -450,10 +530,58 @@ fn native_viewer_connect_to_ws_url(
     Ok(())
 }
 
-fn load_file_to_channel(path: &std::path::Path) -> anyhow::Result<Receiver<LogMsg>> {
+/// Streams the contents of the `.rrd` file at `path` into a channel.
+///
+/// With a `time_window` (timeline name, `--from`, `--to`), seekable files only have the parts
+/// needed for that time window decoded.
+fn load_file_to_channel(
+    path: &std::path::Path,
+    time_window: Option<(&str, Option<TimeBound>, Option<TimeBound>)>,
+) -> anyhow::Result<Receiver<LogMsg>> {
     use anyhow::Context as _;
-    let file = std::fs::File::open(path).context("Failed to open file")?;
-    let decoder = re_log_encoding::decoder::Decoder::new(file)?;
+    use re_log_encoding::decoder::Decoder;
+    use std::io::Seek as _;
+
+    let mut file = std::fs::File::open(path).context("Failed to open file")?;
+
+    let index = if time_window.is_some() {
+        let index = Decoder::read_index(&mut file)?;
+        file.rewind()?;
+        if index.is_none() {
+            re_log::warn!(
+                "{path:?} is not seekable: loading all of it. \
+                Use `rerun rrd recompress --seekable` to make it seekable."
+            );
+        }
+        index
+    } else {
+        None
+    };
+
+    let decoder = match (time_window, index) {
+        (Some((timeline_name, from, to)), Some(index)) => {
+            let time_ranges = index.time_ranges();
+            let found = time_ranges
+                .iter()
+                .find(|(timeline, _)| timeline.name().as_str() == timeline_name);
+            if let Some((timeline, time_range)) = found {
+                let range = TimeBound::resolve_range(timeline, *time_range, from, to)?;
+                re_log::info!(
+                    "Loading {} on timeline {:?}",
+                    timeline.format_time_range(&range),
+                    timeline.name(),
+                );
+                Decoder::new_in_time_range(file, timeline, range)?
+            } else {
+                re_log::warn!(
+                    "Timeline {timeline_name:?} not found in {path:?}: loading all of it."
+                );
+                Decoder::new(file)?
+            }
+        }
+        _ => Decoder::new(file)?,
+    };
+    let mut decoder = decoder.recovering();
 
     let (tx, rx) = re_smart_channel::smart_channel(re_smart_channel::Source::File {
         path: path.to_owned(),
@@ -463,46 +591,115 @@ fn load_file_to_channel(path: &std::path::Path) -> anyhow::Result<Receiver<LogMs
     std::thread::Builder::new()
         .name("rrd_file_reader".into())
         .spawn(move || {
-            for msg in decoder {
-                match msg {
-                    Ok(msg) => {
-                        tx.send(msg).ok();
-                    }
+            for msg in decoder.by_ref().flatten() {
+                tx.send(msg).ok();
+            }
+
+            if let Some(report) = decoder.recovery_report() {
+                if !report.is_clean() {
+                    re_log::warn!(
+                        "{path:?} is damaged: {} could not be decoded. Run `rerun rrd repair` for details.",
+                        re_format::format_bytes(report.num_bytes_skipped() as _),
+                    );
+                }
+            }
+        })
+        .expect("Failed to spawn thread");
+
+    Ok(rx)
+}
+
+fn load_stdin_to_channel() -> Receiver<LogMsg> {
+    let (tx, rx) = re_smart_channel::smart_channel(re_smart_channel::Source::Stdin);
+
+    std::thread::Builder::new()
+        .name("rrd_stdin_reader".into())
+        .spawn(move || {
+            // Whoever is writing to stdin may take their time, so wait for the header here
+            // rather than on the main thread.
+            let mut decoder =
+                match re_log_encoding::decoder::Decoder::new_recovering(std::io::stdin()) {
+                    Ok(decoder) => decoder,
                     Err(err) => {
-                        re_log::warn_once!("Failed to decode message in {path:?}: {err}");
+                        re_log::error!("Failed to read log stream from stdin: {err}");
+                        return;
                     }
+                };
+
+            for msg in decoder.by_ref().flatten() {
+                tx.send(msg).ok();
+            }
+
+            if let Some(report) = decoder.recovery_report() {
+                if !report.is_clean() {
+                    re_log::warn!(
+                        "The stream on stdin is damaged: {} could not be decoded.",
+                        re_format::format_bytes(report.num_bytes_skipped() as _),
+                    );
                 }
             }
         })
         .expect("Failed to spawn thread");
 
-    Ok(rx)
+    rx
+}
+
+fn parse_file_rotation(args: &Args) -> anyhow::Result<re_log_encoding::FileRotation> {
+    let max_bytes = args
+        .save_max_size
+        .as_ref()
+        .map(|size| {
+            re_format::parse_bytes(size)
+                .and_then(|bytes| u64::try_from(bytes).ok())
+                .ok_or_else(|| anyhow::anyhow!("Bad --save-max-size: {size:?}"))
+        })
+        .transpose()?;
+
+    let max_duration = args
+        .save_max_duration
+        .as_ref()
+        .map(|duration| {
+            re_format::parse_duration(duration)
+                .map(std::time::Duration::from_secs_f32)
+                .map_err(|err| anyhow::anyhow!("Bad --save-max-duration: {err}"))
+        })
+        .transpose()?;
+
+    let rotation = re_log_encoding::FileRotation {
+        max_bytes,
+        max_duration,
+        max_files: args.save_max_files,
+    };
+
+    if rotation.max_files.is_some() && !rotation.is_enabled() {
+        anyhow::bail!("--save-max-files requires --save-max-size or --save-max-duration");
+    }
+
+    Ok(rotation)
 }
 
 fn stream_to_rrd(
     rx: &re_smart_channel::Receiver<LogMsg>,
     path: &std::path::PathBuf,
+    rotation: re_log_encoding::FileRotation,
     shutdown_bool: &Arc<AtomicBool>,
-) -> Result<(), re_log_encoding::FileSinkError> {
-    use re_log_encoding::FileSinkError;
+) -> Result<(), re_log_encoding::SinkError> {
     use re_smart_channel::RecvTimeoutError;
 
-    if path.exists() {
+    if !rotation.is_enabled() && path.exists() {
         re_log::warn!("Overwriting existing file at {path:?}");
     }
 
     re_log::info!("Saving incoming log stream to {path:?}. Abort with Ctrl-C.");
 
-    let file =
-        std::fs::File::create(path).map_err(|err| FileSinkError::CreateFile(path.clone(), err))?;
-    let mut encoder = re_log_encoding::encoder::Encoder::new(file)?;
+    let sink = re_log_encoding::FileSink::with_rotation(path.clone(), rotation)?;
 
     while !shutdown_bool.load(std::sync::atomic::Ordering::Relaxed) {
         // We wake up and poll shutdown_bool every now and then.
         // This is far from elegant, but good enough.
         match rx.recv_timeout(std::time::Duration::from_millis(500)) {
             Ok(log_msg) => {
-                encoder.append(&log_msg)?;
+                sink.send(log_msg);
             }
             Err(RecvTimeoutError::Timeout) => {}
             Err(RecvTimeoutError::Disconnected) => {
@@ -510,15 +707,51 @@ fn stream_to_rrd(
                 break;
             }
         }
+
+        if sink.has_failed() {
+            break;
+        }
     }
 
-    encoder.finish()?;
+    // Waits for everything to be written.
+    sink.finish()?;
 
     re_log::info!("File saved to {path:?}");
 
     Ok(())
 }
 
+fn stream_to_stdout(
+    rx: &re_smart_channel::Receiver<LogMsg>,
+    shutdown_bool: &Arc<AtomicBool>,
+) -> Result<(), re_log_encoding::SinkError> {
+    use re_smart_channel::RecvTimeoutError;
+
+    re_log::info!("Writing incoming log stream to stdout. Abort with Ctrl-C.");
+
+    let sink = re_log_encoding::StdoutSink::new()?;
+
+    while !shutdown_bool.load(std::sync::atomic::Ordering::Relaxed) {
+        match rx.recv_timeout(std::time::Duration::from_millis(500)) {
+            Ok(log_msg) => {
+                sink.send(log_msg);
+            }
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => {
+                re_log::info!("Log stream disconnected, stopping.");
+                break;
+            }
+        }
+
+        if sink.has_failed() {
+            break;
+        }
+    }
+
+    // Waits for everything to be written.
+    sink.finish()
+}
+
 #[cfg(feature = "server")]
 fn parse_max_latency(max_latency: Option<&String>) -> f32 {
     max_latency.as_ref().map_or(f32::INFINITY, |time| {
@@ -172,7 +172,7 @@ impl PythonSession {
     pub fn save(
         &mut self,
         path: impl Into<std::path::PathBuf>,
-    ) -> Result<(), rerun::sink::FileSinkError> {
+    ) -> Result<(), rerun::sink::SinkError> {
         if !self.enabled {
             re_log::debug!("Rerun disabled - call to save() ignored");
             return Ok(());